@@ -0,0 +1,42 @@
+use anyhow::{Context, Result};
+use log::{error, warn};
+use std::process::Command;
+use std::thread;
+
+use crate::config::AlertConfig;
+use crate::db::LogEntry;
+
+/// Raise an alert for a log entry as soon as it is seen
+/// The alert is always logged; when a command is configured it is started in
+/// the background with the entry in SYSTERS_ALERT_* environment variables
+pub fn send_alert(entry: &LogEntry, config: &AlertConfig) -> Result<()> {
+    error!(
+        "ALERT [{}] {}: {}",
+        entry.level,
+        entry.source,
+        entry.headline()
+    );
+
+    let Some(ref command) = config.command else {
+        return Ok(());
+    };
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("SYSTERS_ALERT_LEVEL", &entry.level)
+        .env("SYSTERS_ALERT_SOURCE", &entry.source)
+        .env("SYSTERS_ALERT_TIMESTAMP", entry.timestamp.to_rfc3339())
+        .env("SYSTERS_ALERT_MESSAGE", &entry.message)
+        .spawn()
+        .context(format!("Failed to run alert command: {}", command))?;
+
+    // Reap the child without holding up the caller
+    thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => warn!("Alert command exited with {}", status),
+        Ok(_) => {}
+        Err(e) => warn!("Failed to wait for alert command: {}", e),
+    });
+
+    Ok(())
+}
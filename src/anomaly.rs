@@ -0,0 +1,269 @@
+use crate::db::{
+    for_each_metric, query_metric_baselines, replace_metric_baselines, HostFilter, MetricBaseline,
+};
+use crate::rollup::{metric_values, METRIC_CPU, METRIC_LOAD, METRIC_MEMORY, METRIC_PROCESSES};
+use crate::timezone::DisplayTimezone;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Timelike, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Samples an hour-of-week slot needs before it gets a baseline
+const MIN_SLOT_SAMPLES: usize = 3;
+/// Scales the MAD to be comparable with a standard deviation for
+/// normally distributed values
+const MAD_SCALE: f64 = 1.4826;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// A metric that gets baselines and how to show it
+struct BaselineMetric {
+    name: &'static str,
+    label: &'static str,
+    unit: &'static str,
+    decimals: usize,
+    /// Smallest distance from the median that counts, so a metric that
+    /// barely moves (MAD near 0) is not flagged for every small wobble
+    min_spread: f64,
+}
+
+const METRICS: [BaselineMetric; 4] = [
+    BaselineMetric { name: METRIC_CPU, label: "CPU", unit: "%", decimals: 1, min_spread: 5.0 },
+    BaselineMetric { name: METRIC_MEMORY, label: "Memory", unit: "%", decimals: 1, min_spread: 3.0 },
+    BaselineMetric { name: METRIC_LOAD, label: "Load", unit: "", decimals: 2, min_spread: 0.5 },
+    BaselineMetric { name: METRIC_PROCESSES, label: "Processes", unit: "", decimals: 0, min_spread: 10.0 },
+];
+
+/// Hours since Monday 00:00 local time, so a nightly job keeps its slot
+pub fn hour_of_week(time: DateTime<Utc>) -> u32 {
+    let local: DateTime<Local> = time.into();
+    local.weekday().num_days_from_monday() * 24 + local.hour()
+}
+
+/// Slot as "Wed 14:00"
+pub fn describe_hour_of_week(hour_of_week: u32) -> String {
+    format!(
+        "{} {:02}:00",
+        WEEKDAYS[(hour_of_week / 24) as usize % 7],
+        hour_of_week % 24
+    )
+}
+
+/// What a baseline rebuild learned from
+#[derive(Debug, Clone, Serialize)]
+pub struct BaselineRebuild {
+    /// Raw samples read
+    pub samples: usize,
+    /// Metric and hour-of-week slots with enough samples for a baseline
+    pub slots: usize,
+}
+
+/// Learn the median and MAD of each metric per hour of the week from this
+/// host's raw samples of `now - lookback..now`, replacing the stored baselines
+pub fn rebuild_baselines(
+    conn: &Connection,
+    now: DateTime<Utc>,
+    lookback: Duration,
+) -> Result<BaselineRebuild> {
+    let mut values: BTreeMap<(&'static str, u32), Vec<f64>> = BTreeMap::new();
+    let samples = for_each_metric(conn, now - lookback, now, &HostFilter::Local, |_, sample| {
+        let slot = hour_of_week(sample.timestamp);
+        for (metric, value) in metric_values(&sample) {
+            if metric_info(metric).is_some() && value.is_finite() {
+                values.entry((metric, slot)).or_default().push(value);
+            }
+        }
+        Ok(())
+    })
+    .context("Failed to read metrics for baselines")?;
+
+    let baselines: Vec<MetricBaseline> = values
+        .into_iter()
+        .filter(|(_, values)| values.len() >= MIN_SLOT_SAMPLES)
+        .map(|((metric, hour_of_week), mut values)| {
+            let center = median(&mut values);
+            let mut deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+            MetricBaseline {
+                metric: metric.to_string(),
+                hour_of_week,
+                samples: values.len(),
+                median: center,
+                mad: median(&mut deviations),
+                built_at: now,
+            }
+        })
+        .collect();
+
+    replace_metric_baselines(conn, &baselines).context("Failed to store baselines")?;
+    Ok(BaselineRebuild {
+        samples,
+        slots: baselines.len(),
+    })
+}
+
+/// Consecutive samples of one metric outside their usual range
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomaly {
+    pub metric: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub samples: usize,
+    /// Value furthest outside the expected range, and when it was seen
+    pub value: f64,
+    pub at: DateTime<Utc>,
+    /// Baseline the value was compared with
+    pub hour_of_week: u32,
+    pub median: f64,
+    pub expected_low: f64,
+    pub expected_high: f64,
+}
+
+impl Anomaly {
+    /// How far the value lies outside the expected range
+    fn excess(&self) -> f64 {
+        outside(self.value, self.expected_low, self.expected_high)
+    }
+}
+
+/// Samples of the report period that left their learned range
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalyReport {
+    /// Allowed distance from the median, in scaled MADs
+    pub mad_multiplier: f64,
+    pub baseline_built_at: DateTime<Utc>,
+    /// Metric and hour-of-week slots with a baseline
+    pub baseline_slots: usize,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Compare this host's raw samples of `start..end` with the stored baselines
+/// Returns None until baselines have been built
+pub fn detect_anomalies(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    mad_multiplier: f64,
+) -> Result<Option<AnomalyReport>> {
+    let baselines = query_metric_baselines(conn).context("Failed to read baselines")?;
+    let Some(built_at) = baselines.iter().map(|b| b.built_at).max() else {
+        return Ok(None);
+    };
+    let by_slot: HashMap<(&str, u32), &MetricBaseline> = baselines
+        .iter()
+        .map(|b| ((b.metric.as_str(), b.hour_of_week), b))
+        .collect();
+
+    let mut anomalies = Vec::new();
+    // Anomaly still growing for each metric
+    let mut open: BTreeMap<&str, Anomaly> = BTreeMap::new();
+    for_each_metric(conn, start, end, &HostFilter::Local, |_, sample| {
+        let slot = hour_of_week(sample.timestamp);
+        for (metric, value) in metric_values(&sample) {
+            let (Some(baseline), Some(info)) = (by_slot.get(&(metric, slot)), metric_info(metric))
+            else {
+                continue;
+            };
+            let spread = (mad_multiplier * MAD_SCALE * baseline.mad).max(info.min_spread);
+            let (low, high) = (baseline.median - spread, baseline.median + spread);
+            if !value.is_finite() || (low..=high).contains(&value) {
+                anomalies.extend(open.remove(metric));
+                continue;
+            }
+
+            let anomaly = Anomaly {
+                metric: metric.to_string(),
+                start: sample.timestamp,
+                end: sample.timestamp,
+                samples: 1,
+                value,
+                at: sample.timestamp,
+                hour_of_week: slot,
+                median: baseline.median,
+                expected_low: low,
+                expected_high: high,
+            };
+            match open.get_mut(metric) {
+                Some(current) => {
+                    current.end = sample.timestamp;
+                    current.samples += 1;
+                    if anomaly.excess() > current.excess() {
+                        *current = Anomaly {
+                            start: current.start,
+                            samples: current.samples,
+                            ..anomaly
+                        };
+                    }
+                }
+                None => {
+                    open.insert(metric, anomaly);
+                }
+            }
+        }
+        Ok(())
+    })
+    .context("Failed to read metrics for anomaly detection")?;
+    anomalies.extend(open.into_values());
+    anomalies.sort_by_key(|a| a.start);
+
+    Ok(Some(AnomalyReport {
+        mad_multiplier,
+        baseline_built_at: built_at,
+        baseline_slots: baselines.len(),
+        anomalies,
+    }))
+}
+
+/// One-line description with the expected range, e.g.
+/// "CPU 96.0% at 2025-11-05 14:00 (expected 4.0-16.0% for Wed 14:00)"
+pub fn describe_anomaly(anomaly: &Anomaly, timezone: DisplayTimezone) -> String {
+    let (label, unit, decimals) = metric_info(&anomaly.metric)
+        .map_or((anomaly.metric.as_str(), "", 2), |m| (m.label, m.unit, m.decimals));
+    let local_at = timezone.convert(anomaly.at);
+    // Baselines are keyed by the collecting host's local hour; the label names
+    // the same hour in the display zone, like the timestamps around it
+    let slot = local_at.weekday().num_days_from_monday() * 24 + local_at.hour();
+    let mut text = format!(
+        "{} {:.*}{} at {} (expected {:.*}-{:.*}{} for {}",
+        label,
+        decimals,
+        anomaly.value,
+        unit,
+        local_at.format("%Y-%m-%d %H:%M"),
+        decimals,
+        anomaly.expected_low.max(0.0),
+        decimals,
+        anomaly.expected_high,
+        unit,
+        describe_hour_of_week(slot)
+    );
+    if anomaly.samples > 1 {
+        let local_start = timezone.convert(anomaly.start);
+        let local_end = timezone.convert(anomaly.end);
+        text.push_str(&format!(
+            "; {} samples from {} to {}",
+            anomaly.samples,
+            local_start.format("%Y-%m-%d %H:%M"),
+            local_end.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    text.push(')');
+    text
+}
+
+fn metric_info(metric: &str) -> Option<&'static BaselineMetric> {
+    METRICS.iter().find(|m| m.name == metric)
+}
+
+fn outside(value: f64, low: f64, high: f64) -> f64 {
+    (low - value).max(value - high).max(0.0)
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
@@ -0,0 +1,191 @@
+use crate::collector::extract_timestamp;
+use crate::db::AuthEvent;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Failed SSH password/key attempt
+pub const EVENT_FAILED_LOGIN: &str = "failed_login";
+/// SSH attempt for a user that does not exist
+pub const EVENT_INVALID_USER: &str = "invalid_user";
+/// Successful SSH login
+pub const EVENT_ACCEPTED_LOGIN: &str = "accepted_login";
+/// Command run through sudo
+pub const EVENT_SUDO_COMMAND: &str = "sudo_command";
+/// Rejected sudo attempt (wrong password, not in sudoers, command not allowed)
+pub const EVENT_SUDO_FAILURE: &str = "sudo_failure";
+
+/// File names of authentication logs (Debian/Ubuntu and RHEL/Fedora)
+const AUTH_LOG_NAMES: &[&str] = &["auth.log", "secure"];
+
+/// Check whether a log path is an authentication log that the auth analyzer understands
+pub fn is_auth_log<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| AUTH_LOG_NAMES.contains(&name))
+        .unwrap_or(false)
+}
+
+/// Compiled sshd and sudo patterns (cached using OnceLock)
+struct AuthPatterns {
+    failed: Regex,
+    accepted: Regex,
+    invalid_user: Regex,
+    sudo: Regex,
+}
+
+fn get_auth_patterns() -> &'static AuthPatterns {
+    static PATTERNS: OnceLock<AuthPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| AuthPatterns {
+        // sshd[123]: Failed password for (invalid user) root from 203.0.113.9 port 4242 ssh2
+        failed: Regex::new(
+            r"sshd\[\d+\]: Failed (\S+) for (?:invalid user )?(\S+) from (\S+) port \d+",
+        )
+        .unwrap(),
+        // sshd[123]: Accepted publickey for alice from 198.51.100.7 port 51234 ssh2
+        accepted: Regex::new(r"sshd\[\d+\]: Accepted (\S+) for (\S+) from (\S+) port \d+")
+            .unwrap(),
+        // sshd[123]: Invalid user admin from 203.0.113.9 port 4242
+        invalid_user: Regex::new(r"sshd\[\d+\]: Invalid user (\S*) from (\S+)").unwrap(),
+        // sudo:    alice : TTY=pts/0 ; PWD=/home/alice ; USER=root ; COMMAND=/usr/bin/apt update
+        sudo: Regex::new(r"sudo(?:\[\d+\])?:\s+(\S+) : (.*?)COMMAND=(.*)$").unwrap(),
+    })
+}
+
+/// Parse a single auth.log line into an authentication event
+/// Returns None for lines that are not sshd login or sudo events
+pub fn parse_auth_line(line: &str, fallback_timestamp: DateTime<Utc>) -> Option<AuthEvent> {
+    let patterns = get_auth_patterns();
+    let timestamp = || extract_timestamp(line).unwrap_or(fallback_timestamp);
+    let event = |event_type: &str| AuthEvent {
+        timestamp: timestamp(),
+        event_type: event_type.to_string(),
+        username: None,
+        source_ip: None,
+        method: None,
+        command: None,
+        message: line.trim().to_string(),
+    };
+
+    if let Some(cap) = patterns.failed.captures(line) {
+        return Some(AuthEvent {
+            method: Some(cap[1].to_string()),
+            username: Some(cap[2].to_string()),
+            source_ip: Some(cap[3].to_string()),
+            ..event(EVENT_FAILED_LOGIN)
+        });
+    }
+
+    if let Some(cap) = patterns.accepted.captures(line) {
+        return Some(AuthEvent {
+            method: Some(cap[1].to_string()),
+            username: Some(cap[2].to_string()),
+            source_ip: Some(cap[3].to_string()),
+            ..event(EVENT_ACCEPTED_LOGIN)
+        });
+    }
+
+    if let Some(cap) = patterns.invalid_user.captures(line) {
+        return Some(AuthEvent {
+            username: Some(cap[1].to_string()),
+            source_ip: Some(cap[2].to_string()),
+            ..event(EVENT_INVALID_USER)
+        });
+    }
+
+    if let Some(cap) = patterns.sudo.captures(line) {
+        let details = &cap[2];
+        // Rejected attempts carry the reason before the TTY field
+        let is_failure = details.contains("NOT in sudoers")
+            || details.contains("command not allowed")
+            || details.contains("incorrect password attempt");
+        return Some(AuthEvent {
+            username: Some(cap[1].to_string()),
+            command: Some(cap[3].trim().to_string()),
+            ..event(if is_failure {
+                EVENT_SUDO_FAILURE
+            } else {
+                EVENT_SUDO_COMMAND
+            })
+        });
+    }
+
+    None
+}
+
+/// Parse an authentication log for sshd and sudo events
+pub fn collect_auth_events<P: AsRef<Path>>(
+    log_path: P,
+    max_lines: usize,
+) -> Result<Vec<AuthEvent>> {
+    let file = File::open(log_path.as_ref()).context("Failed to open auth log")?;
+    let reader = BufReader::new(file);
+    let fallback_timestamp = Utc::now();
+
+    let mut events = Vec::new();
+    for line in reader.lines().take(max_lines) {
+        if let Some(event) = parse_auth_line(&line?, fallback_timestamp) {
+            events.push(event);
+        }
+    }
+
+    Ok(events)
+}
+
+/// A run of failed logins from one address in quick succession
+#[derive(Debug, Clone, Serialize)]
+pub struct BruteForceBurst {
+    pub source_ip: String,
+    pub attempts: usize,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Find brute-force bursts: runs of at least `min_attempts` failed logins from one
+/// address where consecutive attempts are no more than `max_gap` apart
+pub fn detect_brute_force_bursts(
+    events: &[AuthEvent],
+    min_attempts: usize,
+    max_gap: Duration,
+) -> Vec<BruteForceBurst> {
+    let mut failures_by_ip: HashMap<&str, Vec<DateTime<Utc>>> = HashMap::new();
+    // sshd logs "Invalid user" and "Failed password for invalid user" for the same
+    // attempt, so only failed logins are counted
+    for event in events.iter().filter(|e| e.event_type == EVENT_FAILED_LOGIN) {
+        if let Some(ref ip) = event.source_ip {
+            failures_by_ip.entry(ip).or_default().push(event.timestamp);
+        }
+    }
+
+    let mut bursts = Vec::new();
+    for (ip, mut times) in failures_by_ip {
+        times.sort();
+
+        let mut run_start = 0;
+        for i in 1..=times.len() {
+            let run_continues = i < times.len() && times[i] - times[i - 1] <= max_gap;
+            if !run_continues {
+                let attempts = i - run_start;
+                if attempts >= min_attempts {
+                    bursts.push(BruteForceBurst {
+                        source_ip: ip.to_string(),
+                        attempts,
+                        start: times[run_start],
+                        end: times[i - 1],
+                    });
+                }
+                run_start = i;
+            }
+        }
+    }
+
+    bursts.sort_by(|a, b| b.attempts.cmp(&a.attempts).then(a.start.cmp(&b.start)));
+    bursts
+}
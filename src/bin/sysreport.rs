@@ -1,10 +1,23 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use rusqlite::Connection;
+use chrono::{DateTime, Duration, Utc};
+use clap::{Parser, Subcommand};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-use systers::reporter::{export_report, generate_report, ExportFormat};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use systers::anomaly::rebuild_baselines;
+use systers::compare::{compare_reports, export_comparison, previous_period};
+use systers::config::{Config, DEFAULT_INCIDENT_GAP_MINUTES};
+use systers::db::{init_database_with_options, open_for_reading, HostFilter};
+use systers::export::{export_table, parse_time_bound, DataFormat, ExportTable};
+use systers::forecast::ForecastOptions;
+use systers::markdown::format_markdown_summary;
+use systers::reporter::{
+    export_full_report, generate_full_report_between, ExportFormat, ReportOptions,
+};
+use systers::timeline::{build_timeline, export_timeline};
+use systers::timezone::DisplayTimezone;
+use systers::tui::{self, App};
 
 /// System Analysis Report Generator
 ///
@@ -15,21 +28,193 @@ use systers::reporter::{export_report, generate_report, ExportFormat};
 #[command(version = systers::VERSION)]
 #[command(about = "System Analysis Report Generator", long_about = None)]
 struct Args {
-    /// Number of hours to look back for analysis
-    #[arg(long, default_value_t = 24, value_name = "N")]
-    hours: i64,
+    #[command(flatten)]
+    period: Period,
 
     /// Path to database file (overrides SYSTERS_DB_PATH env var)
-    #[arg(long, value_name = "PATH")]
+    #[arg(long, value_name = "PATH", global = true)]
     db_path: Option<PathBuf>,
 
+    /// Time zone for report timestamps and for dates in time ranges: local,
+    /// UTC, an offset like +02:00 or an IANA name like Europe/Berlin
+    #[arg(long, value_name = "ZONE", global = true, default_value = "local")]
+    tz: String,
+
+    /// Host whose rows to read: local (this machine), the name of a host
+    /// imported with `systers db merge`, or all
+    #[arg(long, value_name = "HOST", global = true, default_value = "local")]
+    host: String,
+
     /// Output file path (if not specified, prints to stdout)
     #[arg(short, long, value_name = "FILE")]
     output: Option<PathBuf>,
 
-    /// Output format: text or json (default: text)
+    /// Output format: text, json, html or markdown (default: text)
     #[arg(short, long, value_name = "FORMAT", default_value = "text")]
     format: String,
+
+    /// With --format markdown, print a short summary that fits in a chat message
+    #[arg(long)]
+    compact: bool,
+
+    /// Compare with another period; 'previous' is the window of the same
+    /// length just before the report period
+    #[arg(long, value_name = "PERIOD", conflicts_with = "baseline_since")]
+    compare: Option<String>,
+
+    /// Start of a custom baseline period to compare with, in the same forms
+    /// as --since
+    #[arg(long, value_name = "TIME")]
+    baseline_since: Option<String>,
+
+    /// End of the baseline period (default: baseline start plus the report length)
+    #[arg(long, value_name = "TIME", requires = "baseline_since")]
+    baseline_until: Option<String>,
+
+    /// Days of per-mount disk usage the fill forecast is fitted on
+    /// (default: forecast.lookback_days from the config, 14)
+    #[arg(long, value_name = "DAYS")]
+    forecast_lookback: Option<i64>,
+
+    /// Flag mount points projected to be full within this many days
+    /// (default: forecast.horizon_days from the config, 30)
+    #[arg(long, value_name = "DAYS")]
+    forecast_within: Option<i64>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Time range of a report, timeline or browser session
+#[derive(clap::Args)]
+struct Period {
+    /// Number of hours to look back
+    #[arg(long, default_value_t = 24, value_name = "N")]
+    hours: i64,
+
+    /// Number of days to look back (overrides --hours); long ranges are read
+    /// from hourly or daily rollups
+    #[arg(long, value_name = "N", conflicts_with = "hours")]
+    days: Option<i64>,
+
+    /// Start of the range: RFC 3339, 'YYYY-MM-DD [HH:MM]' (in the --tz zone),
+    /// today, yesterday or a duration like 2d or 90m (overrides --hours/--days)
+    #[arg(long, value_name = "TIME", conflicts_with_all = ["hours", "days"])]
+    since: Option<String>,
+
+    /// End of the range, in the same forms as --since (default: now); without
+    /// --since the range is the --hours or --days before it
+    #[arg(long, value_name = "TIME")]
+    until: Option<String>,
+}
+
+impl Period {
+    /// Resolve to `start..end`, reading relative times against `now` and
+    /// wall-clock times in `timezone`
+    fn range(
+        &self,
+        now: DateTime<Utc>,
+        timezone: DisplayTimezone,
+    ) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let end = match self.until {
+            Some(ref until) => parse_time_bound(until, now, timezone)?,
+            None => now,
+        };
+        let start = match self.since {
+            Some(ref since) => parse_time_bound(since, now, timezone)?,
+            None => {
+                let hours = match self.days {
+                    Some(days) => days.checked_mul(24),
+                    None => Some(self.hours),
+                };
+                if hours.is_some_and(|hours| hours <= 0) {
+                    anyhow::bail!("--hours and --days must be at least 1");
+                }
+                hours
+                    .and_then(Duration::try_hours)
+                    .and_then(|age| end.checked_sub_signed(age))
+                    .ok_or_else(|| anyhow::anyhow!("--hours or --days is too long"))?
+            }
+        };
+        if start >= end {
+            anyhow::bail!(
+                "--since ({}) must be before --until ({})",
+                start.to_rfc3339(),
+                end.to_rfc3339()
+            );
+        }
+        Ok((start, end))
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export raw rows of one table for analysis in other tools
+    Export(ExportArgs),
+    /// Show threshold crossings, log error bursts, check status changes,
+    /// reboots and kernel events in order, grouped into incidents
+    Timeline {
+        #[command(flatten)]
+        period: Period,
+
+        /// Events at most this many minutes apart belong to the same incident
+        #[arg(long, value_name = "MINUTES", default_value_t = DEFAULT_INCIDENT_GAP_MINUTES)]
+        gap: i64,
+
+        /// Output format: text or json
+        #[arg(short, long, value_name = "FORMAT", default_value = "text")]
+        format: String,
+
+        /// Output file path (if not specified, prints to stdout)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Browse metrics, logs and check history interactively
+    Tui {
+        #[command(flatten)]
+        period: Period,
+    },
+    /// Manage the per-hour-of-week baselines used for anomaly detection
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineCommand,
+    },
+}
+
+/// Table, range and destination of a raw row export
+#[derive(clap::Args)]
+struct ExportArgs {
+    /// Table to export: metrics, logs or checks
+    #[arg(long, value_name = "TABLE")]
+    table: String,
+
+    /// Start of the range: RFC 3339, 'YYYY-MM-DD [HH:MM]' (in the --tz
+    /// zone), today, yesterday or a duration like 24h or 7d (default: all data)
+    #[arg(long, value_name = "TIME")]
+    since: Option<String>,
+
+    /// End of the range, in the same forms as --since (default: now)
+    #[arg(long, value_name = "TIME")]
+    until: Option<String>,
+
+    /// Output format: csv, jsonl or parquet
+    #[arg(short, long, value_name = "FORMAT", default_value = "csv")]
+    format: String,
+
+    /// Output file path (if not specified, writes to stdout)
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum BaselineCommand {
+    /// Relearn the baselines from the stored raw samples
+    Rebuild {
+        /// Days of history to learn from (default: anomaly.lookback_days
+        /// from the config, 28)
+        #[arg(long, value_name = "N")]
+        days: Option<i64>,
+    },
 }
 
 fn get_db_path(cli_path: Option<PathBuf>) -> PathBuf {
@@ -50,6 +235,8 @@ fn main() -> Result<()> {
 
     let args = Args::parse();
     let db_path = get_db_path(args.db_path);
+    let timezone: DisplayTimezone = args.tz.parse().context("Invalid --tz")?;
+    let host: HostFilter = args.host.parse().context("Invalid --host")?;
 
     if !db_path.exists() {
         eprintln!("Error: Database not found at {}", db_path.display());
@@ -67,20 +254,95 @@ fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    match args.command {
+        Some(Command::Export(export)) => return run_export(&db_path, export, timezone, &host),
+        Some(Command::Timeline {
+            period,
+            gap,
+            format,
+            output,
+        }) => return run_timeline(&db_path, &period, gap, &format, output, timezone, &host),
+        Some(Command::Tui { period }) => return run_tui(&db_path, &period, timezone, &host),
+        Some(Command::Baseline {
+            action: BaselineCommand::Rebuild { days },
+        }) => {
+            if !host.is_local() {
+                anyhow::bail!("Baselines are learned from local samples only; drop --host");
+            }
+            return run_baseline_rebuild(&db_path, days);
+        }
+        None => {}
+    }
+
     // Parse export format
-    let export_format = ExportFormat::from_str(&args.format)
-        .context(format!("Invalid format '{}'. Supported: text, json", args.format))?;
+    let export_format = args
+        .format
+        .parse::<ExportFormat>()
+        .context(format!("Invalid format '{}'. Supported: text, json, html, markdown", args.format))?;
+    if args.compact && export_format != ExportFormat::Markdown {
+        anyhow::bail!("--compact is only supported with --format markdown");
+    }
 
-    // Open database
-    let conn = Connection::open(&db_path).context("Failed to open database")?;
+    // Open database read-only so reports never take write locks; it must
+    // already be migrated
+    let config = Config::load().context("Failed to load configuration")?;
+    let conn = open_for_reading(&db_path, &config.database.connection_options()?)?;
 
     // Generate report
-    let (metrics, logs, system_checks) =
-        generate_report(&conn, args.hours).context("Failed to generate report")?;
+    let now = Utc::now();
+    let (start, end) = args.period.range(now, timezone)?;
+    let lookback_days = args.forecast_lookback.unwrap_or(config.forecast.lookback_days);
+    let horizon_days = args.forecast_within.unwrap_or(config.forecast.horizon_days);
+    if lookback_days <= 0 || horizon_days <= 0 {
+        anyhow::bail!("--forecast-lookback and --forecast-within must be at least 1 day");
+    }
+    let options = ReportOptions {
+        collection_interval: config.collection.interval(),
+        forecast: ForecastOptions {
+            lookback: Duration::days(lookback_days),
+            horizon: Duration::days(horizon_days),
+            warning_percent: config.thresholds.disk_warning as f64,
+        },
+        anomaly_mad_multiplier: config.anomaly.mad_multiplier,
+        error_count: config.thresholds.error_count,
+        timezone,
+        host,
+    };
+    let report = generate_full_report_between(&conn, start, end, &options)
+        .context("Failed to generate report")?;
+
+    let baseline = match (&args.compare, &args.baseline_since) {
+        (Some(period), _) if period == "previous" => Some(previous_period(start, end)),
+        (Some(period), _) => {
+            anyhow::bail!("Invalid --compare '{}'. Supported: previous", period)
+        }
+        (None, Some(since)) => {
+            let baseline_start = parse_time_bound(since, now, timezone)?;
+            let baseline_end = match args.baseline_until {
+                Some(ref until) => parse_time_bound(until, now, timezone)?,
+                None => baseline_start + (end - start),
+            };
+            if baseline_start >= baseline_end {
+                anyhow::bail!("--baseline-since must be before --baseline-until");
+            }
+            Some((baseline_start, baseline_end))
+        }
+        (None, None) => None,
+    };
 
     // Export report in the specified format
-    let report_content = export_report(&metrics, &logs, &system_checks, export_format)
-        .context("Failed to export report")?;
+    let report_content = if let Some((baseline_start, baseline_end)) = baseline {
+        if args.compact {
+            anyhow::bail!("--compact cannot be combined with a comparison");
+        }
+        let baseline = generate_full_report_between(&conn, baseline_start, baseline_end, &options)
+            .context("Failed to generate baseline report")?;
+        export_comparison(&compare_reports(baseline, report), export_format)?
+    } else if args.compact {
+        format_markdown_summary(&report)
+    } else {
+        export_full_report(&report, export_format).context("Failed to export report")?
+    };
 
     // Write to file or stdout
     if let Some(output_path) = args.output {
@@ -93,3 +355,127 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Print the incident timeline of `period`
+fn run_timeline(
+    db_path: &Path,
+    period: &Period,
+    gap: i64,
+    format: &str,
+    output: Option<PathBuf>,
+    timezone: DisplayTimezone,
+    host: &HostFilter,
+) -> Result<()> {
+    let format = format
+        .parse::<ExportFormat>()
+        .context(format!("Invalid format '{}'. Supported: text, json", format))?;
+    if gap <= 0 {
+        anyhow::bail!("--gap must be at least 1 minute");
+    }
+
+    let (start, end) = period.range(Utc::now(), timezone)?;
+
+    let config = Config::load().context("Failed to load configuration")?;
+    let conn = open_for_reading(db_path, &config.database.connection_options()?)?;
+    let timeline = build_timeline(&conn, start, end, host, Duration::minutes(gap), timezone)
+        .context("Failed to build timeline")?;
+    let content = export_timeline(&timeline, format)?;
+
+    if let Some(output_path) = output {
+        fs::write(&output_path, &content)
+            .context(format!("Failed to write timeline to {}", output_path.display()))?;
+        eprintln!("Timeline saved to: {}", output_path.display());
+    } else {
+        println!("{}", content);
+    }
+
+    Ok(())
+}
+
+/// Open the terminal UI on `period` of history
+fn run_tui(
+    db_path: &Path,
+    period: &Period,
+    timezone: DisplayTimezone,
+    host: &HostFilter,
+) -> Result<()> {
+    let (start, end) = period.range(Utc::now(), timezone)?;
+    let config = Config::load().context("Failed to load configuration")?;
+    let conn = open_for_reading(db_path, &config.database.connection_options()?)?;
+    let mut app = App::load(&conn, start, end, host)?;
+    app.timezone = timezone;
+    tui::run(app)
+}
+
+/// Relearn the anomaly baselines; opens the database for writing
+fn run_baseline_rebuild(db_path: &Path, days: Option<i64>) -> Result<()> {
+    let config = Config::load().context("Failed to load configuration")?;
+    let days = days.unwrap_or(config.anomaly.lookback_days);
+    if days <= 0 {
+        anyhow::bail!("--days must be at least 1");
+    }
+
+    let conn = init_database_with_options(db_path, &config.database.connection_options()?)?;
+    let rebuild = rebuild_baselines(&conn, Utc::now(), Duration::days(days))
+        .context("Failed to rebuild baselines")?;
+    println!(
+        "Rebuilt {} baselines (metric and hour of week) from {} samples of the last {} days",
+        rebuild.slots, rebuild.samples, days
+    );
+    if rebuild.slots == 0 {
+        println!("Each hour of the week needs at least 3 samples; collect data for a few weeks first");
+    }
+
+    Ok(())
+}
+
+/// Stream raw table rows to a file or stdout
+fn run_export(
+    db_path: &Path,
+    args: ExportArgs,
+    timezone: DisplayTimezone,
+    host: &HostFilter,
+) -> Result<()> {
+    let ExportArgs {
+        table,
+        since,
+        until,
+        format,
+        output,
+    } = args;
+    let table = table
+        .parse::<ExportTable>()
+        .context(format!("Invalid table '{}'. Supported: metrics, logs, checks", table))?;
+    let format = format
+        .parse::<DataFormat>()
+        .context(format!("Invalid format '{}'. Supported: csv, jsonl, parquet", format))?;
+
+    let now = Utc::now();
+    let start = match since {
+        Some(value) => parse_time_bound(&value, now, timezone)?,
+        None => DateTime::<Utc>::UNIX_EPOCH,
+    };
+    let end = match until {
+        Some(value) => parse_time_bound(&value, now, timezone)?,
+        None => now,
+    };
+    if start > end {
+        anyhow::bail!("--since ({}) is after --until ({})", start.to_rfc3339(), end.to_rfc3339());
+    }
+
+    let config = Config::load().context("Failed to load configuration")?;
+    let conn = open_for_reading(db_path, &config.database.connection_options()?)?;
+
+    if let Some(output_path) = output {
+        let file = fs::File::create(&output_path)
+            .context(format!("Failed to create {}", output_path.display()))?;
+        let rows = export_table(&conn, table, start, end, host, format, BufWriter::new(file))
+            .context(format!("Failed to export to {}", output_path.display()))?;
+        eprintln!("Exported {} rows to: {}", rows, output_path.display());
+    } else {
+        export_table(&conn, table, start, end, host, format, BufWriter::new(io::stdout()))
+            .context("Failed to export rows")?;
+    }
+
+    Ok(())
+}
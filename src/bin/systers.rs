@@ -0,0 +1,347 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use systers::config::Config;
+#[cfg(unix)]
+use systers::db::loose_permissions;
+use systers::db::{
+    open_database, try_writer_lock, writer_lock_holder, WriterLock, SCHEMA_VERSION,
+};
+use systers::maintenance::{backup_path, backup_to, integrity_check, table_stats, vacuum};
+use systers::merge::merge_database;
+use systers::migrations::{
+    get_schema_version, migrate_to, migration_status, pending_migrations, rollback_to,
+};
+
+/// Systers Maintenance Tool
+///
+/// Administrative commands for the Systers database.
+#[derive(Parser)]
+#[command(name = "systers")]
+#[command(version = systers::VERSION)]
+#[command(about = "Systers Maintenance Tool", long_about = None)]
+struct Args {
+    /// Path to database file (overrides SYSTERS_DB_PATH env var)
+    #[arg(long, value_name = "PATH", global = true)]
+    db_path: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Show the schema version and which migrations are applied
+    Status,
+
+    /// Apply pending migrations (the database file is backed up first)
+    Migrate {
+        /// List the migrations that would be applied without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Schema version to migrate to (default: latest)
+        #[arg(long, value_name = "VERSION")]
+        to: Option<i32>,
+    },
+
+    /// Undo applied migrations (the database file is backed up first)
+    Rollback {
+        /// Schema version to roll back to (default: one version back)
+        #[arg(long, value_name = "VERSION")]
+        to: Option<i32>,
+    },
+
+    /// Copy the database to a timestamped file while it stays in use
+    Backup {
+        /// Directory to write the backup to (default: next to the database)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Rebuild the database file to reclaim free space and refresh statistics
+    Vacuum,
+
+    /// Check the database for corruption (exits with status 1 if any is found)
+    Integrity,
+
+    /// Show row counts, time span and size of each table
+    Stats,
+
+    /// Import rows from other systers databases, e.g. copied from other hosts
+    Merge {
+        /// Database files to import
+        #[arg(required = true, value_name = "SOURCE")]
+        sources: Vec<PathBuf>,
+
+        /// Host to tag the imported rows with (default: the hostname recorded
+        /// in each source's collection runs)
+        #[arg(long, value_name = "NAME")]
+        host: Option<String>,
+    },
+}
+
+fn get_db_path(cli_path: Option<PathBuf>) -> PathBuf {
+    cli_path.unwrap_or_else(|| {
+        env::var("SYSTERS_DB_PATH")
+            .unwrap_or_else(|_| {
+                let mut path = env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+                path.push_str("/.systers.db");
+                path
+            })
+            .into()
+    })
+}
+
+/// Format a byte count for display
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Take the writer lock exclusively, failing if a collection or watcher is running
+fn writer_lock(db_path: &Path) -> Result<WriterLock> {
+    try_writer_lock(db_path)?.ok_or_else(|| {
+        anyhow!(
+            "syswriter is writing to {} (pid {}); try again when it has finished",
+            db_path.display(),
+            writer_lock_holder(db_path).map_or_else(|| "unknown".to_string(), |pid| pid.to_string())
+        )
+    })
+}
+
+fn run_db_command(command: DbCommand, db_path: &Path) -> Result<()> {
+    if !db_path.exists() {
+        return Err(anyhow!("Database not found at {}", db_path.display()));
+    }
+
+    // Keep syswriter (collections and --watch) out of every command that
+    // changes the database: its init_database would migrate a half rolled back
+    // schema straight back, and a vacuum or large import can outlast its busy
+    // timeout
+    let _lock = match command {
+        DbCommand::Migrate { dry_run: false, .. }
+        | DbCommand::Rollback { .. }
+        | DbCommand::Vacuum
+        | DbCommand::Merge { .. } => Some(writer_lock(db_path)?),
+        _ => None,
+    };
+
+    // Opened directly so that nothing is migrated implicitly
+    let config = Config::load().context("Failed to load configuration")?;
+    let options = config.database.connection_options()?;
+    let conn = open_database(db_path, &options)?;
+
+    match command {
+        DbCommand::Status => {
+            let current = get_schema_version(&conn)?;
+            println!("Database: {}", db_path.display());
+            println!("Schema version: {} (latest: {})", current, SCHEMA_VERSION);
+            #[cfg(unix)]
+            for (path, mode) in loose_permissions(db_path) {
+                println!(
+                    "Warning: {} is accessible by other users (mode {:o}); run: chmod 600 {}",
+                    path.display(),
+                    mode,
+                    path.display()
+                );
+            }
+            println!();
+            for status in migration_status(&conn)? {
+                println!(
+                    "  [{}] {:>3}  {}",
+                    if status.applied { "x" } else { " " },
+                    status.version,
+                    status.description
+                );
+            }
+        }
+        DbCommand::Migrate { dry_run, to } => {
+            let target = to.unwrap_or(SCHEMA_VERSION);
+            if target > SCHEMA_VERSION {
+                return Err(anyhow!(
+                    "Unknown schema version {} (latest: {})",
+                    target,
+                    SCHEMA_VERSION
+                ));
+            }
+
+            if dry_run {
+                let pending = pending_migrations(&conn, target)?;
+                if pending.is_empty() {
+                    println!(
+                        "Nothing to migrate: database is at schema v{}",
+                        get_schema_version(&conn)?
+                    );
+                } else {
+                    println!("Would apply {} migration(s):", pending.len());
+                    for migration in pending {
+                        println!("  {:>3}  {}", migration.version, migration.description);
+                    }
+                }
+                return Ok(());
+            }
+
+            let applied = migrate_to(&conn, target, Some(db_path))?;
+            if applied.is_empty() {
+                println!(
+                    "Nothing to migrate: database is at schema v{}",
+                    get_schema_version(&conn)?
+                );
+            } else {
+                for migration in &applied {
+                    println!("Applied {:>3}  {}", migration.version, migration.description);
+                }
+            }
+        }
+        DbCommand::Rollback { to } => {
+            let current = get_schema_version(&conn)?;
+            let target = to.unwrap_or(current - 1).max(0);
+            if target >= current {
+                println!("Nothing to roll back: database is at schema v{}", current);
+                return Ok(());
+            }
+
+            for migration in rollback_to(&conn, target, Some(db_path))? {
+                println!("Rolled back {:>3}  {}", migration.version, migration.description);
+            }
+            println!(
+                "Database is now at schema v{}; syswriter will migrate it again on its next run",
+                target
+            );
+        }
+        DbCommand::Backup { dir } => {
+            if let Some(dir) = &dir {
+                if !dir.is_dir() {
+                    return Err(anyhow!("Backup directory {} does not exist", dir.display()));
+                }
+            }
+            let path = backup_path(db_path, dir.as_deref(), "backup");
+            backup_to(&conn, &path)?;
+            println!("Backed up {} to {}", db_path.display(), path.display());
+        }
+        DbCommand::Vacuum => {
+            let (before, after) = vacuum(&conn)?;
+            println!(
+                "Vacuumed {}: {} -> {} ({} reclaimed)",
+                db_path.display(),
+                format_bytes(before),
+                format_bytes(after),
+                format_bytes(before.saturating_sub(after))
+            );
+        }
+        DbCommand::Integrity => {
+            let problems = integrity_check(&conn)?;
+            if problems.is_empty() {
+                println!("Integrity check passed: {}", db_path.display());
+            } else {
+                eprintln!("Integrity check FAILED for {}:", db_path.display());
+                for problem in &problems {
+                    eprintln!("  {}", problem);
+                }
+                drop(conn);
+                process::exit(1);
+            }
+        }
+        DbCommand::Stats => {
+            let stats = table_stats(&conn)?;
+            println!(
+                "{:<20} {:>10} {:>10}  {:<20} {:<20}",
+                "TABLE", "ROWS", "SIZE", "OLDEST", "NEWEST"
+            );
+            let format_time = |ts: Option<chrono::DateTime<chrono::Utc>>| {
+                ts.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            for table in &stats {
+                println!(
+                    "{:<20} {:>10} {:>10}  {:<20} {:<20}",
+                    table.name,
+                    table.rows,
+                    format_bytes(table.size_bytes),
+                    format_time(table.oldest),
+                    format_time(table.newest)
+                );
+            }
+            println!();
+            println!(
+                "Total: {} rows, {} on disk",
+                stats.iter().map(|t| t.rows).sum::<u64>(),
+                format_bytes(std::fs::metadata(db_path)?.len())
+            );
+        }
+        DbCommand::Merge { sources, host } => {
+            let mut failed = 0;
+            for source in &sources {
+                match merge_database(
+                    &conn,
+                    source,
+                    host.as_deref(),
+                    &options,
+                ) {
+                    Ok(report) => {
+                        println!(
+                            "Merged {} (host {}, schema v{}{}): {} rows added, {} duplicates skipped",
+                            source.display(),
+                            if report.host.is_empty() { "local" } else { &report.host },
+                            report.source_version,
+                            if report.migrated { ", migrated copy" } else { "" },
+                            report.merged(),
+                            report.duplicates()
+                        );
+                        for table in &report.tables {
+                            println!(
+                                "  {:<20} {:>10} added {:>10} skipped",
+                                table.name, table.merged, table.duplicates
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to merge {}: {:#}", source.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed > 0 {
+                drop(conn);
+                process::exit(1);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // Initialize logger (defaults to WARN level, configurable via RUST_LOG env var)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::parse();
+    let db_path = get_db_path(args.db_path);
+
+    match args.command {
+        Command::Db { command } => run_db_command(command, &db_path),
+    }
+}
@@ -1,15 +1,33 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
 use clap::Parser;
 use log::{debug, info, warn};
+use rusqlite::Connection;
 use std::env;
 use std::path::PathBuf;
-use systers::collector::{collect_system_metrics, scan_system_logs_with_paths};
-use systers::config::DEFAULT_RETENTION_DAYS;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use systers::anomaly::rebuild_baselines;
+use systers::auth::{collect_auth_events, is_auth_log};
+use systers::collector::{
+    collect_disk_usage, collect_system_metrics, hostname, resolve_log_sources, scan_log_sources,
+};
+use systers::config::{
+    Config, LogSourceConfig, DEFAULT_RETENTION_DAYS, MAX_LOG_LINES_PER_FILE, SWAP_WARNING_THRESHOLD,
+};
 use systers::db::{
-    cleanup_old_data, init_database, insert_log_entry, insert_metrics, insert_system_check,
-    SystemCheckResult,
+    cleanup_collection_runs, cleanup_merged_data, cleanup_old_data, cleanup_rollups,
+    collection_lock_holder, finish_collection_run, init_database_with_options,
+    query_metric_baselines,
+    start_collection_run, try_collection_lock, try_shared_writer_lock, writer_lock_holder, Batch,
+    CheckStatus, CollectorOutcome, KernelEvent, Resolution, SystemCheckResult, RUN_MODE_COLLECT,
+    RUN_MODE_WATCH,
+};
+use systers::kernel::{
+    boot_event, collect_kernel_events, collect_system_kernel_events, is_kernel_log,
 };
+use systers::rollup::build_rollups;
+use systers::watcher::{run_watch, LogWatcher};
 
 /// System Data Collector
 ///
@@ -51,6 +69,11 @@ struct Args {
     /// Show available and missing external tools for system checks
     #[arg(long)]
     show_tools: bool,
+
+    /// Keep running and follow log files with inotify, storing new issues as
+    /// they are written (stop with Ctrl+C or SIGTERM)
+    #[arg(long)]
+    watch: bool,
 }
 
 fn get_db_path(cli_path: Option<PathBuf>) -> PathBuf {
@@ -86,98 +109,67 @@ fn get_log_paths(cli_paths: Option<Vec<PathBuf>>) -> Option<Vec<PathBuf>> {
     None
 }
 
-fn main() -> Result<()> {
-    // Initialize logger (defaults to INFO level, configurable via RUST_LOG env var)
-    env_logger::init();
-
-    let args = Args::parse();
-
-    // Handle show-tools first
-    if args.show_tools {
-        use systers::system_checks::{detect_available_tools, EXTERNAL_TOOLS};
-
-        println!("External Tools for Enhanced System Checks");
-        println!("=========================================\n");
-
-        let available_tools = detect_available_tools();
-        let available_names: Vec<&str> = available_tools.iter().map(|t| t.name).collect();
-
-        println!("✓ Available Tools:");
-        for tool in &available_tools {
-            println!("  • {} - {}", tool.name, tool.description);
+/// Build hourly and daily rollups, logging rather than failing on errors
+fn update_rollups(conn: &Connection) {
+    match build_rollups(conn, Utc::now()) {
+        Ok((hourly, daily)) => {
+            if hourly > 0 || daily > 0 {
+                info!("Built {} hourly and {} daily rollups", hourly, daily);
+            }
         }
+        Err(e) => warn!("Could not build rollups: {}", e),
+    }
+}
 
-        let missing: Vec<_> = EXTERNAL_TOOLS
+/// Relearn the anomaly baselines once they are a day old, logging rather
+/// than failing on errors
+fn update_baselines(conn: &Connection, config: &Config) {
+    let now = Utc::now();
+    let stale = match query_metric_baselines(conn) {
+        Ok(baselines) => baselines
             .iter()
-            .filter(|t| !available_names.contains(&t.name))
-            .collect();
-
-        if !missing.is_empty() {
-            println!("\n✗ Missing Tools (optional):");
-            for tool in &missing {
-                println!("  • {} - {}", tool.name, tool.description);
-                println!("    Install: {}", tool.install_hint.lines().next().unwrap_or(""));
-            }
-
-            println!("\nNote: Missing tools are optional. Basic monitoring works without them.");
-            println!("Run with --system-checks to enable enhanced checks using available tools.");
-        } else {
-            println!("\nAll external tools are available!");
+            .map(|b| b.built_at)
+            .max()
+            .is_none_or(|built| now - built >= chrono::Duration::days(1)),
+        Err(e) => {
+            warn!("Could not read baselines: {}", e);
+            return;
         }
-
-        return Ok(());
+    };
+    if !stale {
+        return;
     }
-
-    // Handle config generation
-    if let Some(config_path) = args.generate_config {
-        use systers::config::Config;
-        let config = Config::default();
-        config.save_to_file(&config_path)
-            .context("Failed to generate configuration file")?;
-        println!("Configuration file generated at: {}", config_path.display());
-        println!();
-        println!("You can now:");
-        println!("  1. Edit the file to customize your settings");
-        println!("  2. Move it to one of these locations:");
-        println!("     - ./systers.yaml (current directory)");
-        println!("     - ~/.config/systers/config.yaml (user config)");
-        println!("     - /etc/systers/config.yaml (system config)");
-        println!();
-        println!("See config.example.yaml for more examples and documentation.");
-        return Ok(());
+    match rebuild_baselines(conn, now, chrono::Duration::days(config.anomaly.lookback_days)) {
+        Ok(rebuild) => info!(
+            "Rebuilt {} baselines from {} samples",
+            rebuild.slots, rebuild.samples
+        ),
+        Err(e) => warn!("Could not rebuild baselines: {}", e),
     }
+}
 
-    let db_path = get_db_path(args.db_path);
-    let log_paths = get_log_paths(args.log_paths);
-    let retention_days = args.retention_days.unwrap_or(DEFAULT_RETENTION_DAYS);
-
-    info!(
-        "Systers Writer - System Data Collector v{}",
-        systers::VERSION
-    );
-    info!("Database: {}", db_path.display());
-    if let Some(ref paths) = log_paths {
-        debug!("Custom log paths: {:?}", paths);
-    }
-
-    // Initialize database
-    let conn = init_database(&db_path).context("Failed to initialize database")?;
-
-    // If cleanup-only mode, run cleanup and exit
-    if args.cleanup {
-        info!(
-            "Cleaning up old data (retention: {} days)...",
-            retention_days
-        );
-        let (metrics_deleted, logs_deleted, checks_deleted) =
-            cleanup_old_data(&conn, retention_days).context("Failed to cleanup old data")?;
-        info!(
-            "Deleted {} metrics, {} log entries, and {} system checks",
-            metrics_deleted, logs_deleted, checks_deleted
-        );
-        return Ok(());
-    }
+/// Sources for the keyword scan and watch mode
+/// Auth logs are analyzed into auth events instead, so a failed SSH password
+/// is not also stored (and counted) as an ERROR log entry
+fn keyword_log_sources(sources: &[LogSourceConfig]) -> Vec<LogSourceConfig> {
+    sources
+        .iter()
+        .filter(|source| !is_auth_log(&source.path))
+        .cloned()
+        .collect()
+}
 
+/// Collect metrics, logs, kernel/auth events and optional system checks,
+/// then store them in one transaction linked to the run
+/// The outcome of each collector is appended to `collectors`
+fn collect_and_store(
+    conn: &Connection,
+    run_id: i64,
+    config: &Config,
+    log_paths: Option<&[PathBuf]>,
+    system_checks: bool,
+    collectors: &mut Vec<CollectorOutcome>,
+) -> Result<()> {
     info!("Collecting system metrics...");
 
     // Collect system metrics
@@ -192,25 +184,27 @@ fn main() -> Result<()> {
         "Disk: {:.1}% used",
         (metrics.disk_used as f32 / metrics.disk_total as f32) * 100.0
     );
+    let disk_usage = collect_disk_usage(metrics.timestamp);
+    for usage in &disk_usage {
+        debug!("Disk {}: {:.1}% used", usage.mount_point, usage.used_percent());
+    }
     debug!("Processes: {}", metrics.process_count);
     debug!(
         "Load Average: {:.2}, {:.2}, {:.2}",
         metrics.load_avg_1min, metrics.load_avg_5min, metrics.load_avg_15min
     );
 
-    // Store metrics
-    insert_metrics(&conn, &metrics).context("Failed to insert metrics")?;
-
     info!("Scanning system logs for issues...");
 
-    // Collect and store log entries
-    let entries_result = if let Some(ref paths) = log_paths {
-        scan_system_logs_with_paths(Some(paths.as_slice()))
-    } else {
-        scan_system_logs_with_paths::<PathBuf>(None)
-    };
+    // Collect log entries (stored with the rest of the run below)
+    let log_sources = resolve_log_sources(
+        log_paths,
+        &config.collection.log_sources,
+    );
+    let entries_result = scan_log_sources(&keyword_log_sources(&log_sources));
 
-    match entries_result {
+    let mut log_error = None;
+    let log_entries = match entries_result {
         Ok(entries) => {
             let error_count = entries.iter().filter(|e| e.level == "ERROR").count();
             let warning_count = entries.iter().filter(|e| e.level == "WARNING").count();
@@ -221,19 +215,63 @@ fn main() -> Result<()> {
                 critical_count, error_count, warning_count
             );
 
-            for entry in entries {
-                insert_log_entry(&conn, &entry).context("Failed to insert log entry")?;
-            }
+            entries
         }
         Err(e) => {
             warn!("Could not scan all logs: {}", e);
+            log_error = Some(format!("{:#}", e));
+            Vec::new()
+        }
+    };
+
+    // Analyze authentication logs for sshd logins and sudo usage
+    let mut auth_events = Vec::new();
+    let mut auth_errors = Vec::new();
+    for source in log_sources
+        .iter()
+        .filter(|s| is_auth_log(&s.path) && s.path.exists())
+    {
+        match collect_auth_events(&source.path, MAX_LOG_LINES_PER_FILE) {
+            Ok(events) => auth_events.push((source.path.clone(), events)),
+            Err(e) => {
+                warn!("Could not analyze {}: {}", source.path.display(), e);
+                auth_errors.push(format!("{}: {:#}", source.path.display(), e));
+            }
         }
     }
 
-    info!("Data collection complete at {}", metrics.timestamp);
+    // Extract structured kernel events (OOM kills, segfaults, hardware/disk errors)
+    let kernel_logs: Vec<_> = log_sources
+        .iter()
+        .filter(|s| is_kernel_log(&s.path) && s.path.exists())
+        .collect();
+    // The boot is stored once; later runs find its boot id already recorded
+    let mut kernel_events: Vec<KernelEvent> = vec![boot_event()];
+    let mut kernel_errors = Vec::new();
+    if kernel_logs.is_empty() {
+        // No kernel log files (e.g. journald-only systems): use journalctl or dmesg
+        match collect_system_kernel_events(MAX_LOG_LINES_PER_FILE) {
+            Ok(mut events) => kernel_events.append(&mut events),
+            Err(e) => {
+                warn!("Could not read kernel messages: {}", e);
+                kernel_errors.push(format!("{:#}", e));
+            }
+        }
+    } else {
+        for source in kernel_logs {
+            match collect_kernel_events(&source.path, MAX_LOG_LINES_PER_FILE) {
+                Ok(mut events) => kernel_events.append(&mut events),
+                Err(e) => {
+                    warn!("Could not analyze {}: {}", source.path.display(), e);
+                    kernel_errors.push(format!("{}: {:#}", source.path.display(), e));
+                }
+            }
+        }
+    }
 
     // Run enhanced system checks if enabled
-    if args.system_checks {
+    let mut check_results: Vec<SystemCheckResult> = Vec::new();
+    if system_checks {
         use systers::system_checks::*;
 
         println!("\n╔════════════════════════════════════════════════════════════════╗");
@@ -244,6 +282,37 @@ fn main() -> Result<()> {
         let mut active_checks = Vec::new();
         let mut unavailable_checks = Vec::new();
 
+        // Check swap usage (always available)
+        active_checks.push("Swap Usage");
+        println!("✓ Running: Swap Usage Check");
+        let swap = check_swap_usage();
+        let swap_percent = swap.used_percent();
+        let message = if swap.total_bytes == 0 {
+            "No swap configured".to_string()
+        } else {
+            format!(
+                "{:.1}% of {} MB swap used",
+                swap_percent,
+                swap.total_bytes / 1024 / 1024
+            )
+        };
+        println!("  → {}", message);
+        check_results.push(SystemCheckResult {
+            timestamp: Utc::now(),
+            check_name: "Swap Usage".to_string(),
+            check_type: "swap".to_string(),
+            status: if swap_percent > SWAP_WARNING_THRESHOLD as f64 {
+                CheckStatus::Warning
+            } else {
+                CheckStatus::Ok
+            },
+            value: Some(swap_percent),
+            unit: Some("% used".to_string()),
+            details: serde_json::to_value(&swap).ok(),
+            message,
+        });
+        println!();
+
         // Check for package updates
         if is_command_available("apt") || is_command_available("dnf") {
             active_checks.push("Package Updates");
@@ -266,17 +335,16 @@ fn main() -> Result<()> {
                         check_name: "Package Updates".to_string(),
                         check_type: "package_manager".to_string(),
                         status: if updates.updates_available > 0 {
-                            "warning"
+                            CheckStatus::Warning
                         } else {
-                            "ok"
-                        }
-                        .to_string(),
-                        value: Some(updates.updates_available.to_string()),
+                            CheckStatus::Ok
+                        },
+                        value: Some(updates.updates_available as f64),
+                        unit: Some("updates".to_string()),
+                        details: serde_json::to_value(&updates).ok(),
                         message,
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store package update check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
                 Err(e) => {
                     println!("  → Failed: {} (may need sudo)", e);
@@ -284,13 +352,13 @@ fn main() -> Result<()> {
                         timestamp: Utc::now(),
                         check_name: "Package Updates".to_string(),
                         check_type: "package_manager".to_string(),
-                        status: "error".to_string(),
+                        status: CheckStatus::Error,
                         value: None,
+                        unit: None,
+                        details: None,
                         message: format!("Check failed: {}", e),
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store package update check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
             }
             println!();
@@ -336,17 +404,16 @@ fn main() -> Result<()> {
                         check_name: "Systemd Services".to_string(),
                         check_type: "systemd".to_string(),
                         status: if status.failed_services > 0 {
-                            "warning"
+                            CheckStatus::Warning
                         } else {
-                            "ok"
-                        }
-                        .to_string(),
-                        value: Some(status.failed_services.to_string()),
+                            CheckStatus::Ok
+                        },
+                        value: Some(status.failed_services as f64),
+                        unit: Some("failed services".to_string()),
+                        details: serde_json::to_value(&status).ok(),
                         message,
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store systemd check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
                 Err(e) => {
                     println!("  → Failed: {}", e);
@@ -354,13 +421,13 @@ fn main() -> Result<()> {
                         timestamp: Utc::now(),
                         check_name: "Systemd Services".to_string(),
                         check_type: "systemd".to_string(),
-                        status: "error".to_string(),
+                        status: CheckStatus::Error,
                         value: None,
+                        unit: None,
+                        details: None,
                         message: format!("Check failed: {}", e),
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store systemd check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
             }
             println!();
@@ -380,13 +447,13 @@ fn main() -> Result<()> {
                             timestamp: Utc::now(),
                             check_name: "SMART Disk Health".to_string(),
                             check_type: "disk_health".to_string(),
-                            status: "warning".to_string(),
-                            value: Some("0".to_string()),
+                            status: CheckStatus::Warning,
+                            value: Some(0.0),
+                            unit: Some("failed disks".to_string()),
+                            details: None,
                             message: "No disks found or unable to access".to_string(),
                         };
-                        if let Err(e) = insert_system_check(&conn, &check_result) {
-                            warn!("Failed to store disk health check result: {}", e);
-                        }
+                        check_results.push(check_result);
                     } else {
                         for disk in &disks {
                             let status_icon = if disk.health_status == "PASSED" { "✓" } else { "⚠️" };
@@ -418,17 +485,16 @@ fn main() -> Result<()> {
                             check_name: "SMART Disk Health".to_string(),
                             check_type: "disk_health".to_string(),
                             status: if failed_disks.is_empty() {
-                                "ok"
+                                CheckStatus::Ok
                             } else {
-                                "critical"
-                            }
-                            .to_string(),
-                            value: Some(failed_disks.len().to_string()),
+                                CheckStatus::Critical
+                            },
+                            value: Some(failed_disks.len() as f64),
+                            unit: Some("failed disks".to_string()),
+                            details: serde_json::to_value(&disks).ok(),
                             message,
                         };
-                        if let Err(e) = insert_system_check(&conn, &check_result) {
-                            warn!("Failed to store disk health check result: {}", e);
-                        }
+                        check_results.push(check_result);
                     }
                 }
                 Err(e) => {
@@ -437,13 +503,13 @@ fn main() -> Result<()> {
                         timestamp: Utc::now(),
                         check_name: "SMART Disk Health".to_string(),
                         check_type: "disk_health".to_string(),
-                        status: "error".to_string(),
+                        status: CheckStatus::Error,
                         value: None,
+                        unit: None,
+                        details: None,
                         message: format!("Check failed: {}", e),
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store disk health check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
             }
             println!();
@@ -473,13 +539,13 @@ fn main() -> Result<()> {
                         timestamp: Utc::now(),
                         check_name: "Directory Sizes".to_string(),
                         check_type: "disk_usage".to_string(),
-                        status: "info".to_string(),
-                        value: Some(dirs.len().to_string()),
+                        status: CheckStatus::Ok,
+                        value: Some(dirs.len() as f64),
+                        unit: Some("directories".to_string()),
+                        details: serde_json::to_value(&dirs).ok(),
                         message: format!("Top directories: {}", top_dirs),
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store directory size check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
                 Err(e) => {
                     println!("  → Failed: {}", e);
@@ -487,13 +553,13 @@ fn main() -> Result<()> {
                         timestamp: Utc::now(),
                         check_name: "Directory Sizes".to_string(),
                         check_type: "disk_usage".to_string(),
-                        status: "error".to_string(),
+                        status: CheckStatus::Error,
                         value: None,
+                        unit: None,
+                        details: None,
                         message: format!("Check failed: {}", e),
                     };
-                    if let Err(e) = insert_system_check(&conn, &check_result) {
-                        warn!("Failed to store directory size check result: {}", e);
-                    }
+                    check_results.push(check_result);
                 }
             }
             println!();
@@ -522,8 +588,302 @@ fn main() -> Result<()> {
         info!("Enhanced system checks complete");
     }
 
+    // Store the whole run in one transaction; any failure rolls it back
+    let batch = Batch::begin_run(conn, run_id)?;
+    batch
+        .insert_metrics(&metrics)
+        .context("Failed to insert metrics")?;
+    for usage in &disk_usage {
+        batch
+            .insert_disk_usage(usage)
+            .context("Failed to insert disk usage")?;
+    }
+    for entry in &log_entries {
+        batch
+            .insert_log_entry(entry)
+            .context("Failed to insert log entry")?;
+    }
+    let mut auth_rows = 0;
+    for (path, events) in &auth_events {
+        let mut new_events = 0;
+        for event in events {
+            if batch
+                .insert_auth_event(event)
+                .context("Failed to insert auth event")?
+            {
+                new_events += 1;
+            }
+        }
+        info!(
+            "Recorded {} new auth events from {}",
+            new_events,
+            path.display()
+        );
+        auth_rows += new_events;
+    }
+    let mut new_kernel_events = 0;
+    for event in &kernel_events {
+        if batch
+            .insert_kernel_event(event)
+            .context("Failed to insert kernel event")?
+        {
+            new_kernel_events += 1;
+        }
+    }
+    info!("Recorded {} new kernel events", new_kernel_events);
+    for check in &check_results {
+        batch
+            .insert_system_check(check)
+            .context("Failed to insert system check result")?;
+    }
+    batch.commit()?;
+
+    let outcome = |name: &str, errors: &[String], rows: usize| {
+        if errors.is_empty() {
+            CollectorOutcome::ok(name, rows)
+        } else {
+            CollectorOutcome::failed(name, errors.join("; "), rows)
+        }
+    };
+    collectors.push(CollectorOutcome::ok("metrics", 1 + disk_usage.len()));
+    collectors.push(outcome("logs", log_error.as_slice(), log_entries.len()));
+    collectors.push(outcome("auth", &auth_errors, auth_rows));
+    collectors.push(outcome("kernel", &kernel_errors, new_kernel_events));
+    if system_checks {
+        collectors.push(CollectorOutcome::ok("system_checks", check_results.len()));
+    }
+
+    info!("Data collection complete at {}", metrics.timestamp);
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // Initialize logger (defaults to INFO level, configurable via RUST_LOG env var)
+    env_logger::init();
+
+    let args = Args::parse();
+
+    // Handle show-tools first
+    if args.show_tools {
+        use systers::system_checks::{detect_available_tools, EXTERNAL_TOOLS};
+
+        println!("External Tools for Enhanced System Checks");
+        println!("=========================================\n");
+
+        let available_tools = detect_available_tools();
+        let available_names: Vec<&str> = available_tools.iter().map(|t| t.name).collect();
+
+        println!("✓ Available Tools:");
+        for tool in &available_tools {
+            println!("  • {} - {}", tool.name, tool.description);
+        }
+
+        let missing: Vec<_> = EXTERNAL_TOOLS
+            .iter()
+            .filter(|t| !available_names.contains(&t.name))
+            .collect();
+
+        if !missing.is_empty() {
+            println!("\n✗ Missing Tools (optional):");
+            for tool in &missing {
+                println!("  • {} - {}", tool.name, tool.description);
+                println!("    Install: {}", tool.install_hint.lines().next().unwrap_or(""));
+            }
+
+            println!("\nNote: Missing tools are optional. Basic monitoring works without them.");
+            println!("Run with --system-checks to enable enhanced checks using available tools.");
+        } else {
+            println!("\nAll external tools are available!");
+        }
+
+        return Ok(());
+    }
+
+    // Handle config generation
+    if let Some(config_path) = args.generate_config {
+        let config = Config::default();
+        config.save_to_file(&config_path)
+            .context("Failed to generate configuration file")?;
+        println!("Configuration file generated at: {}", config_path.display());
+        println!();
+        println!("You can now:");
+        println!("  1. Edit the file to customize your settings");
+        println!("  2. Move it to one of these locations:");
+        println!("     - ./systers.yaml (current directory)");
+        println!("     - ~/.config/systers/config.yaml (user config)");
+        println!("     - /etc/systers/config.yaml (system config)");
+        println!();
+        println!("See config.example.yaml for more examples and documentation.");
+        return Ok(());
+    }
+
+    let config = Config::load().context("Failed to load configuration")?;
+
+    let db_path = get_db_path(args.db_path);
+    let log_paths = get_log_paths(args.log_paths);
+    let retention_days = args.retention_days.unwrap_or(DEFAULT_RETENTION_DAYS);
+
+    info!(
+        "Systers Writer - System Data Collector v{}",
+        systers::VERSION
+    );
+    info!("Database: {}", db_path.display());
+    if let Some(ref paths) = log_paths {
+        debug!("Custom log paths: {:?}", paths);
+    }
+
+    // Every run that writes shares the writer lock, which keeps it out of the
+    // way of `systers db rollback`, `vacuum` and `merge`; collections and
+    // cleanups also take the collection lock so only one runs at a time, while
+    // --watch holds just the shared lock and runs alongside them
+    let _writer_lock = match try_shared_writer_lock(&db_path)? {
+        Some(lock) => {
+            debug!("Holding shared writer lock {}", lock.path().display());
+            lock
+        }
+        None => {
+            let holder = writer_lock_holder(&db_path)
+                .map(|pid| format!(" (pid {})", pid))
+                .unwrap_or_default();
+            let message = format!(
+                "A systers db command{} is changing {}",
+                holder,
+                db_path.display()
+            );
+            // A watcher exits with an error so its service manager restarts it
+            if args.watch {
+                return Err(anyhow!("{}; try again when it has finished", message));
+            }
+            warn!("{}, skipping this run", message);
+            return Ok(());
+        }
+    };
+    let _collection_lock = if args.watch {
+        None
+    } else {
+        match try_collection_lock(&db_path)? {
+            Some(lock) => {
+                debug!("Holding collection lock {}", lock.path().display());
+                Some(lock)
+            }
+            None => {
+                let holder = collection_lock_holder(&db_path)
+                    .map(|pid| format!(" (pid {})", pid))
+                    .unwrap_or_default();
+                warn!(
+                    "Another syswriter{} is already collecting into {}, skipping this run",
+                    holder,
+                    db_path.display()
+                );
+                return Ok(());
+            }
+        }
+    };
+
+    // Initialize database
+    let conn = init_database_with_options(&db_path, &config.database.connection_options()?)
+        .context("Failed to initialize database")?;
+
+    // If cleanup-only mode, run cleanup and exit
+    if args.cleanup {
+        // Roll up raw data before it is deleted
+        update_rollups(&conn);
+        cleanup_rollups(&conn, Resolution::Hourly, config.retention.hourly_days)
+            .context("Failed to cleanup old rollups")?;
+        cleanup_collection_runs(&conn, retention_days)
+            .context("Failed to cleanup old collection runs")?;
+        if let Some(merged_days) = config.retention.merged_days {
+            let deleted = cleanup_merged_data(&conn, merged_days)
+                .context("Failed to cleanup old merged data")?;
+            info!("Deleted {} merged rows from other hosts", deleted);
+        }
+        info!(
+            "Cleaning up old data (retention: {} days)...",
+            retention_days
+        );
+        let (metrics_deleted, logs_deleted, checks_deleted) =
+            cleanup_old_data(&conn, retention_days).context("Failed to cleanup old data")?;
+        info!(
+            "Deleted {} metrics, {} log entries, and {} system checks",
+            metrics_deleted, logs_deleted, checks_deleted
+        );
+        return Ok(());
+    }
+
+    // Daemon mode: follow log files instead of running one collection
+    if args.watch {
+        let log_sources = resolve_log_sources(
+            log_paths.as_deref(),
+            &config.collection.log_sources,
+        );
+        let mut watcher = LogWatcher::new(&keyword_log_sources(&log_sources))
+            .context("Failed to start log watcher")?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let handler_flag = Arc::clone(&running);
+        ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))
+            .context("Failed to install signal handler")?;
+
+        let run_id = start_collection_run(&conn, RUN_MODE_WATCH, Utc::now(), &hostname())
+            .context("Failed to record collection run")?;
+        info!("Watching log files (Ctrl+C to stop)...");
+        let result = run_watch(
+            &conn,
+            &mut watcher,
+            &config.watch,
+            &config.alerts,
+            &running,
+            Some(run_id),
+        );
+        let (collectors, error) = match &result {
+            Ok(written) => (vec![CollectorOutcome::ok("watch", *written)], None),
+            Err(e) => (Vec::new(), Some(format!("{:#}", e))),
+        };
+        finish_collection_run(&conn, run_id, Utc::now(), &collectors, error.as_deref())
+            .context("Failed to record collection run")?;
+        return result.map(|_| ());
+    }
+
+    let run_id = start_collection_run(&conn, RUN_MODE_COLLECT, Utc::now(), &hostname())
+        .context("Failed to record collection run")?;
+    let mut collectors = Vec::new();
+    let result = collect_and_store(
+        &conn,
+        run_id,
+        &config,
+        log_paths.as_deref(),
+        args.system_checks,
+        &mut collectors,
+    );
+    let error = result.as_ref().err().map(|e| format!("{:#}", e));
+    finish_collection_run(&conn, run_id, Utc::now(), &collectors, error.as_deref())
+        .context("Failed to record collection run")?;
+    result?;
+
+    // Summarize completed hours and days for long-term retention
+    update_rollups(&conn);
+    update_baselines(&conn, &config);
+
     // Automatic cleanup of old data
     if !args.no_cleanup {
+        match cleanup_rollups(&conn, Resolution::Hourly, config.retention.hourly_days) {
+            Ok(deleted) if deleted > 0 => info!("Deleted {} old hourly rollups", deleted),
+            Ok(_) => {}
+            Err(e) => warn!("Rollup cleanup failed: {}", e),
+        }
+        if let Err(e) = cleanup_collection_runs(&conn, retention_days) {
+            warn!("Collection run cleanup failed: {}", e);
+        }
+        if let Some(merged_days) = config.retention.merged_days {
+            match cleanup_merged_data(&conn, merged_days) {
+                Ok(deleted) if deleted > 0 => {
+                    info!("Deleted {} merged rows from other hosts", deleted)
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Merged data cleanup failed: {}", e),
+            }
+        }
         info!(
             "Cleaning up old data (retention: {} days)...",
             retention_days
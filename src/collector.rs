@@ -1,15 +1,24 @@
-use crate::config::{CPU_MEASUREMENT_DELAY_MS, MAX_LOG_LINES_PER_FILE};
-use crate::db::{LogEntry, SystemMetrics};
+use crate::config::{
+    JsonLogConfig, LogFormat, LogSourceConfig, MultilineConfig, CPU_MEASUREMENT_DELAY_MS,
+    MAX_LOG_LINES_PER_FILE,
+};
+use crate::db::{DiskUsage, LogEntry, SystemMetrics};
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
-use log::warn;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Utc};
+use log::{debug, warn};
 use regex::Regex;
+use serde_json::{Map, Value};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::sync::OnceLock;
 use sysinfo::{Disks, System};
 
+/// Host name recorded with each collection run
+pub fn hostname() -> String {
+    System::host_name().unwrap_or_else(|| "unknown".to_string())
+}
+
 /// Collect current system metrics
 pub fn collect_system_metrics() -> Result<SystemMetrics> {
     let mut sys = System::new_all();
@@ -59,6 +68,46 @@ pub fn collect_system_metrics() -> Result<SystemMetrics> {
     })
 }
 
+/// Collect used and total space of each mounted filesystem
+/// Mount points listed twice (bind mounts) are kept once and pseudo
+/// filesystems without any size are skipped
+pub fn collect_disk_usage(timestamp: DateTime<Utc>) -> Vec<DiskUsage> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut usage: Vec<DiskUsage> = Vec::new();
+
+    for disk in &disks {
+        let mount_point = disk.mount_point().to_string_lossy().to_string();
+        if disk.total_space() == 0 || usage.iter().any(|u| u.mount_point == mount_point) {
+            continue;
+        }
+        usage.push(DiskUsage {
+            timestamp,
+            mount_point,
+            filesystem: disk.file_system().to_string_lossy().to_string(),
+            total_bytes: disk.total_space(),
+            used_bytes: disk.total_space().saturating_sub(disk.available_space()),
+        });
+    }
+
+    usage
+}
+
+/// Continuation lines used when a multiline source sets no pattern:
+/// indented lines (Java/Rust frames, Python trace lines), "Caused by:",
+/// "Traceback" headers, "... N more" markers and the exception line that
+/// ends a Python traceback
+pub const DEFAULT_CONTINUATION_PATTERN: &str = concat!(
+    r"^(\s+\S|Caused by:|Traceback \(most recent call last\)",
+    r"|\.\.\. \d+ (more|common frames omitted)|\w+(\.\w+)*(Error|Exception):)"
+);
+
+/// Exception line of a trace, e.g. `ValueError: bad input` or
+/// `java.lang.IllegalStateException: closed`
+fn exception_line_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^\w+(\.\w+)*(Error|Exception):").unwrap())
+}
+
 /// Get compiled regex patterns (cached using OnceLock)
 fn get_log_patterns() -> &'static [(&'static str, Regex)] {
     static PATTERNS: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
@@ -75,7 +124,35 @@ fn get_log_patterns() -> &'static [(&'static str, Regex)] {
 }
 
 /// Try to extract timestamp from log line (supports common formats)
-fn extract_timestamp(line: &str) -> Option<DateTime<Utc>> {
+pub(crate) fn extract_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    extract_iso_timestamp(line).or_else(|| parse_syslog_timestamp(line, Local::now()))
+}
+
+/// Parse a traditional syslog prefix like "Nov  5 20:00:01" (local time, no year)
+/// The year is taken from `now`; dates that would lie in the future belong to last year
+pub(crate) fn parse_syslog_timestamp(line: &str, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+    static SYSLOG_PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = SYSLOG_PATTERN.get_or_init(|| {
+        Regex::new(r"^([A-Z][a-z]{2})\s+(\d{1,2}) (\d{2}:\d{2}:\d{2})").unwrap()
+    });
+
+    let cap = pattern.captures(line)?;
+    let parse_in_year = |year: i32| {
+        let text = format!("{} {} {} {}", year, &cap[1], &cap[2], &cap[3]);
+        NaiveDateTime::parse_from_str(&text, "%Y %b %d %H:%M:%S")
+            .ok()
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+    };
+
+    let mut timestamp = parse_in_year(now.year())?;
+    if timestamp > now + chrono::Duration::days(1) {
+        timestamp = parse_in_year(now.year() - 1)?;
+    }
+    Some(timestamp.with_timezone(&Utc))
+}
+
+/// Extract an ISO 8601 / RFC 3339 timestamp from anywhere in the line
+fn extract_iso_timestamp(line: &str) -> Option<DateTime<Utc>> {
     static TIMESTAMP_PATTERN: OnceLock<Regex> = OnceLock::new();
     let pattern = TIMESTAMP_PATTERN.get_or_init(|| {
         // Matches ISO 8601 timestamps like 2025-11-05T20:00:01.529-0500
@@ -93,66 +170,195 @@ fn extract_timestamp(line: &str) -> Option<DateTime<Utc>> {
             if let Ok(dt) = DateTime::parse_from_rfc3339(&ts_str) {
                 return Some(dt.with_timezone(&Utc));
             }
+            // Try an offset without a colon (e.g. -0500 from journalctl -o short-iso)
+            if let Ok(dt) = DateTime::parse_from_str(&ts_str, "%Y-%m-%dT%H:%M:%S%.f%z") {
+                return Some(dt.with_timezone(&Utc));
+            }
         }
     }
     None
 }
 
+/// Classify a log line (or grouped event) by keyword patterns
+/// Returns None if no CRITICAL, ERROR or WARNING pattern matches
+fn classify_log_text(text: &str) -> Option<&'static str> {
+    let text_lower = text.to_lowercase();
+
+    // Check each pattern in priority order (CRITICAL, ERROR, WARNING)
+    for (level, pattern) in get_log_patterns() {
+        if pattern.is_match(&text_lower) {
+            // Additional check to reduce false positives for ERROR level
+            if *level == "ERROR" {
+                // Skip if it's just mentioning error in a benign context
+                if text_lower.contains("error=0")
+                    || text_lower.contains("errors=0")
+                    || text_lower.contains("no error")
+                    || text_lower.contains("without error") {
+                    continue;
+                }
+            }
+            return Some(level);
+        }
+    }
+    None
+}
+
+/// Compiled multi-line grouping rules for a log source
+enum EventBoundary {
+    /// Lines matching the pattern begin a new event
+    Start(Regex),
+    /// Lines matching the pattern continue the current event
+    Continuation(Regex),
+}
+
+impl EventBoundary {
+    fn from_config(config: &MultilineConfig) -> Result<Self> {
+        if let Some(ref pattern) = config.start_pattern {
+            let regex = Regex::new(pattern)
+                .context(format!("Invalid multiline start_pattern: {}", pattern))?;
+            return Ok(EventBoundary::Start(regex));
+        }
+
+        let pattern = config
+            .continuation_pattern
+            .as_deref()
+            .unwrap_or(DEFAULT_CONTINUATION_PATTERN);
+        let regex = Regex::new(pattern)
+            .context(format!("Invalid multiline continuation_pattern: {}", pattern))?;
+        Ok(EventBoundary::Continuation(regex))
+    }
+
+    fn continues_event(&self, line: &str) -> bool {
+        match self {
+            EventBoundary::Start(regex) => !regex.is_match(line),
+            EventBoundary::Continuation(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// Group raw lines into events according to the multi-line rules
+/// Without rules every line is its own event
+fn group_log_lines(
+    lines: Vec<String>,
+    boundary: Option<&EventBoundary>,
+    max_event_lines: usize,
+) -> Vec<Vec<String>> {
+    let mut events: Vec<Vec<String>> = Vec::new();
+
+    for line in lines {
+        let continues = match (boundary, events.last()) {
+            (Some(boundary), Some(_)) => boundary.continues_event(&line),
+            _ => false,
+        };
+
+        if continues {
+            if let Some(event) = events.last_mut() {
+                // Very long traces are cut off rather than split into new events
+                if event.len() < max_event_lines {
+                    event.push(line);
+                }
+            }
+        } else {
+            events.push(vec![line]);
+        }
+    }
+
+    events
+}
+
 /// Parse system log file for errors and warnings with improved pattern matching
 pub fn collect_log_entries<P: AsRef<Path>>(
     log_path: P,
     max_entries: usize,
+) -> Result<Vec<LogEntry>> {
+    collect_log_events(log_path, None, max_entries)
+}
+
+/// Parse a plain-text log file, optionally merging multi-line events
+/// (stack traces, panics) into a single entry
+/// Each event is classified by its first line and exception lines and
+/// timestamped from its first line
+pub fn collect_log_events<P: AsRef<Path>>(
+    log_path: P,
+    multiline: Option<&MultilineConfig>,
+    max_entries: usize,
 ) -> Result<Vec<LogEntry>> {
     let log_path_ref = log_path.as_ref();
     let file = File::open(log_path_ref).context("Failed to open log file")?;
     let reader = BufReader::new(file);
 
-    let mut entries = Vec::new();
-    let fallback_timestamp = Utc::now();
+    let lines = reader
+        .lines()
+        .take(max_entries)
+        .collect::<std::io::Result<Vec<String>>>()?;
 
     // Use the log file path as the source
-    let source = log_path_ref.to_string_lossy().to_string();
+    plain_lines_to_entries(lines, multiline, &log_path_ref.to_string_lossy())
+}
 
-    let patterns = get_log_patterns();
+/// Level of a grouped event from its first line and any exception lines
+/// Other trace lines are ignored, so a frame that mentions "error" does not
+/// escalate an INFO event; an exception line makes the event at least an ERROR
+fn classify_event(event: &[String]) -> Option<&'static str> {
+    let exceptions: Vec<&str> = event[1..]
+        .iter()
+        .map(String::as_str)
+        .filter(|line| exception_line_pattern().is_match(line))
+        .collect();
+    let text = std::iter::once(event[0].as_str())
+        .chain(exceptions.iter().copied())
+        .collect::<Vec<_>>()
+        .join("\n");
 
-    // Parse log file using regex patterns
-    for line in reader.lines().take(max_entries) {
-        let line = line?;
-        let line_lower = line.to_lowercase();
+    match classify_log_text(&text) {
+        Some("CRITICAL") => Some("CRITICAL"),
+        _ if !exceptions.is_empty() => Some("ERROR"),
+        level => level,
+    }
+}
 
-        // Skip empty lines and lines that are just URLs/paths
-        if line.trim().is_empty() || line.starts_with("http://") || line.starts_with("https://") {
-            continue;
-        }
+/// Group and classify plain-text lines read from one source
+fn plain_lines_to_entries(
+    lines: Vec<String>,
+    multiline: Option<&MultilineConfig>,
+    source: &str,
+) -> Result<Vec<LogEntry>> {
+    let boundary = multiline.map(EventBoundary::from_config).transpose()?;
+    let max_event_lines = multiline.map_or(1, |m| m.max_lines.max(1));
 
-        // Check each pattern in priority order (CRITICAL, ERROR, WARNING)
-        let mut matched_level = None;
-        for (level, pattern) in patterns {
-            if pattern.is_match(&line_lower) {
-                // Additional check to reduce false positives for ERROR level
-                if *level == "ERROR" {
-                    // Skip if it's just mentioning error in a benign context
-                    if line_lower.contains("error=0")
-                        || line_lower.contains("errors=0")
-                        || line_lower.contains("no error")
-                        || line_lower.contains("without error") {
-                        continue;
-                    }
-                }
-                matched_level = Some(level);
-                break;
-            }
-        }
+    let fallback_timestamp = Utc::now();
 
-        if let Some(level) = matched_level {
-            // Try to extract timestamp from the log line
-            let timestamp = extract_timestamp(&line).unwrap_or(fallback_timestamp);
+    // Skip empty lines and lines that are just URLs/paths
+    let lines: Vec<String> = lines
+        .into_iter()
+        .filter(|line| {
+            !(line.trim().is_empty() || line.starts_with("http://") || line.starts_with("https://"))
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    for event in group_log_lines(lines, boundary.as_ref(), max_event_lines) {
+        let headline = event[0].trim();
+        let message = if event.len() == 1 {
+            headline.to_string()
+        } else {
+            // Keep indentation of continuation lines so traces stay readable
+            std::iter::once(headline)
+                .chain(event[1..].iter().map(|l| l.trim_end()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        if let Some(level) = classify_event(&event) {
+            // Try to extract timestamp from the first line of the event
+            let timestamp = extract_timestamp(headline).unwrap_or(fallback_timestamp);
 
             entries.push(LogEntry {
                 timestamp,
                 level: level.to_string(),
-                source: source.clone(),
-                message: line.trim().to_string(),
+                source: source.to_string(),
+                message,
+                attributes: None,
             });
         }
     }
@@ -160,6 +366,168 @@ pub fn collect_log_entries<P: AsRef<Path>>(
     Ok(entries)
 }
 
+/// Map a JSON log level (string or numeric) onto a systers level
+/// Returns None for levels below WARNING (info, debug, trace, ...)
+pub fn map_json_level(level: &Value) -> Option<&'static str> {
+    match level {
+        Value::String(name) => match name.trim().to_lowercase().as_str() {
+            "fatal" | "panic" | "critical" | "crit" | "emerg" | "emergency" | "alert" => {
+                Some("CRITICAL")
+            }
+            "error" | "err" => Some("ERROR"),
+            "warn" | "warning" => Some("WARNING"),
+            _ => None,
+        },
+        // Numeric levels as used by bunyan/pino (60 fatal, 50 error, 40 warn)
+        Value::Number(number) => match number.as_u64()? {
+            60.. => Some("CRITICAL"),
+            50..=59 => Some("ERROR"),
+            40..=49 => Some("WARNING"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Look up a possibly dotted key (e.g. `log.level`) in a JSON object
+fn lookup_json_key<'a>(object: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    // Prefer a literal key so names that contain dots still work
+    if let Some(value) = object.get(key) {
+        return Some(value);
+    }
+
+    let mut parts = key.split('.');
+    let mut current = object.get(parts.next()?)?;
+    for part in parts {
+        current = current.as_object()?.get(part)?;
+    }
+    Some(current)
+}
+
+/// Parse a JSON timestamp (RFC 3339 string or Unix seconds/milliseconds)
+fn parse_json_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(text) => extract_timestamp(text),
+        Value::Number(number) => {
+            let raw = number.as_f64()?;
+            // Values this large are milliseconds since the epoch
+            let seconds = if raw.abs() >= 1e11 { raw / 1000.0 } else { raw };
+            Utc.timestamp_opt(seconds.trunc() as i64, (seconds.fract() * 1e9) as u32)
+                .single()
+        }
+        _ => None,
+    }
+}
+
+/// Parse a single JSON log line into a log entry
+/// Returns None for invalid JSON or levels below WARNING
+pub fn parse_json_log_line(
+    line: &str,
+    fields: &JsonLogConfig,
+    source: &str,
+    fallback_timestamp: DateTime<Utc>,
+) -> Option<LogEntry> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let object = value.as_object()?;
+
+    let level = map_json_level(lookup_json_key(object, &fields.level_key)?)?;
+
+    let timestamp = lookup_json_key(object, &fields.timestamp_key)
+        .and_then(parse_json_timestamp)
+        .unwrap_or(fallback_timestamp);
+
+    let message = match lookup_json_key(object, &fields.message_key) {
+        Some(Value::String(text)) => text.trim().to_string(),
+        Some(other) => other.to_string(),
+        None => line.trim().to_string(),
+    };
+
+    let attributes: Map<String, Value> = fields
+        .attributes
+        .iter()
+        .filter_map(|key| lookup_json_key(object, key).map(|v| (key.clone(), v.clone())))
+        .collect();
+
+    Some(LogEntry {
+        timestamp,
+        level: level.to_string(),
+        source: source.to_string(),
+        message,
+        attributes: if attributes.is_empty() {
+            None
+        } else {
+            Some(Value::Object(attributes))
+        },
+    })
+}
+
+/// Parse a log file containing one JSON object per line
+pub fn collect_json_log_entries<P: AsRef<Path>>(
+    log_path: P,
+    fields: &JsonLogConfig,
+    max_entries: usize,
+) -> Result<Vec<LogEntry>> {
+    let log_path_ref = log_path.as_ref();
+    let file = File::open(log_path_ref).context("Failed to open log file")?;
+    let reader = BufReader::new(file);
+
+    let lines = reader
+        .lines()
+        .take(max_entries)
+        .collect::<std::io::Result<Vec<String>>>()?;
+
+    Ok(json_lines_to_entries(&lines, fields, &log_path_ref.to_string_lossy()))
+}
+
+/// Parse JSON lines read from one source, skipping lines that are not objects
+fn json_lines_to_entries(lines: &[String], fields: &JsonLogConfig, source: &str) -> Vec<LogEntry> {
+    let mut entries = Vec::new();
+    let fallback_timestamp = Utc::now();
+    let mut invalid_lines = 0usize;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if !trimmed.starts_with('{') {
+            invalid_lines += 1;
+            continue;
+        }
+
+        if let Some(entry) = parse_json_log_line(trimmed, fields, source, fallback_timestamp) {
+            entries.push(entry);
+        }
+    }
+
+    if invalid_lines > 0 {
+        debug!("Skipped {} non-JSON lines in {}", invalid_lines, source);
+    }
+
+    entries
+}
+
+/// Parse lines already read from a log source (e.g. appended lines seen by the
+/// watcher) according to the source's format
+pub fn parse_log_lines(source: &LogSourceConfig, lines: Vec<String>) -> Result<Vec<LogEntry>> {
+    let name = source.path.to_string_lossy();
+    match source.format {
+        LogFormat::Plain => plain_lines_to_entries(lines, source.multiline.as_ref(), &name),
+        LogFormat::Json => Ok(json_lines_to_entries(&lines, &source.json, &name)),
+    }
+}
+
+/// Parse a configured log source according to its format
+pub fn collect_log_source(source: &LogSourceConfig, max_entries: usize) -> Result<Vec<LogEntry>> {
+    match source.format {
+        LogFormat::Plain => {
+            collect_log_events(&source.path, source.multiline.as_ref(), max_entries)
+        }
+        LogFormat::Json => collect_json_log_entries(&source.path, &source.json, max_entries),
+    }
+}
+
 /// Get default log file paths for common Linux distributions
 fn get_default_log_paths() -> Vec<&'static str> {
     vec![
@@ -170,52 +538,73 @@ fn get_default_log_paths() -> Vec<&'static str> {
     ]
 }
 
+/// Build the list of log sources to scan
+/// Plain paths come from custom_paths (or the default locations when None);
+/// a configured source replaces a plain path with the same location and any
+/// remaining configured sources are appended
+pub fn resolve_log_sources<P: AsRef<Path>>(
+    custom_paths: Option<&[P]>,
+    configured: &[LogSourceConfig],
+) -> Vec<LogSourceConfig> {
+    let plain_paths: Vec<&Path> = match custom_paths {
+        Some(paths) => paths.iter().map(|p| p.as_ref()).collect(),
+        // Default locations vary by distribution, so missing ones are skipped quietly
+        None => get_default_log_paths()
+            .into_iter()
+            .map(Path::new)
+            .filter(|p| p.exists())
+            .collect(),
+    };
+
+    let mut sources: Vec<LogSourceConfig> = plain_paths
+        .into_iter()
+        .map(|path| {
+            configured
+                .iter()
+                .find(|c| c.path == path)
+                .cloned()
+                .unwrap_or_else(|| LogSourceConfig::plain(path))
+        })
+        .collect();
+
+    for source in configured {
+        if !sources.iter().any(|s| s.path == source.path) {
+            sources.push(source.clone());
+        }
+    }
+
+    sources
+}
+
 /// Scan log files for issues
 /// If custom_paths is None, uses default Linux log locations
 pub fn scan_system_logs_with_paths<P: AsRef<Path>>(
     custom_paths: Option<&[P]>,
 ) -> Result<Vec<LogEntry>> {
+    scan_log_sources(&resolve_log_sources(custom_paths, &[]))
+}
+
+/// Scan configured log sources for issues, parsing each according to its format
+pub fn scan_log_sources(sources: &[LogSourceConfig]) -> Result<Vec<LogEntry>> {
     let mut all_entries = Vec::new();
 
-    if let Some(paths) = custom_paths {
-        // Use custom paths
-        for log_path in paths {
-            let path_ref = log_path.as_ref();
-            if path_ref.exists() {
-                match collect_log_entries(path_ref, MAX_LOG_LINES_PER_FILE) {
-                    Ok(mut entries) => all_entries.append(&mut entries),
-                    Err(e) => {
-                        if e.to_string().contains("Permission denied") {
-                            warn!(
-                                "Permission denied reading {}: Try running with sudo or add your user to the 'adm' group",
-                                path_ref.display()
-                            );
-                        } else {
-                            warn!("Could not read {}: {}", path_ref.display(), e);
-                        }
-                    }
-                }
-            } else {
-                warn!("Log file does not exist: {}", path_ref.display());
-            }
+    for source in sources {
+        let path_ref = source.path.as_path();
+        if !path_ref.exists() {
+            warn!("Log file does not exist: {}", path_ref.display());
+            continue;
         }
-    } else {
-        // Use default paths
-        let default_paths = get_default_log_paths();
-        for log_path in default_paths {
-            if Path::new(log_path).exists() {
-                match collect_log_entries(log_path, MAX_LOG_LINES_PER_FILE) {
-                    Ok(mut entries) => all_entries.append(&mut entries),
-                    Err(e) => {
-                        if e.to_string().contains("Permission denied") {
-                            warn!(
-                                "Permission denied reading {}: Try running with sudo or add your user to the 'adm' group",
-                                log_path
-                            );
-                        } else {
-                            warn!("Could not read {}: {}", log_path, e);
-                        }
-                    }
+
+        match collect_log_source(source, MAX_LOG_LINES_PER_FILE) {
+            Ok(mut entries) => all_entries.append(&mut entries),
+            Err(e) => {
+                if e.to_string().contains("Permission denied") {
+                    warn!(
+                        "Permission denied reading {}: Try running with sudo or add your user to the 'adm' group",
+                        path_ref.display()
+                    );
+                } else {
+                    warn!("Could not read {}: {}", path_ref.display(), e);
                 }
             }
         }
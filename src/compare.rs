@@ -0,0 +1,344 @@
+use crate::db::CheckStatus;
+use crate::reporter::{ExportFormat, FullReport, MetricStatistics};
+use crate::rollup::{METRIC_CPU, METRIC_DISK, METRIC_LOAD, METRIC_MEMORY, METRIC_PROCESSES};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// An increase counts as a significant regression when it is at least this
+/// fraction of the baseline value and above the metric's noise floor
+const REGRESSION_RATIO: f64 = 0.25;
+
+/// Increase in minutes above threshold that counts as a regression
+const MINUTES_ABOVE_FLOOR: f64 = 15.0;
+
+/// Compared metrics: key, label, unit, decimals and the smallest change that matters
+const METRICS: [(&str, &str, &str, usize, f64); 5] = [
+    (METRIC_CPU, "CPU Usage", "%", 1, 5.0),
+    (METRIC_MEMORY, "Memory Usage", "%", 1, 5.0),
+    (METRIC_DISK, "Disk Usage", "%", 1, 2.0),
+    (METRIC_LOAD, "Load Average (1 min)", "", 2, 0.5),
+    (METRIC_PROCESSES, "Process Count", "", 0, 20.0),
+];
+
+/// Compared statistics and whether an increase can be flagged as a regression
+const STATISTICS: [(&str, &str, bool); 9] = [
+    ("avg", "Average", true),
+    ("min", "Min", false),
+    ("p50", "p50", false),
+    ("p90", "p90", true),
+    ("p95", "p95", true),
+    ("p99", "p99", true),
+    ("max", "Peak", true),
+    ("std_dev", "Std Dev", false),
+    ("minutes_above_threshold", "Above threshold", true),
+];
+
+/// Compared log counts: key, label and the smallest increase that matters
+const LOG_LEVELS: [(&str, &str, usize); 3] = [
+    ("critical", "Critical", 1),
+    ("errors", "Errors", 5),
+    ("warnings", "Warnings", 10),
+];
+
+/// Change of one statistic of one metric between the two periods
+#[derive(Debug, Clone, Serialize)]
+pub struct StatisticDelta {
+    pub metric: String,
+    pub statistic: String,
+    pub baseline: f64,
+    pub current: f64,
+    pub change: f64,
+    /// None when the baseline value is zero
+    pub percent_change: Option<f64>,
+    pub regression: bool,
+}
+
+/// Change of a log entry count between the two periods
+#[derive(Debug, Clone, Serialize)]
+pub struct CountDelta {
+    pub name: String,
+    pub baseline: usize,
+    pub current: usize,
+    pub change: i64,
+    pub regression: bool,
+}
+
+/// A check whose latest status differs between the two periods
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckChange {
+    pub check_name: String,
+    /// None when the check did not run in that period
+    pub baseline: Option<CheckStatus>,
+    pub current: Option<CheckStatus>,
+    pub regression: bool,
+}
+
+/// Two report periods and how the current one differs from the baseline
+#[derive(Debug, Serialize)]
+pub struct ComparisonReport {
+    pub version: String,
+    pub baseline: FullReport,
+    pub current: FullReport,
+    pub metrics: Vec<StatisticDelta>,
+    pub log_counts: Vec<CountDelta>,
+    pub checks: Vec<CheckChange>,
+}
+
+impl ComparisonReport {
+    /// Number of significant regressions across metrics, logs and checks
+    pub fn regressions(&self) -> usize {
+        self.metrics.iter().filter(|d| d.regression).count()
+            + self.log_counts.iter().filter(|d| d.regression).count()
+            + self.checks.iter().filter(|c| c.regression).count()
+    }
+}
+
+/// Compute the deltas between a baseline and a current report
+pub fn compare_reports(baseline: FullReport, current: FullReport) -> ComparisonReport {
+    let mut metrics = Vec::new();
+    for (metric, _, _, _, floor) in METRICS {
+        let (Some(before), Some(after)) = (
+            baseline.metrics.statistics.get(metric),
+            current.metrics.statistics.get(metric),
+        ) else {
+            continue;
+        };
+        for (statistic, _, flagged) in STATISTICS {
+            let (Some(old), Some(new)) = (statistic_value(before, statistic), statistic_value(after, statistic))
+            else {
+                continue;
+            };
+            let floor = if statistic == "minutes_above_threshold" {
+                MINUTES_ABOVE_FLOOR
+            } else {
+                floor
+            };
+            metrics.push(StatisticDelta {
+                metric: metric.to_string(),
+                statistic: statistic.to_string(),
+                baseline: old,
+                current: new,
+                change: new - old,
+                percent_change: (old != 0.0).then(|| (new - old) / old.abs() * 100.0),
+                regression: flagged && is_regression(old, new, floor),
+            });
+        }
+    }
+
+    let log_counts = LOG_LEVELS
+        .into_iter()
+        .map(|(name, _, floor)| {
+            let count = |report: &FullReport| match name {
+                "critical" => report.logs.total_critical,
+                "errors" => report.logs.total_errors,
+                _ => report.logs.total_warnings,
+            };
+            let (old, new) = (count(&baseline), count(&current));
+            CountDelta {
+                name: name.to_string(),
+                baseline: old,
+                current: new,
+                change: new as i64 - old as i64,
+                regression: is_regression(old as f64, new as f64, floor as f64),
+            }
+        })
+        .collect();
+
+    let mut statuses: BTreeMap<String, (Option<CheckStatus>, Option<CheckStatus>)> =
+        BTreeMap::new();
+    for check in baseline.system_checks.latest() {
+        statuses.entry(check.check_name.clone()).or_default().0 = Some(check.status);
+    }
+    for check in current.system_checks.latest() {
+        statuses.entry(check.check_name.clone()).or_default().1 = Some(check.status);
+    }
+    let checks = statuses
+        .into_iter()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(check_name, (old, new))| CheckChange {
+            regression: new.map_or(0, severity) > old.map_or(0, severity),
+            check_name,
+            baseline: old,
+            current: new,
+        })
+        .collect();
+
+    ComparisonReport {
+        version: crate::VERSION.to_string(),
+        baseline,
+        current,
+        metrics,
+        log_counts,
+        checks,
+    }
+}
+
+fn statistic_value(stats: &MetricStatistics, statistic: &str) -> Option<f64> {
+    match statistic {
+        "avg" => Some(stats.avg),
+        "min" => Some(stats.min),
+        "p50" => Some(stats.p50),
+        "p90" => Some(stats.p90),
+        "p95" => Some(stats.p95),
+        "p99" => Some(stats.p99),
+        "max" => Some(stats.max),
+        "std_dev" => Some(stats.std_dev),
+        "minutes_above_threshold" => stats.above_threshold.as_ref().map(|a| a.minutes),
+        _ => None,
+    }
+}
+
+fn is_regression(baseline: f64, current: f64, floor: f64) -> bool {
+    let change = current - baseline;
+    change >= floor && change >= baseline.abs() * REGRESSION_RATIO
+}
+
+/// How bad a check status is; unknown ranks just above ok
+fn severity(status: CheckStatus) -> u8 {
+    match status {
+        CheckStatus::Ok => 0,
+        CheckStatus::Unknown => 1,
+        CheckStatus::Warning => 2,
+        CheckStatus::Error => 3,
+        CheckStatus::Critical => 4,
+    }
+}
+
+/// Export a comparison in the specified format (text or JSON)
+pub fn export_comparison(report: &ComparisonReport, format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(report)
+            .context("Failed to serialize comparison to JSON"),
+        ExportFormat::Text => Ok(format_comparison(report)),
+        _ => Err(anyhow!("Comparison reports support the text and json formats")),
+    }
+}
+
+fn period(report: &FullReport) -> String {
+    let start = report.timezone.convert(report.metrics.period_start);
+    let end = report.timezone.convert(report.metrics.period_end);
+    format!(
+        "{} to {}",
+        start.format("%Y-%m-%d %H:%M:%S %Z"),
+        end.format("%Y-%m-%d %H:%M:%S %Z")
+    )
+}
+
+fn signed(value: f64, decimals: usize) -> String {
+    if value >= 0.0 {
+        format!("+{:.*}", decimals, value)
+    } else {
+        format!("{:.*}", decimals, value)
+    }
+}
+
+/// Format a comparison for terminal display
+pub fn format_comparison(report: &ComparisonReport) -> String {
+    let mut output = String::new();
+
+    output.push_str("╔════════════════════════════════════════════════════════════════╗\n");
+    output.push_str(&format!(
+        "║         SYSTERS v{:<6} - PERIOD COMPARISON               ║\n",
+        crate::VERSION
+    ));
+    output.push_str("╚════════════════════════════════════════════════════════════════╝\n\n");
+    output.push_str(&format!("Baseline: {}\n", period(&report.baseline)));
+    output.push_str(&format!("Current:  {}\n\n", period(&report.current)));
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  SYSTEM METRICS\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+    if report.metrics.is_empty() {
+        output.push_str("No metrics available in one of the periods\n\n");
+    }
+    output.push_str(&format!(
+        "  {:<20} {:>10} {:>10} {:>18}\n",
+        "", "Baseline", "Current", "Change"
+    ));
+    for (metric, label, unit, decimals, _) in METRICS {
+        let deltas: Vec<&StatisticDelta> =
+            report.metrics.iter().filter(|d| d.metric == metric).collect();
+        if deltas.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("{}:\n", label));
+        for delta in deltas {
+            let (name, unit, decimals) = match STATISTICS.iter().find(|s| s.0 == delta.statistic) {
+                Some((key, name, _)) if *key == "minutes_above_threshold" => (*name, " min", 0),
+                Some((_, name, _)) => (*name, unit, decimals),
+                None => (delta.statistic.as_str(), unit, decimals),
+            };
+            let change = match delta.percent_change {
+                Some(percent) => format!("{} ({}%)", signed(delta.change, decimals), signed(percent, 0)),
+                None => signed(delta.change, decimals),
+            };
+            output.push_str(&format!(
+                "  {:<20} {:>10} {:>10} {:>18}{}\n",
+                name,
+                format!("{:.*}{}", decimals, delta.baseline, unit),
+                format!("{:.*}{}", decimals, delta.current, unit),
+                change,
+                if delta.regression { "  🔴" } else { "" }
+            ));
+        }
+        output.push('\n');
+    }
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  LOG ANALYSIS\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+    for delta in &report.log_counts {
+        let label = LOG_LEVELS
+            .iter()
+            .find(|level| level.0 == delta.name)
+            .map_or(delta.name.as_str(), |level| level.1);
+        output.push_str(&format!(
+            "  {:<20} {:>10} {:>10} {:>18}{}\n",
+            label,
+            delta.baseline,
+            delta.current,
+            signed(delta.change as f64, 0),
+            if delta.regression { "  🔴" } else { "" }
+        ));
+    }
+    output.push('\n');
+
+    if !report.checks.is_empty() {
+        output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+        output.push_str("  SYSTEM CHECK CHANGES\n");
+        output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+        let status = |s: Option<CheckStatus>| s.map_or("not run", |s| s.as_str());
+        for change in &report.checks {
+            output.push_str(&format!(
+                "  {}: {} -> {}{}\n",
+                change.check_name,
+                status(change.baseline),
+                status(change.current),
+                if change.regression { "  🔴" } else { "" }
+            ));
+        }
+        output.push('\n');
+    }
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  SUMMARY\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+    match report.regressions() {
+        0 => output.push_str("✓ No significant regressions compared to the baseline\n\n"),
+        count => output.push_str(&format!(
+            "🔴 {} significant regression{} compared to the baseline\n\n",
+            count,
+            if count == 1 { "" } else { "s" }
+        )),
+    }
+
+    output
+}
+
+/// Window of the same length immediately before `start..end`
+pub fn previous_period(start: DateTime<Utc>, end: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    (start - (end - start), start)
+}
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db::ConnectionOptions;
 
 /// Main configuration structure for Systers
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +15,14 @@ pub struct Config {
     pub collection: CollectionConfig,
     pub display: DisplayConfig,
     pub retention: RetentionConfig,
+    #[serde(default)]
+    pub watch: WatchConfig,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    #[serde(default)]
+    pub forecast: ForecastConfig,
+    #[serde(default)]
+    pub anomaly: AnomalyConfig,
 }
 
 /// Database configuration
@@ -20,6 +31,77 @@ pub struct DatabaseConfig {
     /// Path to the SQLite database file
     /// Can be overridden by SYSTERS_DB_PATH env var or --db-path CLI flag
     pub path: PathBuf,
+
+    /// Milliseconds to wait for a lock held by another process before
+    /// failing with "database is locked"
+    #[serde(default = "default_busy_timeout_ms")]
+    pub busy_timeout_ms: u64,
+
+    /// File holding the SQLCipher key (requires the `encryption` feature)
+    /// Can be overridden by SYSTERS_DB_KEY_FILE, or the key given directly in SYSTERS_DB_KEY
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+}
+
+impl DatabaseConfig {
+    /// Busy timeout as a Duration
+    pub fn busy_timeout(&self) -> Duration {
+        Duration::from_millis(self.busy_timeout_ms)
+    }
+
+    /// Encryption key from SYSTERS_DB_KEY, SYSTERS_DB_KEY_FILE or `key_file`, in that order
+    /// Returns `None` when the database is not encrypted
+    pub fn encryption_key(&self) -> Result<Option<String>> {
+        if let Ok(key) = env::var(DB_KEY_ENV) {
+            if !key.is_empty() {
+                return Ok(Some(key));
+            }
+        }
+
+        let key_file = env::var_os(DB_KEY_FILE_ENV)
+            .map(PathBuf::from)
+            .or_else(|| self.key_file.clone());
+        let Some(path) = key_file else {
+            return Ok(None);
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path)
+                .context(format!("Failed to read key file {}", path.display()))?
+                .permissions()
+                .mode();
+            if mode & 0o077 != 0 {
+                log::warn!(
+                    "Key file {} is readable by other users (mode {:o}); run: chmod 600 {}",
+                    path.display(),
+                    mode & 0o777,
+                    path.display()
+                );
+            }
+        }
+
+        let key = fs::read_to_string(&path)
+            .context(format!("Failed to read key file {}", path.display()))?;
+        let key = key.trim_end_matches(['\r', '\n']);
+        if key.is_empty() {
+            anyhow::bail!("Key file {} is empty", path.display());
+        }
+        Ok(Some(key.to_string()))
+    }
+
+    /// Busy timeout and encryption key for opening the database
+    pub fn connection_options(&self) -> Result<ConnectionOptions> {
+        Ok(ConnectionOptions {
+            busy_timeout: self.busy_timeout(),
+            key: self.encryption_key()?,
+        })
+    }
+}
+
+fn default_busy_timeout_ms() -> u64 {
+    DEFAULT_BUSY_TIMEOUT_MS
 }
 
 /// Issue detection thresholds
@@ -54,6 +136,125 @@ pub struct CollectionConfig {
     /// CPU measurement delay in milliseconds
     /// The sysinfo crate needs at least one refresh cycle to compute CPU percentage
     pub cpu_measurement_delay_ms: u64,
+
+    /// Per-path parsing options for log files that are not plain text
+    /// Paths listed here are scanned in addition to `log_paths`
+    #[serde(default)]
+    pub log_sources: Vec<LogSourceConfig>,
+
+    /// Minutes between scheduled syswriter runs (cron or systemd timer)
+    /// sysreport reports a gap when expected runs are missing
+    #[serde(default = "default_collection_interval_minutes")]
+    pub interval_minutes: u64,
+}
+
+impl CollectionConfig {
+    /// Expected time between runs
+    pub fn interval(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.interval_minutes as i64)
+    }
+}
+
+fn default_collection_interval_minutes() -> u64 {
+    DEFAULT_COLLECTION_INTERVAL_MINUTES
+}
+
+/// Format of the lines in a log file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Free-form text classified by keyword patterns
+    #[default]
+    Plain,
+    /// One JSON object per line
+    Json,
+}
+
+/// Parsing options for a single log file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSourceConfig {
+    /// Path to the log file
+    pub path: PathBuf,
+
+    /// Line format of the file (plain or json)
+    #[serde(default)]
+    pub format: LogFormat,
+
+    /// Field mapping used when `format` is `json`
+    #[serde(default)]
+    pub json: JsonLogConfig,
+
+    /// Merge multi-line events (stack traces, panics) into one entry (plain format only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiline: Option<MultilineConfig>,
+}
+
+impl LogSourceConfig {
+    /// Plain-text source for a path with default options
+    pub fn plain<P: Into<PathBuf>>(path: P) -> Self {
+        LogSourceConfig {
+            path: path.into(),
+            format: LogFormat::Plain,
+            json: JsonLogConfig::default(),
+            multiline: None,
+        }
+    }
+}
+
+/// Field mapping for JSON log lines
+/// Keys may use dots to reach nested objects (e.g. `log.level`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonLogConfig {
+    /// Key holding the log level (string like "warn" or numeric like 50)
+    pub level_key: String,
+
+    /// Key holding the timestamp (RFC 3339 string or Unix seconds/milliseconds)
+    pub timestamp_key: String,
+
+    /// Key holding the human-readable message
+    pub message_key: String,
+
+    /// Extra keys to keep in the `attributes` column
+    pub attributes: Vec<String>,
+}
+
+/// Rules for grouping consecutive lines into one log event
+/// Set either `start_pattern` or `continuation_pattern`; when neither is set
+/// indented lines, "Caused by:" and "Traceback" lines and the exception
+/// line ending a traceback continue an event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultilineConfig {
+    /// Regex matching the first line of an event; other lines are continuations
+    pub start_pattern: Option<String>,
+
+    /// Regex matching continuation lines; other lines start a new event
+    pub continuation_pattern: Option<String>,
+
+    /// Maximum number of lines kept per event (longer traces are cut off)
+    pub max_lines: usize,
+}
+
+impl Default for MultilineConfig {
+    fn default() -> Self {
+        MultilineConfig {
+            start_pattern: None,
+            continuation_pattern: None,
+            max_lines: 200,
+        }
+    }
+}
+
+impl Default for JsonLogConfig {
+    fn default() -> Self {
+        JsonLogConfig {
+            level_key: "level".to_string(),
+            timestamp_key: "timestamp".to_string(),
+            message_key: "message".to_string(),
+            attributes: Vec::new(),
+        }
+    }
 }
 
 /// Display configuration
@@ -66,8 +267,90 @@ pub struct DisplayConfig {
 /// Data retention configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetentionConfig {
-    /// Number of days to retain historical data
+    /// Number of days to retain raw samples and log entries
     pub days: i64,
+
+    /// Number of days to retain hourly rollups (daily rollups are kept forever)
+    #[serde(default = "default_hourly_retention_days")]
+    pub hourly_days: i64,
+
+    /// Number of days to retain rows merged from other hosts (unset keeps them forever)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_days: Option<i64>,
+}
+
+fn default_hourly_retention_days() -> i64 {
+    DEFAULT_HOURLY_RETENTION_DAYS
+}
+
+/// Live log watching configuration (syswriter --watch)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// Number of new log entries buffered before they are written to the database
+    pub batch_size: usize,
+
+    /// Maximum seconds a buffered log entry waits before being written
+    pub flush_interval_secs: u64,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        WatchConfig {
+            batch_size: 100,
+            flush_interval_secs: 5,
+        }
+    }
+}
+
+/// Alerting configuration for CRITICAL log entries seen while watching
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// Shell command run for each alert; the entry is passed in the
+    /// SYSTERS_ALERT_LEVEL, SYSTERS_ALERT_SOURCE, SYSTERS_ALERT_TIMESTAMP and
+    /// SYSTERS_ALERT_MESSAGE environment variables
+    pub command: Option<String>,
+}
+
+/// Disk fill forecasting in reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForecastConfig {
+    /// Days of per-mount disk usage the growth trend is fitted on
+    pub lookback_days: i64,
+
+    /// Mount points projected to be full within this many days are flagged
+    pub horizon_days: i64,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        ForecastConfig {
+            lookback_days: DEFAULT_FORECAST_LOOKBACK_DAYS,
+            horizon_days: DEFAULT_FORECAST_HORIZON_DAYS,
+        }
+    }
+}
+
+/// Anomaly detection against learned per-hour-of-week baselines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    /// Days of raw samples the baselines are learned from
+    pub lookback_days: i64,
+
+    /// Samples further than this many (scaled) MADs from the baseline median are anomalies
+    pub mad_multiplier: f64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        AnomalyConfig {
+            lookback_days: DEFAULT_BASELINE_LOOKBACK_DAYS,
+            mad_multiplier: DEFAULT_ANOMALY_MAD_MULTIPLIER,
+        }
+    }
 }
 
 impl Default for Config {
@@ -76,6 +359,8 @@ impl Default for Config {
         Config {
             database: DatabaseConfig {
                 path: Self::default_db_path(),
+                busy_timeout_ms: DEFAULT_BUSY_TIMEOUT_MS,
+                key_file: None,
             },
             thresholds: ThresholdsConfig {
                 cpu_warning: 90.0,
@@ -93,13 +378,21 @@ impl Default for Config {
                 ],
                 max_log_lines_per_file: 1000,
                 cpu_measurement_delay_ms: 200,
+                log_sources: Vec::new(),
+                interval_minutes: DEFAULT_COLLECTION_INTERVAL_MINUTES,
             },
             display: DisplayConfig {
                 max_recent_errors: 10,
             },
             retention: RetentionConfig {
                 days: 30,
+                hourly_days: DEFAULT_HOURLY_RETENTION_DAYS,
+                merged_days: None,
             },
+            watch: WatchConfig::default(),
+            alerts: AlertConfig::default(),
+            forecast: ForecastConfig::default(),
+            anomaly: AnomalyConfig::default(),
         }
     }
 }
@@ -136,8 +429,9 @@ impl Config {
     /// 4. Built-in defaults
     pub fn load() -> Result<Self> {
         // Try current directory
-        if let Ok(config) = Self::load_from_file("systers.yaml") {
-            return Ok(config);
+        let local_config = PathBuf::from("systers.yaml");
+        if local_config.exists() {
+            return Self::load_from_file(&local_config);
         }
 
         // Try user config directory
@@ -182,12 +476,31 @@ impl Config {
 pub const CPU_WARNING_THRESHOLD: f32 = 90.0;
 pub const MEMORY_WARNING_THRESHOLD: f32 = 90.0;
 pub const DISK_WARNING_THRESHOLD: f32 = 85.0;
+pub const SWAP_WARNING_THRESHOLD: f32 = 50.0;
 pub const LOAD_WARNING_THRESHOLD: f64 = 5.0;
 pub const MAX_LOG_LINES_PER_FILE: usize = 1000;
 pub const MAX_RECENT_ERRORS_DISPLAY: usize = 10;
 pub const CPU_MEASUREMENT_DELAY_MS: u64 = 200;
 pub const ERROR_COUNT_THRESHOLD: usize = 10;
 pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+pub const DEFAULT_HOURLY_RETENTION_DAYS: i64 = 90;
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+pub const DEFAULT_COLLECTION_INTERVAL_MINUTES: u64 = 60;
+pub const DEFAULT_FORECAST_LOOKBACK_DAYS: i64 = 14;
+pub const DEFAULT_FORECAST_HORIZON_DAYS: i64 = 30;
+pub const DEFAULT_BASELINE_LOOKBACK_DAYS: i64 = 28;
+pub const DEFAULT_ANOMALY_MAD_MULTIPLIER: f64 = 3.0;
+pub const DEFAULT_INCIDENT_GAP_MINUTES: i64 = 15;
+
+/// Environment variable holding the database encryption key
+pub const DB_KEY_ENV: &str = "SYSTERS_DB_KEY";
+/// Environment variable naming a file that holds the database encryption key
+pub const DB_KEY_FILE_ENV: &str = "SYSTERS_DB_KEY_FILE";
+
+/// Minimum failed SSH logins from one address to count as a brute-force burst
+pub const BRUTE_FORCE_MIN_ATTEMPTS: usize = 10;
+/// Maximum seconds between failed logins within one brute-force burst
+pub const BRUTE_FORCE_MAX_GAP_SECONDS: i64 = 60;
 
 #[cfg(test)]
 mod tests {
@@ -227,6 +540,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_json_log_source_config() -> Result<()> {
+        let yaml = r#"
+path: /var/log/myapp/app.json
+format: json
+json:
+  level_key: severity
+  attributes: [request_id, user]
+"#;
+        let source: LogSourceConfig = serde_yaml::from_str(yaml)?;
+
+        assert_eq!(source.format, LogFormat::Json);
+        assert_eq!(source.json.level_key, "severity");
+        // Unspecified keys keep their defaults
+        assert_eq!(source.json.message_key, "message");
+        assert_eq!(source.json.attributes, vec!["request_id", "user"]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_load_nonexistent_file_returns_default() -> Result<()> {
         let config = Config::load_from_file("/nonexistent/path/config.yaml")?;
@@ -236,4 +569,29 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_encryption_key_from_file() -> Result<()> {
+        // The environment takes precedence over the configured file
+        if env::var_os(DB_KEY_ENV).is_some() || env::var_os(DB_KEY_FILE_ENV).is_some() {
+            return Ok(());
+        }
+
+        let temp_dir = TempDir::new()?;
+        let key_path = temp_dir.path().join("db.key");
+        fs::write(&key_path, "correct horse battery staple\n")?;
+
+        let mut config = Config::default();
+        assert!(config.database.encryption_key()?.is_none());
+
+        config.database.key_file = Some(key_path.clone());
+        let options = config.database.connection_options()?;
+        assert_eq!(options.key.as_deref(), Some("correct horse battery staple"));
+        assert!(!format!("{:?}", options).contains("horse"));
+
+        fs::write(&key_path, "\n")?;
+        assert!(config.database.encryption_key().is_err());
+
+        Ok(())
+    }
 }
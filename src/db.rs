@@ -1,12 +1,74 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, TimeZone, Utc};
 use log::{info, warn};
-use rusqlite::{params, Connection};
-use serde::Serialize;
-use std::path::Path;
+use rusqlite::{
+    params, Connection, OpenFlags, OptionalExtension, Transaction, TransactionBehavior,
+};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::DEFAULT_BUSY_TIMEOUT_MS;
+use crate::migrations::{get_schema_version, migrate_to};
 
 /// Database schema version
-pub const SCHEMA_VERSION: i32 = 3;
+pub const SCHEMA_VERSION: i32 = 12;
+
+/// Which host's rows a query reads
+/// Rows collected on this machine have an empty host; rows imported by
+/// `systers db merge` carry the name of the host they came from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HostFilter {
+    /// Rows collected on this machine
+    #[default]
+    Local,
+    /// Rows merged from one other host
+    Named(String),
+    /// Rows of every host
+    All,
+}
+
+impl HostFilter {
+    /// Value bound to the `(?N IS NULL OR host = ?N)` condition of a query
+    fn param(&self) -> Option<&str> {
+        match self {
+            HostFilter::Local => Some(""),
+            HostFilter::Named(name) => Some(name),
+            HostFilter::All => None,
+        }
+    }
+
+    pub fn is_local(&self) -> bool {
+        *self == HostFilter::Local
+    }
+}
+
+impl std::str::FromStr for HostFilter {
+    type Err = anyhow::Error;
+
+    /// `all`, `local`, or a host name; this machine's own name means local
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "" => Err(anyhow::anyhow!("Host name must not be empty")),
+            "all" => Ok(HostFilter::All),
+            "local" => Ok(HostFilter::Local),
+            name if name == crate::collector::hostname() => Ok(HostFilter::Local),
+            name => Ok(HostFilter::Named(name.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for HostFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostFilter::Local => write!(f, "local"),
+            HostFilter::Named(name) => write!(f, "{}", name),
+            HostFilter::All => write!(f, "all"),
+        }
+    }
+}
 
 /// System metrics record
 #[derive(Debug, Clone, Serialize)]
@@ -24,6 +86,38 @@ pub struct SystemMetrics {
     pub load_avg_15min: f64,
 }
 
+/// Space used on one mounted filesystem at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiskUsage {
+    pub timestamp: DateTime<Utc>,
+    pub mount_point: String,
+    pub filesystem: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+}
+
+impl DiskUsage {
+    pub fn used_percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.used_bytes as f64 / self.total_bytes as f64 * 100.0
+    }
+}
+
+/// Learned usual value of a metric at one hour of the week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricBaseline {
+    pub metric: String,
+    /// Hours since Monday 00:00 local time (0-167)
+    pub hour_of_week: u32,
+    pub samples: usize,
+    pub median: f64,
+    /// Median absolute deviation from the median
+    pub mad: f64,
+    pub built_at: DateTime<Utc>,
+}
+
 /// Log entry record
 #[derive(Debug, Clone, Serialize)]
 pub struct LogEntry {
@@ -31,6 +125,137 @@ pub struct LogEntry {
     pub level: String,
     pub source: String,
     pub message: String,
+    /// Extra structured fields kept from JSON logs
+    pub attributes: Option<serde_json::Value>,
+}
+
+impl LogEntry {
+    /// First line of the message (the headline of a multi-line event)
+    pub fn headline(&self) -> &str {
+        self.message.lines().next().unwrap_or("")
+    }
+
+    /// Number of lines following the headline (e.g. stack trace frames)
+    pub fn continuation_lines(&self) -> usize {
+        self.message.lines().count().saturating_sub(1)
+    }
+}
+
+/// Granularity of stored metrics and log counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    /// Individual samples and log entries
+    Raw,
+    /// One rollup per hour
+    Hourly,
+    /// One rollup per day (UTC)
+    Daily,
+}
+
+impl Resolution {
+    /// Name stored in the rollup tables
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Raw => "raw",
+            Resolution::Hourly => "hourly",
+            Resolution::Daily => "daily",
+        }
+    }
+
+    /// Width of one bucket in seconds (0 for raw data)
+    pub fn bucket_seconds(&self) -> i64 {
+        match self {
+            Resolution::Raw => 0,
+            Resolution::Hourly => 3600,
+            Resolution::Daily => 86400,
+        }
+    }
+}
+
+/// Summary of one metric over a rollup bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricRollup {
+    pub resolution: Resolution,
+    pub bucket_start: DateTime<Utc>,
+    pub metric: String,
+    pub samples: usize,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p95: f64,
+}
+
+/// Number of log entries of one level over a rollup bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRollup {
+    pub resolution: Resolution,
+    pub bucket_start: DateTime<Utc>,
+    pub level: String,
+    pub count: usize,
+}
+
+/// Outcome of a system check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Critical,
+    /// The check itself could not run
+    Error,
+    Unknown,
+}
+
+impl CheckStatus {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Critical => "critical",
+            CheckStatus::Error => "error",
+            CheckStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Parse a stored or legacy status; informational results count as ok
+    /// and anything unrecognised is unknown
+    pub fn parse(status: &str) -> Self {
+        match status.trim().to_lowercase().as_str() {
+            "ok" | "info" => CheckStatus::Ok,
+            "warning" | "warn" => CheckStatus::Warning,
+            "critical" => CheckStatus::Critical,
+            "error" => CheckStatus::Error,
+            _ => CheckStatus::Unknown,
+        }
+    }
+
+    /// Whether the result needs attention (warning, critical or error)
+    pub fn is_problem(&self) -> bool {
+        matches!(
+            self,
+            CheckStatus::Warning | CheckStatus::Critical | CheckStatus::Error
+        )
+    }
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl rusqlite::types::ToSql for CheckStatus {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl rusqlite::types::FromSql for CheckStatus {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        value.as_str().map(CheckStatus::parse)
+    }
 }
 
 /// System check result record
@@ -39,412 +264,660 @@ pub struct SystemCheckResult {
     pub timestamp: DateTime<Utc>,
     pub check_name: String,
     pub check_type: String,
-    pub status: String,
-    pub value: Option<String>,
+    pub status: CheckStatus,
+    /// Numeric reading, e.g. the number of pending updates or failed disks
+    pub value: Option<f64>,
+    /// Unit of `value` (e.g. "updates", "failed services")
+    pub unit: Option<String>,
+    /// Structured payload, e.g. the full SMART results per disk
+    pub details: Option<serde_json::Value>,
     pub message: String,
 }
 
-/// Get current schema version from database
-fn get_schema_version(conn: &Connection) -> Result<i32> {
-    // Check if schema_version table exists
-    let table_exists: bool = conn
-        .query_row(
-            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='schema_version'",
-            [],
-            |row| {
-                let count: i32 = row.get(0)?;
-                Ok(count > 0)
-            },
-        )?;
+/// Authentication event parsed from auth.log (sshd and sudo)
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthEvent {
+    pub timestamp: DateTime<Utc>,
+    /// One of the `EVENT_*` constants in the `auth` module
+    pub event_type: String,
+    pub username: Option<String>,
+    pub source_ip: Option<String>,
+    /// Authentication method for logins (password, publickey, ...)
+    pub method: Option<String>,
+    /// Command line for sudo events
+    pub command: Option<String>,
+    pub message: String,
+}
 
-    if !table_exists {
-        return Ok(0); // Fresh database
-    }
+/// Structured kernel event (OOM kill, segfault, hardware/I-O/filesystem error)
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelEvent {
+    pub timestamp: DateTime<Utc>,
+    /// One of the `EVENT_*` constants in the `kernel` module
+    pub event_type: String,
+    pub process: Option<String>,
+    pub pid: Option<i64>,
+    pub device: Option<String>,
+    /// Event-specific fields (memory usage, fault addresses, sector, ...)
+    pub details: Option<serde_json::Value>,
+    /// Kernel message without the syslog header
+    pub message: String,
+}
 
-    // Try to get version
-    match conn.query_row("SELECT version FROM schema_version", [], |row| row.get(0)) {
-        Ok(version) => Ok(version),
-        Err(_) => Ok(0),
-    }
+/// Run status while a collection is still in progress (or was interrupted)
+pub const RUN_RUNNING: &str = "running";
+/// Every collector succeeded
+pub const RUN_OK: &str = "ok";
+/// The run was stored but at least one collector failed
+pub const RUN_PARTIAL: &str = "partial";
+/// The run failed and nothing was stored
+pub const RUN_FAILED: &str = "failed";
+
+/// Scheduled one-shot collection
+pub const RUN_MODE_COLLECT: &str = "collect";
+/// Long-running `syswriter --watch` session
+pub const RUN_MODE_WATCH: &str = "watch";
+
+/// Outcome of one collector (metrics, logs, auth, ...) within a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectorOutcome {
+    pub name: String,
+    /// Error text when the collector failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Rows this collector stored
+    pub rows: usize,
 }
 
-/// Migrate from schema v1 to v2 (TEXT timestamps to INTEGER timestamps)
-fn migrate_v1_to_v2(conn: &Connection) -> Result<()> {
-    info!("Migrating database from schema v1 to v2...");
+impl CollectorOutcome {
+    pub fn ok(name: &str, rows: usize) -> Self {
+        CollectorOutcome {
+            name: name.to_string(),
+            error: None,
+            rows,
+        }
+    }
 
-    // Create new tables with INTEGER timestamps
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS system_metrics_v2 (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            cpu_usage REAL NOT NULL,
-            memory_total INTEGER NOT NULL,
-            memory_used INTEGER NOT NULL,
-            memory_available INTEGER NOT NULL,
-            disk_total INTEGER NOT NULL,
-            disk_used INTEGER NOT NULL,
-            process_count INTEGER NOT NULL,
-            load_avg_1min REAL NOT NULL,
-            load_avg_5min REAL NOT NULL,
-            load_avg_15min REAL NOT NULL
-        )",
-        [],
-    )?;
+    pub fn failed(name: &str, error: impl ToString, rows: usize) -> Self {
+        CollectorOutcome {
+            name: name.to_string(),
+            error: Some(error.to_string()),
+            rows,
+        }
+    }
+}
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS log_entries_v2 (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            level TEXT NOT NULL,
-            source TEXT NOT NULL,
-            message TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    // Migrate system_metrics data
-    // SQLite can't parse RFC3339 directly, so we need to convert via Rust
-    let mut stmt = conn.prepare("SELECT * FROM system_metrics")?;
-    let metrics_iter = stmt.query_map([], |row| {
-        let timestamp_str: String = row.get(1)?;
-        Ok((
-            row.get::<_, i32>(0)?, // id
-            timestamp_str,
-            row.get::<_, f32>(2)?,  // cpu_usage
-            row.get::<_, i64>(3)?,  // memory_total
-            row.get::<_, i64>(4)?,  // memory_used
-            row.get::<_, i64>(5)?,  // memory_available
-            row.get::<_, i64>(6)?,  // disk_total
-            row.get::<_, i64>(7)?,  // disk_used
-            row.get::<_, i32>(8)?,  // process_count
-            row.get::<_, f64>(9)?,  // load_avg_1min
-            row.get::<_, f64>(10)?, // load_avg_5min
-            row.get::<_, f64>(11)?, // load_avg_15min
-        ))
-    })?;
+/// One syswriter invocation recorded in the collection_runs ledger
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionRun {
+    pub id: i64,
+    /// One of the `RUN_MODE_*` constants
+    pub mode: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub app_version: String,
+    pub hostname: String,
+    /// One of the `RUN_*` status constants
+    pub status: String,
+    pub collectors: Vec<CollectorOutcome>,
+    pub rows_written: usize,
+    /// Error that stopped the run, if any
+    pub error: Option<String>,
+}
 
-    for row in metrics_iter {
-        let (id, ts_str, cpu, mem_tot, mem_used, mem_avail, disk_tot, disk_used, proc_cnt, load1, load5, load15) = row?;
+impl CollectionRun {
+    /// Errors from the run and its collectors, prefixed with the collector name
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors: Vec<String> = self
+            .collectors
+            .iter()
+            .filter_map(|c| c.error.as_ref().map(|e| format!("{}: {}", c.name, e)))
+            .collect();
+        if let Some(ref error) = self.error {
+            errors.push(error.clone());
+        }
+        errors
+    }
+}
 
-        // Parse RFC3339 timestamp and convert to Unix timestamp
-        let timestamp = if let Ok(dt) = DateTime::parse_from_rfc3339(&ts_str) {
-            dt.timestamp()
-        } else {
-            warn!("Could not parse timestamp '{}', using current time", ts_str);
-            Utc::now().timestamp()
-        };
+/// How connections to a database file are opened
+#[derive(Clone)]
+pub struct ConnectionOptions {
+    /// How long to wait for locks held by other connections
+    pub busy_timeout: Duration,
+    /// SQLCipher key; requires the `encryption` feature
+    pub key: Option<String>,
+}
 
-        conn.execute(
-            "INSERT INTO system_metrics_v2 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            params![id, timestamp, cpu, mem_tot, mem_used, mem_avail, disk_tot, disk_used, proc_cnt, load1, load5, load15],
-        )?;
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        ConnectionOptions {
+            busy_timeout: Duration::from_millis(DEFAULT_BUSY_TIMEOUT_MS),
+            key: None,
+        }
     }
+}
 
-    // Migrate log_entries data
-    let mut stmt = conn.prepare("SELECT * FROM log_entries")?;
-    let logs_iter = stmt.query_map([], |row| {
-        Ok((
-            row.get::<_, i32>(0)?,     // id
-            row.get::<_, String>(1)?,  // timestamp
-            row.get::<_, String>(2)?,  // level
-            row.get::<_, String>(3)?,  // source
-            row.get::<_, String>(4)?,  // message
-        ))
-    })?;
+// Keep the key out of debug logs
+impl std::fmt::Debug for ConnectionOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionOptions")
+            .field("busy_timeout", &self.busy_timeout)
+            .field("key", &self.key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
 
-    for row in logs_iter {
-        let (id, ts_str, level, source, message) = row?;
+/// Files SQLite keeps next to a database (`-wal`, `-shm`, `-journal`)
+pub fn sidecar_paths(db_path: &Path) -> Vec<PathBuf> {
+    ["-wal", "-shm", "-journal"]
+        .iter()
+        .map(|suffix| {
+            let mut name = db_path.as_os_str().to_os_string();
+            name.push(suffix);
+            PathBuf::from(name)
+        })
+        .collect()
+}
 
-        // Parse RFC3339 timestamp and convert to Unix timestamp
-        let timestamp = if let Ok(dt) = DateTime::parse_from_rfc3339(&ts_str) {
-            dt.timestamp()
-        } else {
-            warn!("Could not parse log timestamp '{}', using current time", ts_str);
-            Utc::now().timestamp()
-        };
+/// Paths among the database and its sidecars that other users can access,
+/// with their permission bits
+#[cfg(unix)]
+pub fn loose_permissions(db_path: &Path) -> Vec<(PathBuf, u32)> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::iter::once(db_path.to_path_buf())
+        .chain(sidecar_paths(db_path))
+        .chain([writer_lock_path(db_path), collection_lock_path(db_path)])
+        .filter_map(|path| {
+            let mode = std::fs::metadata(&path).ok()?.permissions().mode() & 0o777;
+            (mode & 0o077 != 0).then_some((path, mode))
+        })
+        .collect()
+}
 
-        conn.execute(
-            "INSERT INTO log_entries_v2 VALUES (?, ?, ?, ?, ?)",
-            params![id, timestamp, level, source, message],
-        )?;
+/// Create the database file readable by its owner only, before SQLite opens it
+/// SQLite gives the WAL and SHM sidecars the same mode as the database file
+pub(crate) fn create_private_file(path: &Path) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600); // Read/write for owner only
     }
 
-    // Drop old tables
-    conn.execute("DROP TABLE system_metrics", [])?;
-    conn.execute("DROP TABLE log_entries", [])?;
-
-    // Rename new tables
-    conn.execute("ALTER TABLE system_metrics_v2 RENAME TO system_metrics", [])?;
-    conn.execute("ALTER TABLE log_entries_v2 RENAME TO log_entries", [])?;
+    match options.open(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+        Err(e) => Err(e).context(format!("Failed to create database {}", path.display())),
+    }
+}
 
-    info!("Migration to schema v2 complete");
+/// Unlock an encrypted database; must run before anything else touches it
+#[cfg(feature = "encryption")]
+fn apply_key(conn: &Connection, key: &str) -> Result<()> {
+    conn.pragma_update(None, "key", key)?;
+    // SQLCipher only checks the key when the first page is read
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .context("Failed to unlock database: wrong key, or the database is not encrypted")?;
     Ok(())
 }
 
-/// Migrate from schema v2 to v3 (add system_checks table)
-fn migrate_v2_to_v3(conn: &Connection) -> Result<()> {
-    info!("Migrating database from schema v2 to v3...");
-
-    // Create system_checks table (new in v3)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS system_checks (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            check_name TEXT NOT NULL,
-            check_type TEXT NOT NULL,
-            status TEXT NOT NULL,
-            value TEXT,
-            message TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    // Create index for better query performance
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_checks_timestamp
-         ON system_checks(timestamp)",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_checks_type
-         ON system_checks(check_type)",
-        [],
-    )?;
+#[cfg(not(feature = "encryption"))]
+fn apply_key(_conn: &Connection, _key: &str) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "A database key is configured but systers was built without the `encryption` feature"
+    ))
+}
 
-    info!("Migration to schema v3 complete");
+/// Apply busy timeout and key to a freshly opened connection
+fn configure_connection(conn: &Connection, options: &ConnectionOptions) -> Result<()> {
+    if let Some(ref key) = options.key {
+        apply_key(conn, key)?;
+    }
+    conn.busy_timeout(options.busy_timeout)?;
     Ok(())
 }
 
 /// Initialize the database with required schema
+/// Pending migrations are applied, after backing up an existing database file
 pub fn init_database<P: AsRef<Path>>(db_path: P) -> Result<Connection> {
+    init_database_with_options(db_path, &ConnectionOptions::default())
+}
+
+/// Initialize the database with a busy timeout and optional encryption key
+/// File databases are created with mode 0600 and switched to WAL mode so
+/// readers never block the writer; looser permissions on an existing
+/// database are reported but left alone
+pub fn init_database_with_options<P: AsRef<Path>>(
+    db_path: P,
+    options: &ConnectionOptions,
+) -> Result<Connection> {
     let path_ref = db_path.as_ref();
+    let in_memory = path_ref.to_str() == Some(":memory:");
+    if !in_memory {
+        create_private_file(path_ref)?;
+    }
+
     let conn = Connection::open(path_ref).context("Failed to open database")?;
+    configure_connection(&conn, options)?;
+
+    if !in_memory {
+        // WAL mode is stored in the file, so later connections inherit it
+        let mode: String =
+            conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            warn!("Could not enable WAL mode (journal mode is {})", mode);
+        }
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+
+        #[cfg(unix)]
+        for (path, mode) in loose_permissions(path_ref) {
+            warn!(
+                "{} is accessible by other users (mode {:o}) and may hold sensitive log lines; run: chmod 600 {}",
+                path.display(),
+                mode,
+                path.display()
+            );
+        }
+    }
 
-    // Create schema_version table if it doesn't exist
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS schema_version (
-            version INTEGER PRIMARY KEY,
-            app_version TEXT
-        )",
-        [],
-    )?;
-
-    // Get current schema version
-    let current_version = get_schema_version(&conn)?;
-
-    // Perform migrations if needed
-    if current_version == 0 {
-        // Fresh database - create v3 schema directly
-        info!("Creating fresh database with schema v3");
-    } else if current_version == 1 {
-        // Migrate from v1 to v2
-        migrate_v1_to_v2(&conn)?;
-        // Then migrate from v2 to v3
-        migrate_v2_to_v3(&conn)?;
-    } else if current_version == 2 {
-        // Migrate from v2 to v3
-        migrate_v2_to_v3(&conn)?;
-    } else if current_version > SCHEMA_VERSION {
-        warn!(
-            "Database schema version ({}) is newer than application version ({})",
-            current_version, SCHEMA_VERSION
-        );
-    }
-
-    // Create or recreate tables with v2 schema (INTEGER timestamps)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS system_metrics (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            cpu_usage REAL NOT NULL,
-            memory_total INTEGER NOT NULL,
-            memory_used INTEGER NOT NULL,
-            memory_available INTEGER NOT NULL,
-            disk_total INTEGER NOT NULL,
-            disk_used INTEGER NOT NULL,
-            process_count INTEGER NOT NULL,
-            load_avg_1min REAL NOT NULL,
-            load_avg_5min REAL NOT NULL,
-            load_avg_15min REAL NOT NULL
-        )",
-        [],
-    )?;
+    let backup_path = if in_memory { None } else { Some(path_ref) };
+    migrate_to(&conn, SCHEMA_VERSION, backup_path)?;
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS log_entries (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            level TEXT NOT NULL,
-            source TEXT NOT NULL,
-            message TEXT NOT NULL
-        )",
-        [],
-    )?;
+    Ok(conn)
+}
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS system_checks (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp INTEGER NOT NULL,
-            check_name TEXT NOT NULL,
-            check_type TEXT NOT NULL,
-            status TEXT NOT NULL,
-            value TEXT,
-            message TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    // Create indices for better query performance
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_metrics_timestamp
-         ON system_metrics(timestamp)",
-        [],
-    )?;
+/// Open an existing database for reading and writing without migrating it
+/// URI file names are enabled so other databases can be attached read-only
+pub fn open_database<P: AsRef<Path>>(db_path: P, options: &ConnectionOptions) -> Result<Connection> {
+    let path_ref = db_path.as_ref();
+    let conn = Connection::open_with_flags(
+        path_ref,
+        OpenFlags::SQLITE_OPEN_READ_WRITE
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .context(format!("Failed to open database {}", path_ref.display()))?;
+    configure_connection(&conn, options)?;
+    Ok(conn)
+}
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_logs_timestamp
-         ON log_entries(timestamp)",
-        [],
-    )?;
+/// Open an existing database for reading only
+/// Nothing is migrated; waits up to the busy timeout for locks held by writers
+pub fn open_read_only<P: AsRef<Path>>(db_path: P, options: &ConnectionOptions) -> Result<Connection> {
+    let path_ref = db_path.as_ref();
+    let conn = Connection::open_with_flags(
+        path_ref,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .context(format!("Failed to open database {}", path_ref.display()))?;
+    configure_connection(&conn, options)?;
+    Ok(conn)
+}
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_logs_level
-         ON log_entries(level)",
-        [],
-    )?;
+/// Open an existing database read-only for reports
+/// Fails unless the database is at the current schema version, since nothing
+/// is migrated on a read-only connection
+pub fn open_for_reading<P: AsRef<Path>>(
+    db_path: P,
+    options: &ConnectionOptions,
+) -> Result<Connection> {
+    let conn = open_read_only(&db_path, options)?;
+    let version = get_schema_version(&conn)?;
+    if version != SCHEMA_VERSION {
+        return Err(anyhow::anyhow!(
+            "{} is at schema v{} (this build reads v{}); run syswriter or `systers db migrate`",
+            db_path.as_ref().display(),
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    Ok(conn)
+}
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_checks_timestamp
-         ON system_checks(timestamp)",
-        [],
-    )?;
+/// Read-only URI for attaching a database file
+pub(crate) fn attach_uri(path: &Path) -> String {
+    let mut uri = String::from("file:");
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '%' => uri.push_str("%25"),
+            '?' => uri.push_str("%3f"),
+            '#' => uri.push_str("%23"),
+            _ => uri.push(c),
+        }
+    }
+    uri.push_str("?mode=ro");
+    uri
+}
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_checks_type
-         ON system_checks(check_type)",
-        [],
-    )?;
+/// Lock on a database's lock file, held by syswriter and `systers db`
+/// The lock is released when this value is dropped or the process exits
+#[derive(Debug)]
+pub struct WriterLock {
+    _file: File,
+    path: PathBuf,
+}
 
-    // Store schema version and app version
-    conn.execute(
-        "INSERT OR REPLACE INTO schema_version (version, app_version) VALUES (?1, ?2)",
-        params![SCHEMA_VERSION, crate::VERSION],
-    )?;
+impl WriterLock {
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Lock file used for a database (`<db>.lock`)
+pub fn writer_lock_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// Lock file that keeps syswriter collections apart (`<db>.collect.lock`)
+pub fn collection_lock_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(".collect.lock");
+    PathBuf::from(name)
+}
+
+/// Take the writer lock for a database exclusively without waiting
+/// Used by commands that must not run alongside any syswriter (rollback,
+/// vacuum, merge). Returns `None` when another process holds it
+pub fn try_writer_lock(db_path: &Path) -> Result<Option<WriterLock>> {
+    try_lock_file(writer_lock_path(db_path), false)
+}
+
+/// Take the writer lock for a database shared without waiting
+/// Held by every syswriter that writes (collections and `--watch`), so they
+/// can run together but never alongside an exclusive holder. Returns `None`
+/// when an exclusive holder has it
+pub fn try_shared_writer_lock(db_path: &Path) -> Result<Option<WriterLock>> {
+    try_lock_file(writer_lock_path(db_path), true)
+}
 
-    // Set restrictive permissions on database file (Unix only)
-    // Skip for in-memory databases
+/// Take the collection lock for a database without waiting
+/// Only one collection or cleanup run holds it at a time. Returns `None`
+/// when another run is still going
+pub fn try_collection_lock(db_path: &Path) -> Result<Option<WriterLock>> {
+    try_lock_file(collection_lock_path(db_path), false)
+}
+
+fn try_lock_file(path: PathBuf, shared: bool) -> Result<Option<WriterLock>> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
     #[cfg(unix)]
     {
-        if path_ref.to_str() != Some(":memory:") {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = std::fs::metadata(path_ref)?.permissions();
-            perms.set_mode(0o600); // Read/write for owner only
-            std::fs::set_permissions(path_ref, perms)?;
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .context(format!("Failed to open lock file {}", path.display()))?;
+
+    let locked = if shared {
+        file.try_lock_shared()
+    } else {
+        file.try_lock()
+    };
+    match locked {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(None),
+        Err(TryLockError::Error(e)) => {
+            return Err(e).context(format!("Failed to lock {}", path.display()))
         }
     }
 
-    Ok(conn)
+    // Record the holder to help whoever finds the lock taken (with shared
+    // holders this is the latest one)
+    file.set_len(0)?;
+    writeln!(file, "{}", std::process::id())?;
+
+    Ok(Some(WriterLock { _file: file, path }))
+}
+
+/// Process id recorded in the writer lock file, if any
+pub fn writer_lock_holder(db_path: &Path) -> Option<u32> {
+    lock_holder(&writer_lock_path(db_path))
+}
+
+/// Process id recorded in the collection lock file, if any
+pub fn collection_lock_holder(db_path: &Path) -> Option<u32> {
+    lock_holder(&collection_lock_path(db_path))
+}
+
+fn lock_holder(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+}
+
+/// Writes everything from one collection run in a single transaction
+/// Statements are prepared once per connection and reused for every row.
+/// Dropping a batch without calling `commit` rolls back all of its writes.
+pub struct Batch<'conn> {
+    tx: Transaction<'conn>,
+    run_id: Option<i64>,
+}
+
+impl<'conn> Batch<'conn> {
+    /// Start a batch, taking the write lock up front so a busy database
+    /// fails here rather than halfway through the run
+    pub fn begin(conn: &'conn Connection) -> Result<Self> {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .context("Failed to start write transaction")?;
+        Ok(Batch { tx, run_id: None })
+    }
+
+    /// Start a batch whose rows are linked to a collection run
+    pub fn begin_run(conn: &'conn Connection, run_id: i64) -> Result<Self> {
+        let mut batch = Self::begin(conn)?;
+        batch.run_id = Some(run_id);
+        Ok(batch)
+    }
+
+    /// Connection inside the transaction, for writes without a batch method
+    pub fn connection(&self) -> &Connection {
+        &self.tx
+    }
+
+    pub fn insert_metrics(&self, metrics: &SystemMetrics) -> Result<()> {
+        insert_metrics_row(&self.tx, metrics, self.run_id)
+    }
+
+    pub fn insert_disk_usage(&self, usage: &DiskUsage) -> Result<()> {
+        insert_disk_usage_row(&self.tx, usage, self.run_id)
+    }
+
+    pub fn insert_log_entry(&self, entry: &LogEntry) -> Result<()> {
+        insert_log_entry_row(&self.tx, entry, self.run_id)
+    }
+
+    pub fn insert_system_check(&self, check: &SystemCheckResult) -> Result<()> {
+        insert_system_check_row(&self.tx, check, self.run_id)
+    }
+
+    /// Returns false if the event was already stored
+    pub fn insert_auth_event(&self, event: &AuthEvent) -> Result<bool> {
+        insert_auth_event_row(&self.tx, event, self.run_id)
+    }
+
+    /// Returns false if the event was already stored
+    pub fn insert_kernel_event(&self, event: &KernelEvent) -> Result<bool> {
+        insert_kernel_event_row(&self.tx, event, self.run_id)
+    }
+
+    /// Make every write in the batch permanent
+    pub fn commit(self) -> Result<()> {
+        self.tx.commit().context("Failed to commit write transaction")
+    }
+
+    /// Discard every write in the batch
+    pub fn rollback(self) -> Result<()> {
+        self.tx.rollback().context("Failed to roll back write transaction")
+    }
 }
 
 /// Insert system metrics into database
 pub fn insert_metrics(conn: &Connection, metrics: &SystemMetrics) -> Result<()> {
-    conn.execute(
+    insert_metrics_row(conn, metrics, None)
+}
+
+fn insert_metrics_row(
+    conn: &Connection,
+    metrics: &SystemMetrics,
+    run_id: Option<i64>,
+) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
         "INSERT INTO system_metrics (
             timestamp, cpu_usage, memory_total, memory_used, memory_available,
             disk_total, disk_used, process_count,
-            load_avg_1min, load_avg_5min, load_avg_15min
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-        params![
-            metrics.timestamp.timestamp(), // Unix timestamp in seconds
-            metrics.cpu_usage,
-            metrics.memory_total,
-            metrics.memory_used,
-            metrics.memory_available,
-            metrics.disk_total,
-            metrics.disk_used,
-            metrics.process_count,
-            metrics.load_avg_1min,
-            metrics.load_avg_5min,
-            metrics.load_avg_15min,
-        ],
+            load_avg_1min, load_avg_5min, load_avg_15min, run_id
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
     )?;
+    stmt.execute(params![
+        metrics.timestamp.timestamp(), // Unix timestamp in seconds
+        metrics.cpu_usage,
+        metrics.memory_total,
+        metrics.memory_used,
+        metrics.memory_available,
+        metrics.disk_total,
+        metrics.disk_used,
+        metrics.process_count,
+        metrics.load_avg_1min,
+        metrics.load_avg_5min,
+        metrics.load_avg_15min,
+        run_id,
+    ])?;
     Ok(())
 }
 
+/// Insert the usage of one mount point into database
+pub fn insert_disk_usage(conn: &Connection, usage: &DiskUsage) -> Result<()> {
+    insert_disk_usage_row(conn, usage, None)
+}
+
+fn insert_disk_usage_row(conn: &Connection, usage: &DiskUsage, run_id: Option<i64>) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO disk_usage (timestamp, mount_point, filesystem, total_bytes, used_bytes, run_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    stmt.execute(params![
+        usage.timestamp.timestamp(), // Unix timestamp in seconds
+        usage.mount_point,
+        usage.filesystem,
+        usage.total_bytes,
+        usage.used_bytes,
+        run_id,
+    ])?;
+    Ok(())
+}
+
+/// Query one host's per-mount disk usage within a time range, oldest first
+pub fn query_disk_usage(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<DiskUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, mount_point, filesystem, total_bytes, used_bytes
+         FROM disk_usage
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+         ORDER BY timestamp, mount_point",
+    )?;
+
+    let params = params![start.timestamp(), end.timestamp(), host.param()];
+    let usage_iter = stmt.query_map(params, |row| {
+        Ok(DiskUsage {
+            timestamp: timestamp_from_db(0, row.get(0)?)?,
+            mount_point: row.get(1)?,
+            filesystem: row.get(2)?,
+            total_bytes: row.get(3)?,
+            used_bytes: row.get(4)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for usage in usage_iter {
+        results.push(usage?);
+    }
+
+    Ok(results)
+}
+
 /// Insert log entry into database
 pub fn insert_log_entry(conn: &Connection, entry: &LogEntry) -> Result<()> {
-    conn.execute(
-        "INSERT INTO log_entries (timestamp, level, source, message)
-         VALUES (?1, ?2, ?3, ?4)",
-        params![
-            entry.timestamp.timestamp(), // Unix timestamp in seconds
-            entry.level,
-            entry.source,
-            entry.message,
-        ],
+    insert_log_entry_row(conn, entry, None)
+}
+
+fn insert_log_entry_row(
+    conn: &Connection,
+    entry: &LogEntry,
+    run_id: Option<i64>,
+) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO log_entries (timestamp, level, source, message, attributes, run_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
     )?;
+    stmt.execute(params![
+        entry.timestamp.timestamp(), // Unix timestamp in seconds
+        entry.level,
+        entry.source,
+        entry.message,
+        entry.attributes.as_ref().map(|a| a.to_string()),
+        run_id,
+    ])?;
     Ok(())
 }
 
 /// Insert system check result into database
 pub fn insert_system_check(conn: &Connection, check: &SystemCheckResult) -> Result<()> {
-    conn.execute(
-        "INSERT INTO system_checks (timestamp, check_name, check_type, status, value, message)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![
-            check.timestamp.timestamp(), // Unix timestamp in seconds
-            check.check_name,
-            check.check_type,
-            check.status,
-            check.value,
-            check.message,
-        ],
+    insert_system_check_row(conn, check, None)
+}
+
+fn insert_system_check_row(
+    conn: &Connection,
+    check: &SystemCheckResult,
+    run_id: Option<i64>,
+) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO system_checks
+            (timestamp, check_name, check_type, status, value, unit, details, message, run_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
     )?;
+    stmt.execute(params![
+        check.timestamp.timestamp(), // Unix timestamp in seconds
+        check.check_name,
+        check.check_type,
+        check.status,
+        check.value,
+        check.unit,
+        check.details.as_ref().map(|d| d.to_string()),
+        check.message,
+        run_id,
+    ])?;
     Ok(())
 }
 
-/// Query system metrics within a time range
+/// Query one host's system metrics within a time range
 pub fn query_metrics(
     conn: &Connection,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
+    host: &HostFilter,
 ) -> Result<Vec<SystemMetrics>> {
     let mut stmt = conn.prepare(
         "SELECT timestamp, cpu_usage, memory_total, memory_used, memory_available,
                 disk_total, disk_used, process_count,
                 load_avg_1min, load_avg_5min, load_avg_15min
          FROM system_metrics
-         WHERE timestamp >= ?1 AND timestamp <= ?2
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
          ORDER BY timestamp DESC",
     )?;
 
-    let metrics_iter = stmt.query_map(params![start.timestamp(), end.timestamp()], |row| {
-        let timestamp_i64: i64 = row.get(0)?;
-        let timestamp = Utc.timestamp_opt(timestamp_i64, 0)
-            .single()
-            .ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    0,
-                    rusqlite::types::Type::Integer,
-                    Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid timestamp")),
-                )
-            })?;
-
-        Ok(SystemMetrics {
-            timestamp,
-            cpu_usage: row.get(1)?,
-            memory_total: row.get(2)?,
-            memory_used: row.get(3)?,
-            memory_available: row.get(4)?,
-            disk_total: row.get(5)?,
-            disk_used: row.get(6)?,
-            process_count: row.get(7)?,
-            load_avg_1min: row.get(8)?,
-            load_avg_5min: row.get(9)?,
-            load_avg_15min: row.get(10)?,
-        })
-    })?;
+    let params = params![start.timestamp(), end.timestamp(), host.param()];
+    let metrics_iter = stmt.query_map(params, metrics_from_row)?;
 
     let mut results = Vec::new();
     for metric in metrics_iter {
@@ -454,33 +927,62 @@ pub fn query_metrics(
     Ok(results)
 }
 
-/// Query log entries within a time range and optional level filter
+/// Visit one host's system metrics within a time range, oldest first, one
+/// row at a time, together with the host each row belongs to
+/// Returns the number of rows visited
+pub fn for_each_metric<F>(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+    mut visit: F,
+) -> Result<usize>
+where
+    F: FnMut(&str, SystemMetrics) -> Result<()>,
+{
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, cpu_usage, memory_total, memory_used, memory_available,
+                disk_total, disk_used, process_count,
+                load_avg_1min, load_avg_5min, load_avg_15min, host
+         FROM system_metrics
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+         ORDER BY timestamp ASC",
+    )?;
+
+    let mut rows = stmt.query(params![start.timestamp(), end.timestamp(), host.param()])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        visit(row.get_ref(11)?.as_str()?, metrics_from_row(row)?)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn metrics_from_row(row: &rusqlite::Row) -> rusqlite::Result<SystemMetrics> {
+    Ok(SystemMetrics {
+        timestamp: timestamp_from_db(0, row.get(0)?)?,
+        cpu_usage: row.get(1)?,
+        memory_total: row.get(2)?,
+        memory_used: row.get(3)?,
+        memory_available: row.get(4)?,
+        disk_total: row.get(5)?,
+        disk_used: row.get(6)?,
+        process_count: row.get(7)?,
+        load_avg_1min: row.get(8)?,
+        load_avg_5min: row.get(9)?,
+        load_avg_15min: row.get(10)?,
+    })
+}
+
+/// Query one host's log entries within a time range and optional level filter
 pub fn query_logs(
     conn: &Connection,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
     level_filter: Option<&str>,
+    host: &HostFilter,
 ) -> Result<Vec<LogEntry>> {
-    let parse_row = |row: &rusqlite::Row| -> rusqlite::Result<LogEntry> {
-        let timestamp_i64: i64 = row.get(0)?;
-        let timestamp = Utc.timestamp_opt(timestamp_i64, 0)
-            .single()
-            .ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    0,
-                    rusqlite::types::Type::Integer,
-                    Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid timestamp")),
-                )
-            })?;
-
-        Ok(LogEntry {
-            timestamp,
-            level: row.get(1)?,
-            source: row.get(2)?,
-            message: row.get(3)?,
-        })
-    };
-
     // Build query and parameters based on filter
     let start_ts = start.timestamp();
     let end_ts = end.timestamp();
@@ -488,20 +990,21 @@ pub fn query_logs(
     let mut stmt;
     let logs_iter = if let Some(level) = level_filter {
         stmt = conn.prepare(
-            "SELECT timestamp, level, source, message
+            "SELECT timestamp, level, source, message, attributes
              FROM log_entries
-             WHERE timestamp >= ?1 AND timestamp <= ?2 AND level = ?3
+             WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+               AND level = ?4
              ORDER BY timestamp DESC",
         )?;
-        stmt.query_map(params![start_ts, end_ts, level], parse_row)?
+        stmt.query_map(params![start_ts, end_ts, host.param(), level], log_entry_from_row)?
     } else {
         stmt = conn.prepare(
-            "SELECT timestamp, level, source, message
+            "SELECT timestamp, level, source, message, attributes
              FROM log_entries
-             WHERE timestamp >= ?1 AND timestamp <= ?2
+             WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
              ORDER BY timestamp DESC",
         )?;
-        stmt.query_map(params![start_ts, end_ts], parse_row)?
+        stmt.query_map(params![start_ts, end_ts, host.param()], log_entry_from_row)?
     };
 
     let mut results = Vec::new();
@@ -512,43 +1015,248 @@ pub fn query_logs(
     Ok(results)
 }
 
-/// Query system check results within a time range
+/// Visit one host's log entries within a time range, oldest first, one row
+/// at a time, together with the host each row belongs to
+/// Returns the number of rows visited
+pub fn for_each_log_entry<F>(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+    mut visit: F,
+) -> Result<usize>
+where
+    F: FnMut(&str, LogEntry) -> Result<()>,
+{
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, level, source, message, attributes, host
+         FROM log_entries
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+         ORDER BY timestamp ASC, id ASC",
+    )?;
+
+    let mut rows = stmt.query(params![start.timestamp(), end.timestamp(), host.param()])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        visit(row.get_ref(5)?.as_str()?, log_entry_from_row(row)?)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn log_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<LogEntry> {
+    // Attributes are stored as JSON text; unreadable values are dropped
+    let attributes: Option<String> = row.get(4)?;
+
+    Ok(LogEntry {
+        timestamp: timestamp_from_db(0, row.get(0)?)?,
+        level: row.get(1)?,
+        source: row.get(2)?,
+        message: row.get(3)?,
+        attributes: attributes.and_then(|a| serde_json::from_str(&a).ok()),
+    })
+}
+
+/// Insert authentication event into database
+/// Returns false if the same event was already stored by an earlier scan
+pub fn insert_auth_event(conn: &Connection, event: &AuthEvent) -> Result<bool> {
+    insert_auth_event_row(conn, event, None)
+}
+
+fn insert_auth_event_row(
+    conn: &Connection,
+    event: &AuthEvent,
+    run_id: Option<i64>,
+) -> Result<bool> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO auth_events
+            (timestamp, event_type, username, source_ip, method, command, message, run_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    let inserted = stmt.execute(params![
+        event.timestamp.timestamp(), // Unix timestamp in seconds
+        event.event_type,
+        event.username,
+        event.source_ip,
+        event.method,
+        event.command,
+        event.message,
+        run_id,
+    ])?;
+    Ok(inserted > 0)
+}
+
+/// Query one host's authentication events within a time range, oldest first
+pub fn query_auth_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<AuthEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, event_type, username, source_ip, method, command, message
+         FROM auth_events
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+         ORDER BY timestamp ASC",
+    )?;
+
+    let params = params![start.timestamp(), end.timestamp(), host.param()];
+    let events_iter = stmt.query_map(params, |row| {
+        let timestamp = timestamp_from_db(0, row.get(0)?)?;
+
+        Ok(AuthEvent {
+            timestamp,
+            event_type: row.get(1)?,
+            username: row.get(2)?,
+            source_ip: row.get(3)?,
+            method: row.get(4)?,
+            command: row.get(5)?,
+            message: row.get(6)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for event in events_iter {
+        results.push(event?);
+    }
+
+    Ok(results)
+}
+
+/// Check whether any auth event of a type matching the given user (and source IP,
+/// if provided) was recorded on a host before a point in time
+pub fn auth_event_seen_before(
+    conn: &Connection,
+    event_type: &str,
+    username: &str,
+    source_ip: Option<&str>,
+    before: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM auth_events
+         WHERE event_type = ?1 AND username = ?2
+           AND (?3 IS NULL OR source_ip = ?3)
+           AND timestamp < ?4 AND (?5 IS NULL OR host = ?5)",
+        params![event_type, username, source_ip, before.timestamp(), host.param()],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Timestamp of a host's oldest stored auth event
+/// Events older than `retention.days` are pruned, so "seen before" only
+/// reaches back this far
+pub fn earliest_auth_event(conn: &Connection, host: &HostFilter) -> Result<Option<DateTime<Utc>>> {
+    let value: Option<i64> = conn.query_row(
+        "SELECT MIN(timestamp) FROM auth_events WHERE ?1 IS NULL OR host = ?1",
+        params![host.param()],
+        |row| row.get(0),
+    )?;
+    Ok(value.and_then(|ts| Utc.timestamp_opt(ts, 0).single()))
+}
+
+/// Insert kernel event into database
+/// Returns false if the same event was already stored
+pub fn insert_kernel_event(conn: &Connection, event: &KernelEvent) -> Result<bool> {
+    insert_kernel_event_row(conn, event, None)
+}
+
+fn insert_kernel_event_row(
+    conn: &Connection,
+    event: &KernelEvent,
+    run_id: Option<i64>,
+) -> Result<bool> {
+    // Boots carrying the kernel's boot id are matched on it rather than on the
+    // timestamp, which shifts when the clock is stepped
+    let boot_id = event.details.as_ref().and_then(|d| d.get("boot_id")?.as_str());
+    if let Some(boot_id) = boot_id {
+        let seen: bool = conn.prepare_cached(
+            "SELECT EXISTS(SELECT 1 FROM kernel_events
+             WHERE host = '' AND event_type = ?1 AND json_extract(details, '$.boot_id') = ?2)",
+        )?
+        .query_row(params![event.event_type, boot_id], |row| row.get(0))?;
+        if seen {
+            return Ok(false);
+        }
+    }
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO kernel_events
+            (timestamp, event_type, process, pid, device, details, message, run_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    let inserted = stmt.execute(params![
+        event.timestamp.timestamp(), // Unix timestamp in seconds
+        event.event_type,
+        event.process,
+        event.pid,
+        event.device,
+        event.details.as_ref().map(|d| d.to_string()),
+        event.message,
+        run_id,
+    ])?;
+    Ok(inserted > 0)
+}
+
+/// Query one host's kernel events within a time range, newest first
+pub fn query_kernel_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<KernelEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, event_type, process, pid, device, details, message
+         FROM kernel_events
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+         ORDER BY timestamp DESC",
+    )?;
+
+    let params = params![start.timestamp(), end.timestamp(), host.param()];
+    let events_iter = stmt.query_map(params, |row| {
+        let timestamp = timestamp_from_db(0, row.get(0)?)?;
+        let details: Option<String> = row.get(5)?;
+
+        Ok(KernelEvent {
+            timestamp,
+            event_type: row.get(1)?,
+            process: row.get(2)?,
+            pid: row.get(3)?,
+            device: row.get(4)?,
+            details: details.and_then(|d| serde_json::from_str(&d).ok()),
+            message: row.get(6)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for event in events_iter {
+        results.push(event?);
+    }
+
+    Ok(results)
+}
+
+/// Query one host's system check results within a time range
 pub fn query_system_checks(
     conn: &Connection,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
+    host: &HostFilter,
 ) -> Result<Vec<SystemCheckResult>> {
     let mut stmt = conn.prepare(
-        "SELECT timestamp, check_name, check_type, status, value, message
+        "SELECT timestamp, check_name, check_type, status, value, unit, details, message
          FROM system_checks
-         WHERE timestamp >= ?1 AND timestamp <= ?2
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
          ORDER BY timestamp DESC",
     )?;
 
     let start_ts = start.timestamp();
     let end_ts = end.timestamp();
 
-    let checks_iter = stmt.query_map(params![start_ts, end_ts], |row| {
-        let timestamp_i64: i64 = row.get(0)?;
-        let timestamp = Utc.timestamp_opt(timestamp_i64, 0)
-            .single()
-            .ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    0,
-                    rusqlite::types::Type::Integer,
-                    Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid timestamp")),
-                )
-            })?;
-
-        Ok(SystemCheckResult {
-            timestamp,
-            check_name: row.get(1)?,
-            check_type: row.get(2)?,
-            status: row.get(3)?,
-            value: row.get(4)?,
-            message: row.get(5)?,
-        })
-    })?;
+    let checks_iter =
+        stmt.query_map(params![start_ts, end_ts, host.param()], system_check_from_row)?;
 
     let mut results = Vec::new();
     for check in checks_iter {
@@ -558,7 +1266,463 @@ pub fn query_system_checks(
     Ok(results)
 }
 
-/// Delete old data beyond the retention period
+/// Visit one host's system check results within a time range, oldest first,
+/// one row at a time, together with the host each row belongs to
+/// Returns the number of rows visited
+pub fn for_each_system_check<F>(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+    mut visit: F,
+) -> Result<usize>
+where
+    F: FnMut(&str, SystemCheckResult) -> Result<()>,
+{
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, check_name, check_type, status, value, unit, details, message, host
+         FROM system_checks
+         WHERE timestamp >= ?1 AND timestamp <= ?2 AND (?3 IS NULL OR host = ?3)
+         ORDER BY timestamp ASC, id ASC",
+    )?;
+
+    let mut rows = stmt.query(params![start.timestamp(), end.timestamp(), host.param()])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        visit(row.get_ref(8)?.as_str()?, system_check_from_row(row)?)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn system_check_from_row(row: &rusqlite::Row) -> rusqlite::Result<SystemCheckResult> {
+    // Details are stored as JSON text; unreadable values are dropped
+    let details: Option<String> = row.get(6)?;
+
+    Ok(SystemCheckResult {
+        timestamp: timestamp_from_db(0, row.get(0)?)?,
+        check_name: row.get(1)?,
+        check_type: row.get(2)?,
+        status: row.get(3)?,
+        value: row.get(4)?,
+        unit: row.get(5)?,
+        details: details.and_then(|d| serde_json::from_str(&d).ok()),
+        message: row.get(7)?,
+    })
+}
+
+fn timestamp_from_db(column: usize, value: i64) -> rusqlite::Result<DateTime<Utc>> {
+    Utc.timestamp_opt(value, 0).single().ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Integer,
+            Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid timestamp")),
+        )
+    })
+}
+
+/// Insert or replace the summary of one metric over a bucket
+pub fn upsert_metric_rollup(conn: &Connection, rollup: &MetricRollup) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO metric_rollups
+            (resolution, bucket_start, metric, samples, avg, min, max, p95)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    stmt.execute(params![
+        rollup.resolution.as_str(),
+        rollup.bucket_start.timestamp(),
+        rollup.metric,
+        rollup.samples,
+        rollup.avg,
+        rollup.min,
+        rollup.max,
+        rollup.p95,
+    ])?;
+    Ok(())
+}
+
+/// Count raw log entries per bucket and level into log_rollups
+/// Covers entries with start <= timestamp < end; returns the rows written
+pub fn rollup_log_counts(
+    conn: &Connection,
+    resolution: Resolution,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<usize> {
+    let bucket = resolution.bucket_seconds();
+    if bucket == 0 {
+        return Err(anyhow::anyhow!("Raw data has no rollups"));
+    }
+
+    let written = conn.execute(
+        "INSERT OR REPLACE INTO log_rollups (resolution, bucket_start, level, count)
+         SELECT ?1, (timestamp / ?2) * ?2, level, COUNT(*)
+         FROM log_entries
+         WHERE host = '' AND timestamp >= ?3 AND timestamp < ?4
+         GROUP BY 2, 3",
+        params![resolution.as_str(), bucket, start.timestamp(), end.timestamp()],
+    )?;
+    Ok(written)
+}
+
+/// Query metric rollups with start <= bucket_start <= end
+pub fn query_metric_rollups(
+    conn: &Connection,
+    resolution: Resolution,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<MetricRollup>> {
+    let mut stmt = conn.prepare(
+        "SELECT bucket_start, metric, samples, avg, min, max, p95
+         FROM metric_rollups
+         WHERE resolution = ?1 AND bucket_start >= ?2 AND bucket_start <= ?3
+         ORDER BY bucket_start DESC, metric",
+    )?;
+
+    let rows = stmt.query_map(
+        params![resolution.as_str(), start.timestamp(), end.timestamp()],
+        |row| {
+            Ok(MetricRollup {
+                resolution,
+                bucket_start: timestamp_from_db(0, row.get(0)?)?,
+                metric: row.get(1)?,
+                samples: row.get(2)?,
+                avg: row.get(3)?,
+                min: row.get(4)?,
+                max: row.get(5)?,
+                p95: row.get(6)?,
+            })
+        },
+    )?;
+
+    let mut results = Vec::new();
+    for rollup in rows {
+        results.push(rollup?);
+    }
+    Ok(results)
+}
+
+/// Query log count rollups with start <= bucket_start <= end
+pub fn query_log_rollups(
+    conn: &Connection,
+    resolution: Resolution,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<LogRollup>> {
+    let mut stmt = conn.prepare(
+        "SELECT bucket_start, level, count
+         FROM log_rollups
+         WHERE resolution = ?1 AND bucket_start >= ?2 AND bucket_start <= ?3
+         ORDER BY bucket_start DESC, level",
+    )?;
+
+    let rows = stmt.query_map(
+        params![resolution.as_str(), start.timestamp(), end.timestamp()],
+        |row| {
+            Ok(LogRollup {
+                resolution,
+                bucket_start: timestamp_from_db(0, row.get(0)?)?,
+                level: row.get(1)?,
+                count: row.get(2)?,
+            })
+        },
+    )?;
+
+    let mut results = Vec::new();
+    for rollup in rows {
+        results.push(rollup?);
+    }
+    Ok(results)
+}
+
+/// End of the last bucket built for a resolution (None if never built)
+pub fn rollup_built_until(conn: &Connection, resolution: Resolution) -> Result<Option<DateTime<Utc>>> {
+    let value: Option<i64> = conn
+        .query_row(
+            "SELECT built_until FROM rollup_state WHERE resolution = ?1",
+            params![resolution.as_str()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value.and_then(|ts| Utc.timestamp_opt(ts, 0).single()))
+}
+
+/// Record that all buckets of a resolution before `until` have been built
+pub fn set_rollup_built_until(
+    conn: &Connection,
+    resolution: Resolution,
+    until: DateTime<Utc>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO rollup_state (resolution, built_until) VALUES (?1, ?2)",
+        params![resolution.as_str(), until.timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Replace all stored metric baselines in one transaction
+pub fn replace_metric_baselines(conn: &Connection, baselines: &[MetricBaseline]) -> Result<()> {
+    let batch = Batch::begin(conn)?;
+    let tx = batch.connection();
+    tx.execute("DELETE FROM metric_baselines", [])?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO metric_baselines (metric, hour_of_week, samples, median, mad, built_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for baseline in baselines {
+            stmt.execute(params![
+                baseline.metric,
+                baseline.hour_of_week,
+                baseline.samples,
+                baseline.median,
+                baseline.mad,
+                baseline.built_at.timestamp(),
+            ])?;
+        }
+    }
+    batch.commit()
+}
+
+/// All stored metric baselines, by metric and hour of the week
+pub fn query_metric_baselines(conn: &Connection) -> Result<Vec<MetricBaseline>> {
+    let mut stmt = conn.prepare(
+        "SELECT metric, hour_of_week, samples, median, mad, built_at
+         FROM metric_baselines
+         ORDER BY metric, hour_of_week",
+    )?;
+
+    let baselines_iter = stmt.query_map([], |row| {
+        Ok(MetricBaseline {
+            metric: row.get(0)?,
+            hour_of_week: row.get(1)?,
+            samples: row.get(2)?,
+            median: row.get(3)?,
+            mad: row.get(4)?,
+            built_at: timestamp_from_db(5, row.get(5)?)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for baseline in baselines_iter {
+        results.push(baseline?);
+    }
+
+    Ok(results)
+}
+
+/// Timestamp of the oldest data kept at a resolution
+/// For raw data this considers both metrics and log entries
+pub fn earliest_data(conn: &Connection, resolution: Resolution) -> Result<Option<DateTime<Utc>>> {
+    let value: Option<i64> = match resolution {
+        Resolution::Raw => conn.query_row(
+            "SELECT MIN(ts) FROM (
+                SELECT MIN(timestamp) AS ts FROM system_metrics WHERE host = ''
+                UNION ALL
+                SELECT MIN(timestamp) FROM log_entries WHERE host = ''
+            )",
+            [],
+            |row| row.get(0),
+        )?,
+        _ => conn.query_row(
+            "SELECT MIN(ts) FROM (
+                SELECT MIN(bucket_start) AS ts FROM metric_rollups WHERE resolution = ?1
+                UNION ALL
+                SELECT MIN(bucket_start) FROM log_rollups WHERE resolution = ?1
+            )",
+            params![resolution.as_str()],
+            |row| row.get(0),
+        )?,
+    };
+    Ok(value.and_then(|ts| Utc.timestamp_opt(ts, 0).single()))
+}
+
+/// Check whether any rollup of a resolution starts within [start, end]
+pub fn rollups_exist_between(
+    conn: &Connection,
+    resolution: Resolution,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (
+            SELECT 1 FROM metric_rollups
+            WHERE resolution = ?1 AND bucket_start >= ?2 AND bucket_start <= ?3
+         ) OR EXISTS (
+            SELECT 1 FROM log_rollups
+            WHERE resolution = ?1 AND bucket_start >= ?2 AND bucket_start <= ?3
+         )",
+        params![resolution.as_str(), start.timestamp(), end.timestamp()],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Delete rollups of a resolution older than the retention period
+/// Returns the number of rows deleted
+pub fn cleanup_rollups(
+    conn: &Connection,
+    resolution: Resolution,
+    retention_days: i64,
+) -> Result<usize> {
+    let cutoff_ts = (Utc::now() - chrono::Duration::days(retention_days)).timestamp();
+
+    let metrics_deleted = conn.execute(
+        "DELETE FROM metric_rollups WHERE resolution = ?1 AND bucket_start < ?2",
+        params![resolution.as_str(), cutoff_ts],
+    )?;
+    let logs_deleted = conn.execute(
+        "DELETE FROM log_rollups WHERE resolution = ?1 AND bucket_start < ?2",
+        params![resolution.as_str(), cutoff_ts],
+    )?;
+
+    Ok(metrics_deleted + logs_deleted)
+}
+
+/// Record the start of a collection run; returns its id
+/// The row is committed right away, so a run that dies midway stays visible
+/// with the `running` status
+pub fn start_collection_run(
+    conn: &Connection,
+    mode: &str,
+    started_at: DateTime<Utc>,
+    hostname: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO collection_runs (mode, started_at, app_version, hostname, status)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![mode, started_at.timestamp(), crate::VERSION, hostname, RUN_RUNNING],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Record how a collection run ended
+/// The status is `failed` when `error` is set, `partial` when a collector
+/// failed and `ok` otherwise
+pub fn finish_collection_run(
+    conn: &Connection,
+    run_id: i64,
+    finished_at: DateTime<Utc>,
+    collectors: &[CollectorOutcome],
+    error: Option<&str>,
+) -> Result<()> {
+    let status = if error.is_some() {
+        RUN_FAILED
+    } else if collectors.iter().any(|c| c.error.is_some()) {
+        RUN_PARTIAL
+    } else {
+        RUN_OK
+    };
+    let rows_written: usize = collectors.iter().map(|c| c.rows).sum();
+
+    conn.execute(
+        "UPDATE collection_runs
+         SET finished_at = ?1, status = ?2, collectors = ?3, rows_written = ?4, error = ?5
+         WHERE id = ?6",
+        params![
+            finished_at.timestamp(),
+            status,
+            serde_json::to_string(collectors)?,
+            rows_written,
+            error,
+            run_id,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Query collection runs of this host started within a time range, oldest first
+/// Runs merged from other hosts' databases are left out
+pub fn query_collection_runs(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<CollectionRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, mode, started_at, finished_at, app_version, hostname, status,
+                collectors, rows_written, error
+         FROM collection_runs
+         WHERE started_at >= ?1 AND started_at <= ?2 AND host = ''
+         ORDER BY started_at ASC, id ASC",
+    )?;
+
+    let runs = stmt
+        .query_map(params![start.timestamp(), end.timestamp()], |row| {
+            let finished_at: Option<i64> = row.get(3)?;
+            let collectors: Option<String> = row.get(7)?;
+            Ok(CollectionRun {
+                id: row.get(0)?,
+                mode: row.get(1)?,
+                started_at: timestamp_from_db(2, row.get(2)?)?,
+                finished_at: finished_at.map(|ts| timestamp_from_db(3, ts)).transpose()?,
+                app_version: row.get(4)?,
+                hostname: row.get(5)?,
+                status: row.get(6)?,
+                collectors: collectors
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                rows_written: row.get(8)?,
+                error: row.get(9)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(runs)
+}
+
+/// Start time of this host's latest run of a mode that started before `before`
+pub fn last_run_before(
+    conn: &Connection,
+    mode: &str,
+    before: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let started_at: Option<i64> = conn.query_row(
+        "SELECT MAX(started_at) FROM collection_runs
+         WHERE mode = ?1 AND started_at < ?2 AND host = ''",
+        params![mode, before.timestamp()],
+        |row| row.get(0),
+    )?;
+    Ok(started_at.and_then(|ts| Utc.timestamp_opt(ts, 0).single()))
+}
+
+/// Delete collection runs older than the retention period
+/// Returns the number of runs deleted
+pub fn cleanup_collection_runs(conn: &Connection, retention_days: i64) -> Result<usize> {
+    let cutoff_ts = (Utc::now() - chrono::Duration::days(retention_days)).timestamp();
+    let deleted = conn.execute(
+        "DELETE FROM collection_runs WHERE started_at < ?1",
+        params![cutoff_ts],
+    )?;
+    Ok(deleted)
+}
+
+/// Tables that carry a host column; rows merged from other machines have it set
+const HOST_TABLES: [&str; 6] = [
+    "system_metrics",
+    "log_entries",
+    "system_checks",
+    "auth_events",
+    "kernel_events",
+    "disk_usage",
+];
+
+/// Delete rows merged from other hosts that are older than the retention period
+/// Returns the number of rows deleted
+pub fn cleanup_merged_data(conn: &Connection, retention_days: i64) -> Result<usize> {
+    let cutoff_ts = (Utc::now() - chrono::Duration::days(retention_days)).timestamp();
+    let mut deleted = 0;
+    for table in HOST_TABLES {
+        deleted += conn.execute(
+            &format!("DELETE FROM {} WHERE timestamp < ?1 AND host != ''", table),
+            params![cutoff_ts],
+        )?;
+    }
+    Ok(deleted)
+}
+
+/// Delete this host's data beyond the retention period
+/// Merged rows are left alone; see cleanup_merged_data
 /// Returns tuple of (metrics_deleted, logs_deleted, checks_deleted)
 pub fn cleanup_old_data(conn: &Connection, retention_days: i64) -> Result<(usize, usize, usize)> {
     let cutoff_date = chrono::Utc::now() - chrono::Duration::days(retention_days);
@@ -566,21 +1730,48 @@ pub fn cleanup_old_data(conn: &Connection, retention_days: i64) -> Result<(usize
 
     // Delete old metrics
     let metrics_deleted = conn.execute(
-        "DELETE FROM system_metrics WHERE timestamp < ?1",
+        "DELETE FROM system_metrics WHERE timestamp < ?1 AND host = ''",
         params![cutoff_ts],
     )?;
 
     // Delete old log entries
     let logs_deleted = conn.execute(
-        "DELETE FROM log_entries WHERE timestamp < ?1",
+        "DELETE FROM log_entries WHERE timestamp < ?1 AND host = ''",
         params![cutoff_ts],
     )?;
 
     // Delete old system checks
     let checks_deleted = conn.execute(
-        "DELETE FROM system_checks WHERE timestamp < ?1",
+        "DELETE FROM system_checks WHERE timestamp < ?1 AND host = ''",
+        params![cutoff_ts],
+    )?;
+
+    // Delete old auth events
+    let auth_deleted = conn.execute(
+        "DELETE FROM auth_events WHERE timestamp < ?1 AND host = ''",
         params![cutoff_ts],
     )?;
+    if auth_deleted > 0 {
+        info!("Deleted {} old auth events", auth_deleted);
+    }
+
+    // Delete old per-mount disk usage
+    let disk_deleted = conn.execute(
+        "DELETE FROM disk_usage WHERE timestamp < ?1 AND host = ''",
+        params![cutoff_ts],
+    )?;
+    if disk_deleted > 0 {
+        info!("Deleted {} old disk usage samples", disk_deleted);
+    }
+
+    // Delete old kernel events
+    let kernel_deleted = conn.execute(
+        "DELETE FROM kernel_events WHERE timestamp < ?1 AND host = ''",
+        params![cutoff_ts],
+    )?;
+    if kernel_deleted > 0 {
+        info!("Deleted {} old kernel events", kernel_deleted);
+    }
 
     // Vacuum to reclaim space
     conn.execute("VACUUM", [])?;
@@ -0,0 +1,482 @@
+use crate::db::{
+    for_each_log_entry, for_each_metric, for_each_system_check, HostFilter, LogEntry,
+    SystemCheckResult, SystemMetrics,
+};
+use crate::timezone::DisplayTimezone;
+use anyhow::{Context, Result};
+use arrow_array::{
+    ArrayRef, Float32Array, Float64Array, RecordBatch, StringArray, TimestampSecondArray,
+    UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Days, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Rows buffered before they are written as one Arrow record batch
+const PARQUET_BATCH_ROWS: usize = 8192;
+/// Rows per Parquet row group; bounds how much the writer keeps in memory
+const PARQUET_ROW_GROUP_ROWS: usize = 65536;
+
+/// Raw table that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTable {
+    Metrics,
+    Logs,
+    Checks,
+}
+
+impl std::str::FromStr for ExportTable {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "metrics" => Ok(ExportTable::Metrics),
+            "logs" => Ok(ExportTable::Logs),
+            "checks" => Ok(ExportTable::Checks),
+            _ => Err(anyhow::anyhow!("Unsupported table: {}", s)),
+        }
+    }
+}
+
+/// File format for raw row exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Csv,
+    Jsonl,
+    /// Columnar format that DuckDB, pandas and Spark read directly
+    Parquet,
+}
+
+impl std::str::FromStr for DataFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(DataFormat::Csv),
+            "jsonl" | "ndjson" => Ok(DataFormat::Jsonl),
+            "parquet" => Ok(DataFormat::Parquet),
+            _ => Err(anyhow::anyhow!("Unsupported format: {}", s)),
+        }
+    }
+}
+
+/// Parse a time bound for `--since` / `--until`
+///
+/// Accepts RFC 3339 (`2025-11-05T20:00:00Z`), a date and time in `timezone`
+/// (`2025-11-05 20:00`, `2025-11-05`), `now`, `today` or `yesterday`
+/// (midnight) and durations before `now` (`30m`, `24h`, `1h30m`, `2d ago`)
+pub fn parse_time_bound(
+    value: &str,
+    now: DateTime<Utc>,
+    timezone: DisplayTimezone,
+) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let days_back = match value.to_lowercase().as_str() {
+        "now" => return Ok(now),
+        "today" => Some(0),
+        "yesterday" => Some(1),
+        _ => None,
+    };
+    if let Some(days_back) = days_back {
+        let date = timezone.convert(now).date_naive() - Days::new(days_back);
+        return timezone
+            .resolve(&date.and_time(NaiveTime::MIN))
+            .ok_or_else(|| {
+                anyhow::anyhow!("Midnight of '{}' does not exist in the display time zone", value)
+            });
+    }
+
+    let duration = value.strip_suffix("ago").map_or(value, str::trim_end);
+    if let Some(age) = parse_age(duration) {
+        return now
+            .checked_sub_signed(age)
+            .ok_or_else(|| anyhow::anyhow!("Duration '{}' is too long", value));
+    }
+
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid time '{}'. Use RFC 3339, 'YYYY-MM-DD [HH:MM[:SS]]', today, yesterday or a duration like 24h or 1h30m",
+                value
+            )
+        })?;
+
+    timezone
+        .resolve(&naive)
+        .ok_or_else(|| anyhow::anyhow!("Time '{}' does not exist in the display time zone", value))
+}
+
+/// Parse a duration such as `30m`, `24h`, `7d` or `1h30m`
+fn parse_age(value: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut amount = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            amount.push(c);
+            continue;
+        }
+        let n: i64 = amount.parse().ok()?;
+        amount.clear();
+        let part = match c {
+            's' => Duration::try_seconds(n),
+            'm' => Duration::try_minutes(n),
+            'h' => Duration::try_hours(n),
+            'd' => Duration::try_days(n),
+            'w' => Duration::try_weeks(n),
+            _ => None,
+        }?;
+        total = total.checked_add(&part)?;
+    }
+    // A trailing number without a unit is not a duration; neither is ""
+    (amount.is_empty() && value.ends_with(['s', 'm', 'h', 'd', 'w'])).then_some(total)
+}
+
+/// Stream one host's rows of a table between `start` and `end` (oldest first)
+/// to `writer`, with the host of each row in a last `host` column (empty for
+/// rows collected on this machine)
+/// Rows are read and written one at a time; returns the number exported
+pub fn export_table<W: Write + Send>(
+    conn: &Connection,
+    table: ExportTable,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+    format: DataFormat,
+    writer: W,
+) -> Result<usize> {
+    match table {
+        ExportTable::Metrics => {
+            let mut sink = RowSink::<W, SystemMetrics>::new(writer, format)?;
+            let count = for_each_metric(conn, start, end, host, |host, row| sink.push(host, row))?;
+            sink.finish()?;
+            Ok(count)
+        }
+        ExportTable::Logs => {
+            let mut sink = RowSink::<W, LogEntry>::new(writer, format)?;
+            let count =
+                for_each_log_entry(conn, start, end, host, |host, row| sink.push(host, row))?;
+            sink.finish()?;
+            Ok(count)
+        }
+        ExportTable::Checks => {
+            let mut sink = RowSink::<W, SystemCheckResult>::new(writer, format)?;
+            let count =
+                for_each_system_check(conn, start, end, host, |host, row| sink.push(host, row))?;
+            sink.finish()?;
+            Ok(count)
+        }
+    }
+}
+
+/// A record type that can be written as CSV, JSON lines or Parquet
+trait ExportRow: Serialize + Sized {
+    /// Arrow schema; field names double as the CSV header
+    fn schema() -> SchemaRef;
+
+    /// Column values for one CSV record
+    fn csv_record(&self) -> Vec<String>;
+
+    /// Columnar arrays for a batch of rows, in schema order
+    fn columns(rows: &[Self]) -> Vec<ArrayRef>;
+}
+
+fn timestamp_field() -> Field {
+    Field::new(
+        "timestamp",
+        DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+        false,
+    )
+}
+
+fn timestamp_column(timestamps: impl Iterator<Item = DateTime<Utc>>) -> ArrayRef {
+    Arc::new(
+        TimestampSecondArray::from(timestamps.map(|t| t.timestamp()).collect::<Vec<_>>())
+            .with_timezone("UTC"),
+    )
+}
+
+fn string_column<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    Arc::new(StringArray::from(values.collect::<Vec<_>>()))
+}
+
+impl ExportRow for SystemMetrics {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            timestamp_field(),
+            Field::new("cpu_usage", DataType::Float32, false),
+            Field::new("memory_total", DataType::UInt64, false),
+            Field::new("memory_used", DataType::UInt64, false),
+            Field::new("memory_available", DataType::UInt64, false),
+            Field::new("disk_total", DataType::UInt64, false),
+            Field::new("disk_used", DataType::UInt64, false),
+            Field::new("process_count", DataType::UInt64, false),
+            Field::new("load_avg_1min", DataType::Float64, false),
+            Field::new("load_avg_5min", DataType::Float64, false),
+            Field::new("load_avg_15min", DataType::Float64, false),
+        ]))
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.timestamp.to_rfc3339(),
+            self.cpu_usage.to_string(),
+            self.memory_total.to_string(),
+            self.memory_used.to_string(),
+            self.memory_available.to_string(),
+            self.disk_total.to_string(),
+            self.disk_used.to_string(),
+            self.process_count.to_string(),
+            self.load_avg_1min.to_string(),
+            self.load_avg_5min.to_string(),
+            self.load_avg_15min.to_string(),
+        ]
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        let u64_column = |value: fn(&Self) -> u64| -> ArrayRef {
+            Arc::new(UInt64Array::from(rows.iter().map(value).collect::<Vec<_>>()))
+        };
+        let f64_column = |value: fn(&Self) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from(rows.iter().map(value).collect::<Vec<_>>()))
+        };
+
+        vec![
+            timestamp_column(rows.iter().map(|m| m.timestamp)),
+            Arc::new(Float32Array::from(rows.iter().map(|m| m.cpu_usage).collect::<Vec<_>>())),
+            u64_column(|m| m.memory_total),
+            u64_column(|m| m.memory_used),
+            u64_column(|m| m.memory_available),
+            u64_column(|m| m.disk_total),
+            u64_column(|m| m.disk_used),
+            u64_column(|m| m.process_count as u64),
+            f64_column(|m| m.load_avg_1min),
+            f64_column(|m| m.load_avg_5min),
+            f64_column(|m| m.load_avg_15min),
+        ]
+    }
+}
+
+impl ExportRow for LogEntry {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            timestamp_field(),
+            Field::new("level", DataType::Utf8, false),
+            Field::new("source", DataType::Utf8, false),
+            Field::new("message", DataType::Utf8, false),
+            // JSON text, as stored in the database
+            Field::new("attributes", DataType::Utf8, true),
+        ]))
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.timestamp.to_rfc3339(),
+            self.level.clone(),
+            self.source.clone(),
+            self.message.clone(),
+            self.attributes
+                .as_ref()
+                .map(|a| a.to_string())
+                .unwrap_or_default(),
+        ]
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        let attributes: Vec<Option<String>> = rows
+            .iter()
+            .map(|e| e.attributes.as_ref().map(|a| a.to_string()))
+            .collect();
+
+        vec![
+            timestamp_column(rows.iter().map(|e| e.timestamp)),
+            string_column(rows.iter().map(|e| e.level.as_str())),
+            string_column(rows.iter().map(|e| e.source.as_str())),
+            string_column(rows.iter().map(|e| e.message.as_str())),
+            Arc::new(StringArray::from(attributes)),
+        ]
+    }
+}
+
+impl ExportRow for SystemCheckResult {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            timestamp_field(),
+            Field::new("check_name", DataType::Utf8, false),
+            Field::new("check_type", DataType::Utf8, false),
+            Field::new("status", DataType::Utf8, false),
+            Field::new("value", DataType::Float64, true),
+            Field::new("unit", DataType::Utf8, true),
+            // JSON text, as stored in the database
+            Field::new("details", DataType::Utf8, true),
+            Field::new("message", DataType::Utf8, false),
+        ]))
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.timestamp.to_rfc3339(),
+            self.check_name.clone(),
+            self.check_type.clone(),
+            self.status.to_string(),
+            self.value.map(|v| v.to_string()).unwrap_or_default(),
+            self.unit.clone().unwrap_or_default(),
+            self.details
+                .as_ref()
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            self.message.clone(),
+        ]
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            timestamp_column(rows.iter().map(|c| c.timestamp)),
+            string_column(rows.iter().map(|c| c.check_name.as_str())),
+            string_column(rows.iter().map(|c| c.check_type.as_str())),
+            string_column(rows.iter().map(|c| c.status.as_str())),
+            Arc::new(Float64Array::from(
+                rows.iter().map(|c| c.value).collect::<Vec<_>>(),
+            )),
+            Arc::new(StringArray::from(
+                rows.iter().map(|c| c.unit.as_deref()).collect::<Vec<_>>(),
+            )),
+            Arc::new(StringArray::from(
+                rows.iter()
+                    .map(|c| c.details.as_ref().map(|d| d.to_string()))
+                    .collect::<Vec<_>>(),
+            )),
+            string_column(rows.iter().map(|c| c.message.as_str())),
+        ]
+    }
+}
+
+/// One exported row as a JSON line, with its host after the row's own fields
+#[derive(Serialize)]
+struct JsonRow<'a, T> {
+    #[serde(flatten)]
+    row: &'a T,
+    host: &'a str,
+}
+
+/// Destination for exported rows in one of the data formats
+enum RowSink<W: Write + Send, T: ExportRow> {
+    Csv(csv::Writer<W>),
+    Jsonl(W),
+    Parquet {
+        writer: ArrowWriter<W>,
+        schema: SchemaRef,
+        buffer: Vec<T>,
+        hosts: Vec<String>,
+    },
+}
+
+impl<W: Write + Send, T: ExportRow> RowSink<W, T> {
+    fn new(writer: W, format: DataFormat) -> Result<Self> {
+        let mut fields: Vec<Field> =
+            T::schema().fields().iter().map(|f| f.as_ref().clone()).collect();
+        fields.push(Field::new("host", DataType::Utf8, false));
+        let schema = Arc::new(Schema::new(fields));
+        match format {
+            DataFormat::Csv => {
+                let mut csv = csv::Writer::from_writer(writer);
+                csv.write_record(schema.fields().iter().map(|f| f.name()))?;
+                Ok(RowSink::Csv(csv))
+            }
+            DataFormat::Jsonl => Ok(RowSink::Jsonl(writer)),
+            DataFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .set_max_row_group_size(PARQUET_ROW_GROUP_ROWS)
+                    .build();
+                let writer = ArrowWriter::try_new(writer, schema.clone(), Some(properties))
+                    .context("Failed to start Parquet file")?;
+                Ok(RowSink::Parquet {
+                    writer,
+                    schema,
+                    buffer: Vec::with_capacity(PARQUET_BATCH_ROWS),
+                    hosts: Vec::with_capacity(PARQUET_BATCH_ROWS),
+                })
+            }
+        }
+    }
+
+    fn push(&mut self, host: &str, row: T) -> Result<()> {
+        match self {
+            RowSink::Csv(csv) => {
+                let mut record = row.csv_record();
+                record.push(host.to_string());
+                csv.write_record(record)?
+            }
+            RowSink::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &JsonRow { row: &row, host })?;
+                writer.write_all(b"\n")?;
+            }
+            RowSink::Parquet {
+                writer,
+                schema,
+                buffer,
+                hosts,
+            } => {
+                buffer.push(row);
+                hosts.push(host.to_string());
+                if buffer.len() >= PARQUET_BATCH_ROWS {
+                    write_batch(writer, schema, buffer, hosts)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            RowSink::Csv(mut csv) => csv.flush()?,
+            RowSink::Jsonl(mut writer) => writer.flush()?,
+            RowSink::Parquet {
+                mut writer,
+                schema,
+                mut buffer,
+                mut hosts,
+            } => {
+                write_batch(&mut writer, &schema, &mut buffer, &mut hosts)?;
+                writer.close().context("Failed to finish Parquet file")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_batch<W: Write + Send, T: ExportRow>(
+    writer: &mut ArrowWriter<W>,
+    schema: &SchemaRef,
+    buffer: &mut Vec<T>,
+    hosts: &mut Vec<String>,
+) -> Result<()> {
+    if buffer.is_empty() {
+        return Ok(());
+    }
+    let mut columns = T::columns(buffer);
+    columns.push(string_column(hosts.iter().map(String::as_str)));
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    writer.write(&batch).context("Failed to write Parquet rows")?;
+    buffer.clear();
+    hosts.clear();
+    Ok(())
+}
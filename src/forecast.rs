@@ -0,0 +1,300 @@
+use crate::config::{DEFAULT_FORECAST_HORIZON_DAYS, DEFAULT_FORECAST_LOOKBACK_DAYS, DISK_WARNING_THRESHOLD};
+use crate::db::{query_disk_usage, DiskUsage, HostFilter};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Samples needed before a trend is fitted for a mount point
+const MIN_SAMPLES: usize = 3;
+/// Samples kept for the fit; longer histories are thinned evenly, since
+/// Theil-Sen compares every pair of points
+const MAX_FIT_POINTS: usize = 400;
+/// Two-sided 95% normal quantile for the slope confidence interval
+const Z_95: f64 = 1.96;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+/// Projections further out than this (ten years) are treated as never;
+/// near-flat growth would otherwise put them past the end of representable time
+const MAX_PROJECTION_DAYS: f64 = 3_650.0;
+
+/// How far back trends are fitted and how soon a fill is worth flagging
+#[derive(Debug, Clone)]
+pub struct ForecastOptions {
+    pub lookback: Duration,
+    /// Mounts projected to be full within this time are flagged
+    pub horizon: Duration,
+    /// Disk usage percentage projected as the warning point
+    pub warning_percent: f64,
+}
+
+impl Default for ForecastOptions {
+    fn default() -> Self {
+        ForecastOptions {
+            lookback: Duration::days(DEFAULT_FORECAST_LOOKBACK_DAYS),
+            horizon: Duration::days(DEFAULT_FORECAST_HORIZON_DAYS),
+            warning_percent: DISK_WARNING_THRESHOLD as f64,
+        }
+    }
+}
+
+/// How much a projection can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForecastConfidence {
+    /// At least a day of history and the trend explains half the variation
+    High,
+    /// Some hours of history with a visible trend
+    Medium,
+    /// Short, sparse or noisy history
+    Low,
+}
+
+impl ForecastConfidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ForecastConfidence::High => "high",
+            ForecastConfidence::Medium => "medium",
+            ForecastConfidence::Low => "low",
+        }
+    }
+}
+
+/// When a mount point is expected to reach a usage level
+#[derive(Debug, Clone, Serialize)]
+pub struct Projection {
+    pub percent: f64,
+    pub at: DateTime<Utc>,
+    /// Days from the newest sample; 0 when the level is already reached
+    pub days: f64,
+    /// Earliest time within the 95% interval of the growth rate
+    pub earliest_days: f64,
+    /// Latest time within the interval; None when the slowest plausible
+    /// rate does not grow at all, or not within ten years
+    pub latest_days: Option<f64>,
+}
+
+/// Growth trend and projected fill times of one mount point
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskForecast {
+    pub mount_point: String,
+    pub filesystem: String,
+    pub samples: usize,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub used_percent: f64,
+    /// Theil-Sen slope of used bytes, in bytes per day
+    pub growth_per_day: f64,
+    /// 95% confidence interval of the growth rate
+    pub growth_per_day_low: f64,
+    pub growth_per_day_high: f64,
+    /// Share of the variation explained by the trend line
+    pub r_squared: f64,
+    pub confidence: ForecastConfidence,
+    /// None when usage is not growing, or too slowly to get there within ten years
+    pub warning: Option<Projection>,
+    pub full: Option<Projection>,
+    /// Projected to be full within the forecast horizon
+    pub flagged: bool,
+}
+
+/// Disk fill forecast for every mount point with enough history
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskForecastReport {
+    pub lookback_days: f64,
+    pub horizon_days: f64,
+    pub mounts: Vec<DiskForecast>,
+}
+
+impl DiskForecastReport {
+    /// Mount points projected to be full within the horizon, soonest first
+    pub fn flagged(&self) -> Vec<&DiskForecast> {
+        let mut flagged: Vec<&DiskForecast> = self.mounts.iter().filter(|m| m.flagged).collect();
+        flagged.sort_by(|a, b| {
+            let days = |m: &DiskForecast| m.full.as_ref().map_or(f64::MAX, |p| p.days);
+            days(a).total_cmp(&days(b))
+        });
+        flagged
+    }
+}
+
+/// Forecast disk fill times from a host's per-mount samples of `end - lookback..end`
+/// Returns None when no per-mount usage has been recorded in that window
+pub fn forecast_disks(
+    conn: &Connection,
+    end: DateTime<Utc>,
+    options: &ForecastOptions,
+    host: &HostFilter,
+) -> Result<Option<DiskForecastReport>> {
+    let usage = query_disk_usage(conn, end - options.lookback, end, host)
+        .context("Failed to query disk usage")?;
+    if usage.is_empty() {
+        return Ok(None);
+    }
+
+    let mut by_mount: BTreeMap<&str, Vec<&DiskUsage>> = BTreeMap::new();
+    for sample in &usage {
+        by_mount.entry(sample.mount_point.as_str()).or_default().push(sample);
+    }
+
+    let horizon_days = options.horizon.num_seconds() as f64 / SECONDS_PER_DAY;
+    let mounts = by_mount
+        .into_values()
+        .filter_map(|samples| forecast_mount(&samples, options.warning_percent, horizon_days))
+        .collect();
+
+    Ok(Some(DiskForecastReport {
+        lookback_days: options.lookback.num_seconds() as f64 / SECONDS_PER_DAY,
+        horizon_days,
+        mounts,
+    }))
+}
+
+/// Fit and project one mount point's samples (oldest first)
+fn forecast_mount(
+    samples: &[&DiskUsage],
+    warning_percent: f64,
+    horizon_days: f64,
+) -> Option<DiskForecast> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let first = samples[0].timestamp;
+    let latest = samples[samples.len() - 1];
+    let points: Vec<(f64, f64)> = thin(samples)
+        .iter()
+        .map(|s| {
+            let days = (s.timestamp - first).num_seconds() as f64 / SECONDS_PER_DAY;
+            (days, s.used_bytes as f64)
+        })
+        .collect();
+    let fit = theil_sen(&points)?;
+    let span_days = points[points.len() - 1].0 - points[0].0;
+
+    let confidence = if span_days >= 1.0 && samples.len() >= 12 && fit.r_squared >= 0.5 {
+        ForecastConfidence::High
+    } else if span_days >= 0.25 && fit.r_squared >= 0.2 {
+        ForecastConfidence::Medium
+    } else {
+        ForecastConfidence::Low
+    };
+
+    let project = |percent: f64| {
+        let target = latest.total_bytes as f64 * percent / 100.0;
+        let remaining = target - latest.used_bytes as f64;
+        let days_at = |rate: f64| {
+            let days = remaining.max(0.0) / rate;
+            (rate > 0.0 && days <= MAX_PROJECTION_DAYS).then_some(days)
+        };
+        let days = if remaining <= 0.0 { Some(0.0) } else { days_at(fit.slope) }?;
+        let offset = Duration::try_seconds((days * SECONDS_PER_DAY) as i64)?;
+        Some(Projection {
+            percent,
+            at: latest.timestamp.checked_add_signed(offset)?,
+            days,
+            earliest_days: days_at(fit.slope_high).unwrap_or(days).min(days),
+            latest_days: if remaining <= 0.0 { Some(0.0) } else { days_at(fit.slope_low) },
+        })
+    };
+    let full = project(100.0);
+
+    Some(DiskForecast {
+        mount_point: latest.mount_point.clone(),
+        filesystem: latest.filesystem.clone(),
+        samples: samples.len(),
+        total_bytes: latest.total_bytes,
+        used_bytes: latest.used_bytes,
+        used_percent: latest.used_percent(),
+        growth_per_day: fit.slope,
+        growth_per_day_low: fit.slope_low,
+        growth_per_day_high: fit.slope_high,
+        r_squared: fit.r_squared,
+        confidence,
+        warning: project(warning_percent),
+        flagged: full.as_ref().is_some_and(|p| p.days <= horizon_days),
+        full,
+    })
+}
+
+/// Every sample when there are few, otherwise an even spread that keeps the newest
+fn thin<'a>(samples: &[&'a DiskUsage]) -> Vec<&'a DiskUsage> {
+    if samples.len() <= MAX_FIT_POINTS {
+        return samples.to_vec();
+    }
+    let step = (samples.len() - 1) as f64 / (MAX_FIT_POINTS - 1) as f64;
+    (0..MAX_FIT_POINTS)
+        .map(|i| samples[(i as f64 * step).round() as usize])
+        .collect()
+}
+
+/// Robust line through a set of points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendFit {
+    pub slope: f64,
+    pub intercept: f64,
+    /// 95% confidence interval of the slope (Sen's method)
+    pub slope_low: f64,
+    pub slope_high: f64,
+    pub r_squared: f64,
+}
+
+/// Theil-Sen estimator: the median of the slopes between every pair of points
+/// One outlier (a log rotated away, a large temporary file) moves the line far
+/// less than it would a least-squares fit. Returns None without two distinct x values
+pub fn theil_sen(points: &[(f64, f64)]) -> Option<TrendFit> {
+    let mut slopes = Vec::new();
+    for (i, &(x1, y1)) in points.iter().enumerate() {
+        for &(x2, y2) in &points[i + 1..] {
+            if x2 != x1 {
+                slopes.push((y2 - y1) / (x2 - x1));
+            }
+        }
+    }
+    if slopes.is_empty() {
+        return None;
+    }
+    slopes.sort_by(f64::total_cmp);
+    let slope = median(&slopes);
+
+    let mut offsets: Vec<f64> = points.iter().map(|&(x, y)| y - slope * x).collect();
+    offsets.sort_by(f64::total_cmp);
+    let intercept = median(&offsets);
+
+    // Ranks of the interval bounds among the sorted slopes
+    let n = points.len() as f64;
+    let pairs = slopes.len() as f64;
+    let spread = Z_95 * (n * (n - 1.0) * (2.0 * n + 5.0) / 18.0).sqrt();
+    let last = slopes.len() - 1;
+    let low_rank = (((pairs - spread) / 2.0).floor().max(0.0) as usize).min(last);
+    let high_rank = (((pairs + spread) / 2.0).ceil().max(0.0) as usize).min(last);
+
+    let mean = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let total: f64 = points.iter().map(|p| (p.1 - mean).powi(2)).sum();
+    let residual: f64 = points
+        .iter()
+        .map(|&(x, y)| (y - (intercept + slope * x)).powi(2))
+        .sum();
+    // A flat series is fully explained by a flat line
+    let r_squared = if total == 0.0 {
+        1.0
+    } else {
+        (1.0 - residual / total).max(0.0)
+    };
+
+    Some(TrendFit {
+        slope,
+        intercept,
+        slope_low: slopes[low_rank],
+        slope_high: slopes[high_rank],
+        r_squared,
+    })
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
@@ -0,0 +1,717 @@
+use crate::config::{
+    CPU_WARNING_THRESHOLD, DISK_WARNING_THRESHOLD, LOAD_WARNING_THRESHOLD,
+    MEMORY_WARNING_THRESHOLD,
+};
+use crate::db::{CheckStatus, SystemCheckResult};
+use crate::kernel::{
+    EVENT_FS_ERROR, EVENT_HARDWARE_ERROR, EVENT_IO_ERROR, EVENT_OOM_KILL, EVENT_SEGFAULT,
+};
+use crate::reporter::{
+    describe_fill_time, describe_kernel_event, format_size, FullReport,
+    MetricPoint,
+};
+use crate::timezone::DisplayTimezone;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Results per check shown in the status grid (newest on the right)
+const GRID_COLUMNS: usize = 12;
+
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 180.0;
+const CHART_LEFT: f64 = 48.0;
+const CHART_RIGHT: f64 = 12.0;
+const CHART_TOP: f64 = 12.0;
+const CHART_BOTTOM: f64 = 24.0;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0; background: #f4f5f7; color: #1f2328; }
+main { max-width: 960px; margin: 0 auto; padding: 24px; }
+h1 { font-size: 22px; margin: 0 0 4px; }
+h2 { font-size: 17px; margin: 28px 0 10px; border-bottom: 1px solid #d0d7de; padding-bottom: 4px; }
+.meta { color: #57606a; font-size: 13px; }
+.cards { display: grid; grid-template-columns: repeat(auto-fit, minmax(170px, 1fr)); gap: 12px; }
+.card { background: #fff; border: 1px solid #d0d7de; border-left: 4px solid #2da44e; border-radius: 6px; padding: 10px 12px; }
+.card.warning { border-left-color: #d4a72c; }
+.card .label { font-size: 12px; color: #57606a; text-transform: uppercase; }
+.card .value { font-size: 22px; font-weight: 600; }
+.card .sub { font-size: 12px; color: #57606a; }
+.issues { list-style: none; padding: 0; margin: 0; }
+.issues li { padding: 8px 12px; margin-bottom: 6px; border-radius: 6px; border-left: 4px solid; background: #fff; }
+.issues .critical { border-color: #cf222e; background: #ffebe9; }
+.issues .warning { border-color: #d4a72c; background: #fff8c5; }
+.issues .info { border-color: #0969da; background: #ddf4ff; }
+.issues code { display: block; font-size: 12px; margin-top: 3px; }
+.chart { background: #fff; border: 1px solid #d0d7de; border-radius: 6px; margin-bottom: 12px; padding: 8px; }
+.chart h3 { font-size: 14px; margin: 0 0 4px 4px; }
+.chart svg { width: 100%; height: auto; }
+.axis { font-size: 10px; fill: #57606a; }
+.grid { stroke: #eaeef2; }
+.threshold { stroke: #cf222e; stroke-dasharray: 4 3; }
+.line { fill: none; stroke: #0969da; stroke-width: 1.5; }
+table { width: 100%; border-collapse: collapse; background: #fff; font-size: 13px; }
+th, td { text-align: left; padding: 6px 8px; border-bottom: 1px solid #eaeef2; vertical-align: top; }
+th { background: #f6f8fa; }
+table.sortable th { cursor: pointer; user-select: none; }
+table.sortable th::after { content: " \2195"; color: #8c959f; }
+.level-CRITICAL { color: #cf222e; font-weight: 600; }
+.level-ERROR { color: #bc4c00; font-weight: 600; }
+.status-grid td.cell { width: 18px; padding: 0; }
+.status-grid .dot { display: block; width: 14px; height: 14px; border-radius: 3px; margin: 0 auto; }
+.status-ok { background: #2da44e; }
+.status-warning { background: #d4a72c; }
+.status-critical { background: #cf222e; }
+.status-error { background: #8250df; }
+.status-unknown { background: #8c959f; }
+tr.flagged td { background: #ffebe9; }
+.badge { display: inline-block; padding: 1px 6px; border-radius: 10px; color: #fff; font-size: 11px; text-transform: uppercase; }
+.muted { color: #57606a; }
+"#;
+
+/// Click-to-sort for tables with class "sortable"
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable").forEach(function (table) {
+  table.querySelectorAll("th").forEach(function (th, column) {
+    var ascending = true;
+    th.addEventListener("click", function () {
+      var body = table.tBodies[0];
+      var rows = Array.prototype.slice.call(body.rows);
+      rows.sort(function (a, b) {
+        var x = a.cells[column].getAttribute("data-sort") || a.cells[column].textContent;
+        var y = b.cells[column].getAttribute("data-sort") || b.cells[column].textContent;
+        return ascending ? x.localeCompare(y) : y.localeCompare(x);
+      });
+      ascending = !ascending;
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+"#;
+
+/// Render a report as one self-contained HTML page
+/// Styles, charts (inline SVG) and the table sorting script are embedded,
+/// so the file can be emailed or archived and opened without network access
+pub fn format_html_report(report: &FullReport) -> String {
+    let metrics = &report.metrics;
+    let local_start = report.timezone.convert(metrics.period_start);
+    let local_end = report.timezone.convert(metrics.period_end);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    let _ = writeln!(
+        html,
+        "<title>Systers report {}</title>",
+        local_end.format("%Y-%m-%d %H:%M")
+    );
+    let _ = writeln!(html, "<style>{}</style>\n</head>\n<body>\n<main>", STYLE);
+
+    html.push_str("<h1>System Analysis Report</h1>\n");
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">{} to {}{} &middot; systers v{}{}</p>",
+        local_start.format("%Y-%m-%d %H:%M:%S %Z"),
+        local_end.format("%Y-%m-%d %H:%M:%S %Z"),
+        report
+            .shown_host()
+            .map(|host| format!(" &middot; host {}", escape(host)))
+            .unwrap_or_default(),
+        escape(&report.version),
+        if metrics.resolution == crate::db::Resolution::Raw {
+            String::new()
+        } else {
+            format!(" &middot; {} rollups", metrics.resolution.as_str())
+        }
+    );
+
+    html.push_str(&summary_cards(report));
+    html.push_str(&issues_section(report));
+    html.push_str(&charts_section(report));
+    html.push_str(&logs_section(report));
+    html.push_str(&checks_section(&report.system_checks.checks, report.timezone));
+    html.push_str(&events_section(report));
+
+    html.push_str("<h2>Recommendations</h2>\n");
+    if report.recommendations.is_empty() {
+        html.push_str("<p>System appears healthy - no immediate action required.</p>\n");
+    } else {
+        html.push_str("<ul class=\"issues\">\n");
+        for rec in &report.recommendations {
+            let _ = write!(
+                html,
+                "<li class=\"{}\"><strong>{}</strong> <span class=\"muted\">{}</span><br>{}",
+                rec.severity.as_str(),
+                escape(&rec.finding),
+                rec.id,
+                escape(&rec.action)
+            );
+            for command in &rec.commands {
+                let _ = write!(html, "<code>$ {}</code>", escape(command));
+            }
+            html.push_str("</li>\n");
+        }
+        html.push_str("</ul>\n");
+    }
+
+    let _ = writeln!(html, "</main>\n<script>{}</script>\n</body>\n</html>", SCRIPT);
+    html
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn local_time(time: DateTime<Utc>, timezone: DisplayTimezone) -> String {
+    let local = timezone.convert(time);
+    local.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn summary_cards(report: &FullReport) -> String {
+    let metrics = &report.metrics;
+    let logs = &report.logs;
+    let cards = [
+        (
+            "CPU",
+            format!("{:.1}%", metrics.avg_cpu_usage),
+            format!("peak {:.1}%", metrics.max_cpu_usage),
+            metrics.max_cpu_usage > CPU_WARNING_THRESHOLD,
+        ),
+        (
+            "Memory",
+            format!("{:.1}%", metrics.avg_memory_used_percent),
+            format!("peak {:.1}%", metrics.max_memory_used_percent),
+            metrics.max_memory_used_percent > MEMORY_WARNING_THRESHOLD,
+        ),
+        (
+            "Disk",
+            format!("{:.1}%", metrics.avg_disk_used_percent),
+            format!("peak {:.1}%", metrics.max_disk_used_percent),
+            metrics.max_disk_used_percent > DISK_WARNING_THRESHOLD,
+        ),
+        (
+            "Load (1 min)",
+            format!("{:.2}", metrics.max_load_avg_1min),
+            "peak".to_string(),
+            metrics.max_load_avg_1min > LOAD_WARNING_THRESHOLD,
+        ),
+        (
+            "Log Errors",
+            (logs.total_errors + logs.total_critical).to_string(),
+            format!("{} critical, {} warnings", logs.total_critical, logs.total_warnings),
+            logs.total_critical > 0,
+        ),
+    ];
+
+    let mut html = String::from("<h2>Summary</h2>\n<div class=\"cards\">\n");
+    for (label, value, sub, warning) in cards {
+        let _ = writeln!(
+            html,
+            "<div class=\"card{}\"><div class=\"label\">{}</div><div class=\"value\">{}</div><div class=\"sub\">{}</div></div>",
+            if warning { " warning" } else { "" },
+            label,
+            value,
+            escape(&sub)
+        );
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+/// Problems found anywhere in the report, most severe first
+fn issues_section(report: &FullReport) -> String {
+    let mut issues: Vec<(&str, String)> = Vec::new();
+
+    if let Some(ref kernel) = report.kernel {
+        for (event_type, label) in [
+            (EVENT_OOM_KILL, "OOM kills"),
+            (EVENT_HARDWARE_ERROR, "hardware errors"),
+            (EVENT_IO_ERROR, "disk I/O errors"),
+            (EVENT_FS_ERROR, "filesystem errors"),
+        ] {
+            let count = kernel.count(event_type);
+            if count > 0 {
+                issues.push(("critical", format!("Kernel reported {} {}", count, label)));
+            }
+        }
+    }
+    if report.logs.total_critical > 0 {
+        issues.push((
+            "critical",
+            format!("{} critical log entries", report.logs.total_critical),
+        ));
+    }
+    if let Some(ref security) = report.security {
+        if !security.brute_force_bursts.is_empty() {
+            issues.push((
+                "critical",
+                format!(
+                    "{} SSH brute-force bursts detected",
+                    security.brute_force_bursts.len()
+                ),
+            ));
+        }
+    }
+    for check in report.system_checks.latest() {
+        let class = match check.status {
+            CheckStatus::Critical | CheckStatus::Error => "critical",
+            CheckStatus::Warning => "warning",
+            _ => continue,
+        };
+        issues.push((class, format!("{}: {}", check.check_name, check.message)));
+    }
+    for issue in &report.metrics.issues {
+        issues.push((issue.severity.as_str(), issue.message.clone()));
+    }
+    if let Some(ref collection) = report.collection {
+        if !collection.gaps.is_empty() {
+            issues.push((
+                "warning",
+                format!(
+                    "{} gaps in scheduled collection (~{} missed runs)",
+                    collection.gaps.len(),
+                    collection.missed_runs()
+                ),
+            ));
+        }
+    }
+
+    let mut html = String::from("<h2>Issues</h2>\n");
+    if issues.is_empty() {
+        html.push_str("<ul class=\"issues\"><li class=\"info\">No issues detected</li></ul>\n");
+        return html;
+    }
+    let rank = |class: &str| match class {
+        "critical" => 0,
+        "warning" => 1,
+        _ => 2,
+    };
+    issues.sort_by_key(|(class, _)| rank(class));
+    html.push_str("<ul class=\"issues\">\n");
+    for (class, text) in issues {
+        let _ = writeln!(html, "<li class=\"{}\">{}</li>", class, escape(&text));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+/// One metric chart: how to read the value and how to scale it
+struct Chart {
+    title: &'static str,
+    value: fn(&MetricPoint) -> f64,
+    y_max: f64,
+    threshold: f64,
+    unit: &'static str,
+}
+
+fn charts_section(report: &FullReport) -> String {
+    let start = report.metrics.period_start;
+    let end = report.metrics.period_end;
+    let series = &report.series;
+    let peak_load = series.iter().map(|p| p.load_avg_1min).fold(0.0, f64::max);
+
+    let charts = [
+        Chart {
+            title: "CPU Usage",
+            value: |p| p.cpu_usage,
+            y_max: 100.0,
+            threshold: CPU_WARNING_THRESHOLD as f64,
+            unit: "%",
+        },
+        Chart {
+            title: "Memory Usage",
+            value: |p| p.memory_used_percent,
+            y_max: 100.0,
+            threshold: MEMORY_WARNING_THRESHOLD as f64,
+            unit: "%",
+        },
+        Chart {
+            title: "Disk Usage",
+            value: |p| p.disk_used_percent,
+            y_max: 100.0,
+            threshold: DISK_WARNING_THRESHOLD as f64,
+            unit: "%",
+        },
+        Chart {
+            title: "Load Average (1 min)",
+            value: |p| p.load_avg_1min,
+            y_max: nice_ceiling(peak_load.max(LOAD_WARNING_THRESHOLD) * 1.1),
+            threshold: LOAD_WARNING_THRESHOLD,
+            unit: "",
+        },
+    ];
+
+    let mut html = String::from("<h2>Metrics Over Time</h2>\n");
+    for chart in charts {
+        let points: Vec<(DateTime<Utc>, f64)> =
+            series.iter().map(|p| (p.timestamp, (chart.value)(p))).collect();
+        let _ = writeln!(
+            html,
+            "<div class=\"chart\"><h3>{}</h3>{}</div>",
+            chart.title,
+            line_chart(
+                &points,
+                start,
+                end,
+                chart.y_max,
+                chart.threshold,
+                chart.unit,
+                report.timezone
+            )
+        );
+    }
+    html
+}
+
+/// Round up to 1, 2 or 5 times a power of ten
+fn nice_ceiling(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|candidate| *candidate >= value)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// SVG line chart of `points` over `start..end`
+/// The line is broken where points are more than three typical intervals apart
+fn line_chart(
+    points: &[(DateTime<Utc>, f64)],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    y_max: f64,
+    threshold: f64,
+    unit: &str,
+    timezone: DisplayTimezone,
+) -> String {
+    let plot_width = CHART_WIDTH - CHART_LEFT - CHART_RIGHT;
+    let plot_height = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+    let span = (end - start).num_seconds().max(1) as f64;
+    let x = |time: DateTime<Utc>| {
+        CHART_LEFT + ((time - start).num_seconds() as f64 / span).clamp(0.0, 1.0) * plot_width
+    };
+    let y = |value: f64| CHART_TOP + plot_height * (1.0 - (value / y_max).clamp(0.0, 1.0));
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\" role=\"img\">",
+        CHART_WIDTH, CHART_HEIGHT
+    );
+
+    for step in 0..=4 {
+        let value = y_max * step as f64 / 4.0;
+        let _ = write!(
+            svg,
+            "<line class=\"grid\" x1=\"{:.1}\" x2=\"{:.1}\" y1=\"{:.1}\" y2=\"{:.1}\"/>\
+             <text class=\"axis\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}{}</text>",
+            CHART_LEFT,
+            CHART_WIDTH - CHART_RIGHT,
+            y(value),
+            y(value),
+            CHART_LEFT - 4.0,
+            y(value) + 3.0,
+            format_tick(value),
+            unit
+        );
+    }
+    for (time, anchor) in [
+        (start, "start"),
+        (start + (end - start) / 2, "middle"),
+        (end, "end"),
+    ] {
+        let local = timezone.convert(time);
+        let _ = write!(
+            svg,
+            "<text class=\"axis\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\">{}</text>",
+            x(time),
+            CHART_HEIGHT - 6.0,
+            anchor,
+            local.format("%m-%d %H:%M")
+        );
+    }
+    if threshold < y_max {
+        let _ = write!(
+            svg,
+            "<line class=\"threshold\" x1=\"{:.1}\" x2=\"{:.1}\" y1=\"{:.1}\" y2=\"{:.1}\"/>",
+            CHART_LEFT,
+            CHART_WIDTH - CHART_RIGHT,
+            y(threshold),
+            y(threshold)
+        );
+    }
+
+    if points.is_empty() {
+        let _ = write!(
+            svg,
+            "<text class=\"axis\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">No data</text>",
+            CHART_LEFT + plot_width / 2.0,
+            CHART_TOP + plot_height / 2.0
+        );
+    } else {
+        let mut gaps: Vec<i64> = points
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0).num_seconds())
+            .collect();
+        gaps.sort_unstable();
+        let typical = gaps.get(gaps.len() / 2).copied().unwrap_or(0);
+
+        let mut path = String::new();
+        for (i, (time, value)) in points.iter().enumerate() {
+            let broken = i == 0 || (*time - points[i - 1].0).num_seconds() > typical * 3;
+            let _ = write!(
+                path,
+                "{}{:.1},{:.1} ",
+                if broken { "M" } else { "L" },
+                x(*time),
+                y(*value)
+            );
+        }
+        let _ = write!(svg, "<path class=\"line\" d=\"{}\"/>", path.trim_end());
+        // Lone points would not be visible as a path
+        if points.len() == 1 {
+            let _ = write!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"#0969da\"/>",
+                x(points[0].0),
+                y(points[0].1)
+            );
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn format_tick(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+fn logs_section(report: &FullReport) -> String {
+    let logs = &report.logs;
+    let mut html = String::from("<h2>Log Errors</h2>\n");
+    let _ = writeln!(
+        html,
+        "<p>{} critical, {} errors, {} warnings</p>",
+        logs.total_critical, logs.total_errors, logs.total_warnings
+    );
+    if logs.recent_errors.is_empty() {
+        return html;
+    }
+
+    html.push_str(
+        "<table class=\"sortable\">\n<thead><tr><th>Time</th><th>Level</th><th>Source</th><th>Message</th></tr></thead>\n<tbody>\n",
+    );
+    for entry in &logs.recent_errors {
+        let trace_lines = entry.continuation_lines();
+        let message = if trace_lines > 0 {
+            format!(
+                "<details><summary>{}</summary><pre>{}</pre></details>",
+                escape(entry.headline()),
+                escape(&entry.message)
+            )
+        } else {
+            escape(&entry.message)
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td data-sort=\"{}\">{}</td><td class=\"level-{}\">{}</td><td>{}</td><td>{}</td></tr>",
+            entry.timestamp.timestamp(),
+            local_time(entry.timestamp, report.timezone),
+            escape(&entry.level),
+            escape(&entry.level),
+            escape(&entry.source),
+            message
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// Grid of recent results per check, oldest to newest
+fn checks_section(checks: &[SystemCheckResult], timezone: DisplayTimezone) -> String {
+    if checks.is_empty() {
+        return String::new();
+    }
+
+    let mut by_name: BTreeMap<&str, Vec<&SystemCheckResult>> = BTreeMap::new();
+    for check in checks {
+        by_name.entry(check.check_name.as_str()).or_default().push(check);
+    }
+
+    let mut html = String::from("<h2>System Checks</h2>\n<table class=\"status-grid\">\n");
+    let _ = writeln!(
+        html,
+        "<thead><tr><th>Check</th><th colspan=\"{}\">Recent results</th><th>Latest</th></tr></thead>\n<tbody>",
+        GRID_COLUMNS
+    );
+    for (name, mut results) in by_name {
+        results.sort_by_key(|check| check.timestamp);
+        let recent = &results[results.len().saturating_sub(GRID_COLUMNS)..];
+        let _ = write!(html, "<tr><td>{}</td>", escape(name));
+        for _ in recent.len()..GRID_COLUMNS {
+            html.push_str("<td class=\"cell\"></td>");
+        }
+        for check in recent {
+            let _ = write!(
+                html,
+                "<td class=\"cell\"><span class=\"dot status-{}\" title=\"{} {}: {}\"></span></td>",
+                check.status,
+                local_time(check.timestamp, timezone),
+                check.status,
+                escape(&check.message)
+            );
+        }
+        let latest = recent[recent.len() - 1];
+        let value = match (latest.value, &latest.unit) {
+            (Some(value), Some(unit)) => format!(" ({} {})", format_tick(value), escape(unit)),
+            (Some(value), None) => format!(" ({})", format_tick(value)),
+            _ => String::new(),
+        };
+        let _ = writeln!(
+            html,
+            "<td><span class=\"badge status-{}\">{}</span>{} <span class=\"muted\">{}</span></td></tr>",
+            latest.status,
+            latest.status,
+            value,
+            escape(&latest.message)
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// Collection health, kernel and security details
+fn events_section(report: &FullReport) -> String {
+    let mut html = String::new();
+
+    if let Some(ref forecast) = report.disk_forecast {
+        html.push_str("<h2>Disk Forecast</h2>\n");
+        let _ = writeln!(
+            html,
+            "<p>Trend over the last {:.0} days; mounts full within {:.0} days are highlighted</p>",
+            forecast.lookback_days, forecast.horizon_days
+        );
+        html.push_str("<table>\n<thead><tr><th>Mount</th><th>Used</th><th>Growth/day (95%)</th><th>Full</th><th>Confidence</th></tr></thead>\n<tbody>\n");
+        for mount in &forecast.mounts {
+            let _ = writeln!(
+                html,
+                "<tr{}><td>{} ({})</td><td>{:.1}% of {}</td><td>{} ({} to {})</td><td>{}</td><td>{} (R² {:.2})</td></tr>",
+                if mount.flagged { " class=\"flagged\"" } else { "" },
+                escape(&mount.mount_point),
+                escape(&mount.filesystem),
+                mount.used_percent,
+                format_size(mount.total_bytes as f64),
+                format_size(mount.growth_per_day),
+                format_size(mount.growth_per_day_low),
+                format_size(mount.growth_per_day_high),
+                escape(&describe_fill_time(mount, report.timezone)),
+                mount.confidence.as_str(),
+                mount.r_squared
+            );
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    if let Some(ref collection) = report.collection {
+        html.push_str("<h2>Collection Health</h2>\n");
+        let _ = writeln!(
+            html,
+            "<p>{} runs ({} ok, {} partial, {} failed, {} interrupted), every {} minutes expected, {} missed</p>",
+            collection.total_runs,
+            collection.ok_runs,
+            collection.partial_runs,
+            collection.failed_runs,
+            collection.interrupted_runs,
+            collection.interval_minutes,
+            collection.missed_runs()
+        );
+        if !collection.gaps.is_empty() {
+            html.push_str("<table>\n<thead><tr><th>Gap from</th><th>To</th><th>Missed runs</th></tr></thead>\n<tbody>\n");
+            for gap in &collection.gaps {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    local_time(gap.from, report.timezone),
+                    local_time(gap.to, report.timezone),
+                    gap.missed_runs
+                );
+            }
+            html.push_str("</tbody>\n</table>\n");
+        }
+    }
+
+    if let Some(ref kernel) = report.kernel {
+        html.push_str("<h2>Kernel Events</h2>\n");
+        let _ = writeln!(
+            html,
+            "<p>{} OOM kills, {} segfaults, {} hardware errors, {} I/O errors, {} filesystem errors</p>",
+            kernel.count(EVENT_OOM_KILL),
+            kernel.count(EVENT_SEGFAULT),
+            kernel.count(EVENT_HARDWARE_ERROR),
+            kernel.count(EVENT_IO_ERROR),
+            kernel.count(EVENT_FS_ERROR)
+        );
+        html.push_str("<table class=\"sortable\">\n<thead><tr><th>Time</th><th>Type</th><th>Event</th></tr></thead>\n<tbody>\n");
+        for event in &kernel.recent_events {
+            let _ = writeln!(
+                html,
+                "<tr><td data-sort=\"{}\">{}</td><td>{}</td><td>{}</td></tr>",
+                event.timestamp.timestamp(),
+                local_time(event.timestamp, report.timezone),
+                escape(&event.event_type),
+                escape(&describe_kernel_event(event))
+            );
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    if let Some(ref security) = report.security {
+        html.push_str("<h2>Security (SSH &amp; sudo)</h2>\n");
+        let _ = writeln!(
+            html,
+            "<p>{} failed logins ({} invalid user), {} accepted logins, {} sudo commands ({} rejected)</p>",
+            security.failed_logins,
+            security.invalid_user_attempts,
+            security.accepted_logins,
+            security.sudo_commands,
+            security.sudo_failures
+        );
+        if !security.top_attacking_ips.is_empty() {
+            html.push_str("<table class=\"sortable\">\n<thead><tr><th>Source IP</th><th>Failed logins</th><th>Invalid users</th></tr></thead>\n<tbody>\n");
+            for ip in &security.top_attacking_ips {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td data-sort=\"{:010}\">{}</td><td data-sort=\"{:010}\">{}</td></tr>",
+                    escape(&ip.source_ip),
+                    ip.failed_logins,
+                    ip.failed_logins,
+                    ip.invalid_users,
+                    ip.invalid_users
+                );
+            }
+            html.push_str("</tbody>\n</table>\n");
+        }
+    }
+
+    html
+}
@@ -0,0 +1,284 @@
+use crate::collector::extract_timestamp;
+use crate::db::KernelEvent;
+use crate::system_checks::is_command_available;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use regex::Regex;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Process killed by the OOM killer
+pub const EVENT_OOM_KILL: &str = "oom_kill";
+/// Process terminated by a segmentation fault
+pub const EVENT_SEGFAULT: &str = "segfault";
+/// Machine check (MCE) or EDAC memory controller error
+pub const EVENT_HARDWARE_ERROR: &str = "hardware_error";
+/// Block device I/O error
+pub const EVENT_IO_ERROR: &str = "io_error";
+/// Filesystem error or forced read-only remount
+pub const EVENT_FS_ERROR: &str = "fs_error";
+/// System start, recorded by syswriter from the kernel's boot time
+pub const EVENT_BOOT: &str = "boot";
+
+/// File names of logs that carry kernel messages
+const KERNEL_LOG_NAMES: &[&str] = &["kern.log", "syslog", "messages"];
+
+/// Check whether a log path contains kernel messages
+pub fn is_kernel_log<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| KERNEL_LOG_NAMES.contains(&name))
+        .unwrap_or(false)
+}
+
+/// Compiled kernel message patterns (cached using OnceLock)
+struct KernelPatterns {
+    oom: Regex,
+    segfault: Regex,
+    hardware: Regex,
+    io_error: Regex,
+    fs_error: Regex,
+    monotonic_prefix: Regex,
+}
+
+fn get_kernel_patterns() -> &'static KernelPatterns {
+    static PATTERNS: OnceLock<KernelPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| KernelPatterns {
+        // Out of memory: Killed process 1234 (java) total-vm:8123456kB, anon-rss:4012345kB, ...
+        oom: Regex::new(
+            r"(?:Out of memory|Memory cgroup out of memory): Kill(?:ed)? process (\d+) \(([^)]+)\)(?:.*?total-vm:(\d+)kB, anon-rss:(\d+)kB)?",
+        )
+        .unwrap(),
+        // myapp[4321]: segfault at 0 ip 000055d5c0a0b1c2 sp 00007ffd1e2f3a40 error 4 in myapp[55d5c0a00000+20000]
+        segfault: Regex::new(
+            r"(\S+)\[(\d+)\]: segfault at ([0-9a-fA-F]+) ip ([0-9a-fA-F]+) sp ([0-9a-fA-F]+) error (\d+)(?: in (\S+?)(?:\[|$))?",
+        )
+        .unwrap(),
+        // mce: [Hardware Error]: ... / EDAC MC0: 1 CE memory read error on ...
+        hardware: Regex::new(r"(mce: \[Hardware Error\]|EDAC \S+: .*\b(?:CE|UE)\b|Machine check events logged)")
+            .unwrap(),
+        // blk_update_request: I/O error, dev sda, sector 12345 / Buffer I/O error on dev sda1, logical block 0
+        io_error: Regex::new(r"I/O error,? (?:on )?dev ([\w-]+)(?:, (?:sector|logical block) (\d+))?")
+            .unwrap(),
+        // EXT4-fs error (device sda1): ... / EXT4-fs (sda1): Remounting filesystem read-only
+        fs_error: Regex::new(r"(EXT[234]-fs|XFS|BTRFS)(?: \w+)? \((?:device )?([\w-]+)\):? (.*)").unwrap(),
+        // [ 1234.567890] prefix from dmesg and some syslog configurations
+        monotonic_prefix: Regex::new(r"^\[\s*(\d+)\.(\d+)\]\s*").unwrap(),
+    })
+}
+
+/// Strip the syslog header and the "[ 123.456]" prefix from a kernel line
+fn kernel_message(line: &str) -> &str {
+    let message = match line.find("kernel: ") {
+        Some(index) => &line[index + "kernel: ".len()..],
+        None => line,
+    };
+    let patterns = get_kernel_patterns();
+    match patterns.monotonic_prefix.find(message) {
+        Some(prefix) => &message[prefix.end()..],
+        None => message,
+    }
+    .trim()
+}
+
+/// Parse a kernel message into a structured event
+/// Returns None for kernel messages that are not one of the tracked event types
+pub fn parse_kernel_line(line: &str, fallback_timestamp: DateTime<Utc>) -> Option<KernelEvent> {
+    let patterns = get_kernel_patterns();
+    let message = kernel_message(line);
+    let event = |event_type: &str| KernelEvent {
+        timestamp: extract_timestamp(line).unwrap_or(fallback_timestamp),
+        event_type: event_type.to_string(),
+        process: None,
+        pid: None,
+        device: None,
+        details: None,
+        message: message.to_string(),
+    };
+
+    if let Some(cap) = patterns.oom.captures(message) {
+        let kb = |i: usize| cap.get(i).and_then(|m| m.as_str().parse::<u64>().ok());
+        return Some(KernelEvent {
+            pid: cap[1].parse().ok(),
+            process: Some(cap[2].to_string()),
+            details: Some(json!({
+                "total_vm_kb": kb(3),
+                "anon_rss_kb": kb(4),
+            })),
+            ..event(EVENT_OOM_KILL)
+        });
+    }
+
+    if let Some(cap) = patterns.segfault.captures(message) {
+        return Some(KernelEvent {
+            process: Some(cap[1].to_string()),
+            pid: cap[2].parse().ok(),
+            details: Some(json!({
+                "address": &cap[3],
+                "ip": &cap[4],
+                "sp": &cap[5],
+                "error_code": cap[6].parse::<u32>().ok(),
+                "object": cap.get(7).map(|m| m.as_str()),
+            })),
+            ..event(EVENT_SEGFAULT)
+        });
+    }
+
+    if let Some(cap) = patterns.hardware.captures(message) {
+        let source = if cap[1].starts_with("EDAC") { "edac" } else { "mce" };
+        return Some(KernelEvent {
+            details: Some(json!({ "source": source })),
+            ..event(EVENT_HARDWARE_ERROR)
+        });
+    }
+
+    if let Some(cap) = patterns.io_error.captures(message) {
+        return Some(KernelEvent {
+            device: Some(cap[1].to_string()),
+            details: cap
+                .get(2)
+                .and_then(|m| m.as_str().parse::<u64>().ok())
+                .map(|sector| json!({ "sector": sector })),
+            ..event(EVENT_IO_ERROR)
+        });
+    }
+
+    if let Some(cap) = patterns.fs_error.captures(message) {
+        let text = cap[3].to_lowercase();
+        let remounted_read_only = text.contains("read-only");
+        // Mount notices and other informational filesystem messages are ignored
+        if !(remounted_read_only
+            || message.contains("error")
+            || text.contains("corrupt")
+            || text.contains("shutting down filesystem"))
+        {
+            return None;
+        }
+        return Some(KernelEvent {
+            device: Some(cap[2].to_string()),
+            details: Some(json!({
+                "filesystem": &cap[1],
+                "remounted_read_only": remounted_read_only,
+            })),
+            ..event(EVENT_FS_ERROR)
+        });
+    }
+
+    None
+}
+
+/// Parse a log file for kernel events (only lines written by the kernel are considered)
+pub fn collect_kernel_events<P: AsRef<Path>>(
+    log_path: P,
+    max_lines: usize,
+) -> Result<Vec<KernelEvent>> {
+    let file = File::open(log_path.as_ref()).context("Failed to open kernel log")?;
+    let reader = BufReader::new(file);
+    let fallback_timestamp = Utc::now();
+
+    let mut events = Vec::new();
+    for line in reader.lines().take(max_lines) {
+        let line = line?;
+        if !line.contains("kernel: ") {
+            continue;
+        }
+        if let Some(event) = parse_kernel_line(&line, fallback_timestamp) {
+            events.push(event);
+        }
+    }
+
+    Ok(events)
+}
+
+/// Read kernel events from the systemd journal (`journalctl -k`)
+pub fn collect_journal_kernel_events(max_lines: usize) -> Result<Vec<KernelEvent>> {
+    let output = Command::new("journalctl")
+        .args(["-k", "--no-pager", "-o", "short-iso", "-n", &max_lines.to_string()])
+        .output()
+        .context("Failed to run journalctl -k")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("journalctl -k failed (may need sudo or the adm group)"));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fallback_timestamp = Utc::now();
+    Ok(stdout
+        .lines()
+        .filter_map(|line| parse_kernel_line(line, fallback_timestamp))
+        .collect())
+}
+
+/// Read kernel events from the kernel ring buffer (`dmesg`)
+/// Message times are converted from seconds-since-boot using the boot time
+pub fn collect_dmesg_kernel_events(boot_time: DateTime<Utc>) -> Result<Vec<KernelEvent>> {
+    let output = Command::new("dmesg")
+        .output()
+        .context("Failed to run dmesg")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("dmesg failed (may need sudo)"));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let patterns = get_kernel_patterns();
+    let mut events = Vec::new();
+    for line in stdout.lines() {
+        let timestamp = patterns
+            .monotonic_prefix
+            .captures(line)
+            .and_then(|cap| cap[1].parse::<i64>().ok())
+            .map(|seconds| boot_time + Duration::seconds(seconds))
+            .unwrap_or_else(Utc::now);
+        if let Some(event) = parse_kernel_line(line, timestamp) {
+            events.push(event);
+        }
+    }
+
+    Ok(events)
+}
+
+/// Random id the kernel assigns to each boot
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Boot of the running kernel as an event
+/// Every run records the same boot; it is stored once per `boot_id`, since the
+/// boot time derived from /proc/stat moves whenever NTP steps the clock
+pub fn boot_event() -> KernelEvent {
+    let boot_time = sysinfo::System::boot_time();
+    let mut details = json!({ "kernel": sysinfo::System::kernel_version() });
+    if let Ok(boot_id) = std::fs::read_to_string(BOOT_ID_PATH) {
+        details["boot_id"] = json!(boot_id.trim());
+    }
+    KernelEvent {
+        timestamp: Utc
+            .timestamp_opt(boot_time as i64, 0)
+            .single()
+            .unwrap_or_else(Utc::now),
+        event_type: EVENT_BOOT.to_string(),
+        process: None,
+        pid: None,
+        device: None,
+        details: Some(details),
+        message: "System booted".to_string(),
+    }
+}
+
+/// Read kernel events when no kernel log file is available:
+/// the systemd journal if present, otherwise the kernel ring buffer
+pub fn collect_system_kernel_events(max_lines: usize) -> Result<Vec<KernelEvent>> {
+    if is_command_available("journalctl") {
+        return collect_journal_kernel_events(max_lines);
+    }
+
+    let boot_time = Utc
+        .timestamp_opt(sysinfo::System::boot_time() as i64, 0)
+        .single()
+        .unwrap_or_else(Utc::now);
+    collect_dmesg_kernel_events(boot_time)
+}
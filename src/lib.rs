@@ -1,8 +1,26 @@
+pub mod alert;
+pub mod anomaly;
+pub mod auth;
 pub mod collector;
+pub mod compare;
 pub mod config;
 pub mod db;
+pub mod export;
+pub mod forecast;
+pub mod html;
+pub mod kernel;
+pub mod maintenance;
+pub mod markdown;
+pub mod merge;
+pub mod migrations;
+pub mod recommendations;
 pub mod reporter;
+pub mod rollup;
 pub mod system_checks;
+pub mod timeline;
+pub mod timezone;
+pub mod tui;
+pub mod watcher;
 
 /// Application version from Cargo.toml
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+#[cfg(not(feature = "encryption"))]
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "encryption")]
+use crate::db::attach_uri;
+#[cfg(not(feature = "encryption"))]
+use crate::db::open_read_only;
+use crate::db::{create_private_file, open_database, ConnectionOptions};
+
+/// Pages copied per backup step; other connections can write between steps
+#[cfg(not(feature = "encryption"))]
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 256;
+/// Pause between backup steps
+#[cfg(not(feature = "encryption"))]
+const BACKUP_STEP_PAUSE: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// Row count, time span and on-disk size of one table
+#[derive(Debug, Clone, Serialize)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: u64,
+    /// Oldest and newest `timestamp` (or `bucket_start`) value, if the table has one
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+    /// Bytes used by the table and its indices
+    pub size_bytes: u64,
+}
+
+/// Build a timestamped backup file name next to the database
+/// e.g. `systers.db.backup-20251105200001.bak`; never returns an existing path
+pub fn backup_path(db_path: &Path, directory: Option<&Path>, label: &str) -> PathBuf {
+    let file_name = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "systers.db".to_string());
+    let stem = format!("{}.{}-{}", file_name, label, Utc::now().format("%Y%m%d%H%M%S"));
+    let directory = directory
+        .map(Path::to_path_buf)
+        .or_else(|| db_path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    let mut path = directory.join(format!("{}.bak", stem));
+    let mut attempt = 1;
+    while path.exists() {
+        path = directory.join(format!("{}-{}.bak", stem, attempt));
+        attempt += 1;
+    }
+    path
+}
+
+/// Copy the database to `destination` with SQLite's online backup API
+/// (`sqlcipher_export` in encrypted builds, so the copy keeps the same key)
+/// The copy is consistent even while other connections keep writing
+pub fn backup_to(conn: &Connection, destination: &Path) -> Result<()> {
+    // Backups hold the same data as the database, so they are owner-only from the start
+    create_private_file(destination)?;
+    copy_database(conn, destination)
+        .context(format!("Failed to back up database to {}", destination.display()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "encryption"))]
+fn copy_database(conn: &Connection, destination: &Path) -> Result<()> {
+    let mut target = Connection::open(destination)
+        .context(format!("Failed to create backup file {}", destination.display()))?;
+    let backup = Backup::new(conn, &mut target)?;
+    backup.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+    Ok(())
+}
+
+/// SQLCipher cannot run the backup API between connections with different keys,
+/// so the copy is exported through an attachment, which inherits the key
+#[cfg(feature = "encryption")]
+fn copy_database(conn: &Connection, destination: &Path) -> Result<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS backup_copy",
+        [destination.to_string_lossy()],
+    )?;
+    let exported = conn.query_row("SELECT sqlcipher_export('backup_copy')", [], |_| Ok(()));
+    conn.execute("DETACH DATABASE backup_copy", [])?;
+    exported?;
+    Ok(())
+}
+
+/// Copy the database at `source` to a new owner-only file at `destination`
+/// and open the copy read-write; the source is only read
+pub fn open_copy(
+    source: &Path,
+    destination: &Path,
+    options: &ConnectionOptions,
+) -> Result<Connection> {
+    create_private_file(destination)?;
+    let mut copy = open_database(destination, options)?;
+    copy_into(&mut copy, source, options).context(format!(
+        "Failed to copy {} to {}",
+        source.display(),
+        destination.display()
+    ))?;
+    Ok(copy)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn copy_into(copy: &mut Connection, source: &Path, options: &ConnectionOptions) -> Result<()> {
+    let source_conn = open_read_only(source, options)?;
+    let backup = Backup::new(&source_conn, copy)?;
+    backup.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+    Ok(())
+}
+
+/// The export runs on the copy's read-write connection: SQLCipher attaches
+/// databases read-only on a read-only connection, so the copy could not be written.
+/// The attached source inherits the copy's key
+#[cfg(feature = "encryption")]
+fn copy_into(copy: &mut Connection, source: &Path, _options: &ConnectionOptions) -> Result<()> {
+    copy.execute("ATTACH DATABASE ?1 AS copy_source", [attach_uri(source)])?;
+    let exported = copy.query_row("SELECT sqlcipher_export('main', 'copy_source')", [], |_| {
+        Ok(())
+    });
+    copy.execute("DETACH DATABASE copy_source", [])?;
+    exported?;
+    Ok(())
+}
+
+/// Size of the database in bytes (page count times page size)
+pub fn database_size(conn: &Connection) -> Result<u64> {
+    let page_count: u64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: u64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    Ok(page_count * page_size)
+}
+
+/// Rebuild the database file and refresh query planner statistics
+/// Returns the size in bytes before and after
+pub fn vacuum(conn: &Connection) -> Result<(u64, u64)> {
+    let before = database_size(conn)?;
+    conn.execute_batch("VACUUM; ANALYZE;")
+        .context("Failed to vacuum database")?;
+    let after = database_size(conn)?;
+    Ok((before, after))
+}
+
+/// Run `PRAGMA integrity_check`
+/// Returns the problems found (empty when the database is intact)
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    if messages.len() == 1 && messages[0] == "ok" {
+        Ok(Vec::new())
+    } else {
+        Ok(messages)
+    }
+}
+
+/// Row count, time span and size of every table in the database
+pub fn table_stats(conn: &Connection) -> Result<Vec<TableStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )?;
+    let tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    let mut stats = Vec::new();
+    for name in tables {
+        let rows: u64 =
+            conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| row.get(0))?;
+
+        let columns = {
+            let mut stmt = conn.prepare(&format!("PRAGMA table_info(\"{}\")", name))?;
+            let columns = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            columns
+        };
+        let time_column = ["timestamp", "bucket_start"]
+            .into_iter()
+            .find(|c| columns.iter().any(|col| col == c));
+
+        // Only integer (Unix seconds) columns count; schema v1 stored text
+        let (oldest, newest) = match time_column {
+            Some(column) => {
+                let (min, max): (Option<i64>, Option<i64>) = conn.query_row(
+                    &format!(
+                        "SELECT MIN({0}), MAX({0}) FROM \"{1}\" WHERE typeof({0}) = 'integer'",
+                        column, name
+                    ),
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                (
+                    min.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+                    max.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+                )
+            }
+            None => (None, None),
+        };
+
+        let size_bytes: Option<u64> = conn.query_row(
+            "SELECT SUM(d.pgsize) FROM dbstat d
+             JOIN sqlite_master m ON m.name = d.name
+             WHERE m.tbl_name = ?1",
+            [&name],
+            |row| row.get(0),
+        )?;
+
+        stats.push(TableStats {
+            name,
+            rows,
+            oldest,
+            newest,
+            size_bytes: size_bytes.unwrap_or(0),
+        });
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,395 @@
+use crate::anomaly::describe_anomaly;
+use crate::db::{CheckStatus, Resolution};
+use crate::kernel::{
+    EVENT_FS_ERROR, EVENT_HARDWARE_ERROR, EVENT_IO_ERROR, EVENT_OOM_KILL, EVENT_SEGFAULT,
+};
+use crate::reporter::{
+    describe_fill_time, describe_kernel_event, format_size, FullReport,
+};
+use crate::rollup::{METRIC_CPU, METRIC_DISK, METRIC_LOAD, METRIC_MEMORY, METRIC_PROCESSES};
+use crate::timezone::DisplayTimezone;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// Lines of a multi-line log event kept in its fenced block
+const MAX_EXCERPT_LINES: usize = 12;
+/// Issues and recommendations listed in the compact summary
+const MAX_SUMMARY_ITEMS: usize = 5;
+
+/// Render a report as GitHub/GitLab flavoured Markdown
+/// Uses headings and tables instead of box-drawing characters, so it
+/// survives being pasted into issues and wiki pages
+pub fn format_markdown_report(report: &FullReport) -> String {
+    let metrics = &report.metrics;
+    let logs = &report.logs;
+    let mut md = String::new();
+
+    md.push_str("# System Analysis Report\n\n");
+    let _ = writeln!(
+        md,
+        "**Period:** {} to {}  ",
+        local_time(metrics.period_start, report.timezone),
+        local_time(metrics.period_end, report.timezone)
+    );
+    if let Some(host) = report.shown_host() {
+        let _ = writeln!(md, "**Host:** {}  ", host);
+    }
+    let _ = writeln!(md, "**Version:** systers v{}", report.version);
+    if metrics.resolution != Resolution::Raw {
+        let _ = writeln!(
+            md,
+            "\n_Statistics read from {} rollups; older raw samples have been cleaned up._",
+            metrics.resolution.as_str()
+        );
+    }
+
+    md.push_str("\n## System Metrics\n\n");
+    md.push_str("| Metric | Average | Min | p50 | p95 | p99 | Peak | Std Dev | Above Threshold |\n");
+    md.push_str("|---|---:|---:|---:|---:|---:|---:|---:|---:|\n");
+    for (label, metric, unit, decimals) in [
+        ("CPU", METRIC_CPU, "%", 1),
+        ("Memory", METRIC_MEMORY, "%", 1),
+        ("Disk", METRIC_DISK, "%", 1),
+        ("Load (1 min)", METRIC_LOAD, "", 2),
+        ("Processes", METRIC_PROCESSES, "", 0),
+    ] {
+        let Some(stats) = metrics.statistics.get(metric) else {
+            continue;
+        };
+        let value = |v: f64| format!("{:.*}{}", decimals, v, unit);
+        let above = stats.above_threshold.as_ref().map_or("-".to_string(), |above| {
+            format!(
+                "{:.0} min ({:.1}%) over {}",
+                above.minutes,
+                above.percent_of_period,
+                value(above.threshold)
+            )
+        });
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+            label,
+            value(stats.avg),
+            value(stats.min),
+            value(stats.p50),
+            value(stats.p95),
+            value(stats.p99),
+            value(stats.max),
+            value(stats.std_dev),
+            above
+        );
+    }
+
+    md.push_str("\n## Log Analysis\n\n");
+    md.push_str("| Level | Entries |\n|---|---:|\n");
+    let _ = writeln!(md, "| Critical | {} |", logs.total_critical);
+    let _ = writeln!(md, "| Error | {} |", logs.total_errors);
+    let _ = writeln!(md, "| Warning | {} |", logs.total_warnings);
+
+    if !logs.recent_errors.is_empty() {
+        md.push_str("\n### Recent Critical/Error Messages\n");
+        for entry in &logs.recent_errors {
+            let _ = writeln!(
+                md,
+                "\n**{}** `{}` {}\n",
+                entry.level,
+                entry.source.replace('`', "'"),
+                local_time(entry.timestamp, report.timezone)
+            );
+            md.push_str(&fenced(&excerpt(&entry.message)));
+        }
+    }
+
+    let latest = report.system_checks.latest();
+    if !latest.is_empty() {
+        let checks = &report.system_checks;
+        md.push_str("\n## System Checks\n\n");
+        let _ = writeln!(
+            md,
+            "{} results: {} ok, {} warning, {} critical, {} error\n",
+            checks.total_checks,
+            checks.checks_ok,
+            checks.checks_warning,
+            checks.checks_critical,
+            checks.checks_error
+        );
+        md.push_str("| Check | Status | Value | Checked | Message |\n|---|---|---:|---|---|\n");
+        for check in latest {
+            let value = match (check.value, &check.unit) {
+                (Some(value), Some(unit)) => format!("{} {}", value, unit),
+                (Some(value), None) => value.to_string(),
+                _ => "-".to_string(),
+            };
+            let _ = writeln!(
+                md,
+                "| {} | {} {} | {} | {} | {} |",
+                cell(&check.check_name),
+                status_icon(check.status),
+                check.status.as_str(),
+                cell(&value),
+                local_time(check.timestamp, report.timezone),
+                cell(&check.message)
+            );
+        }
+    }
+
+    if let Some(ref forecast) = report.disk_forecast {
+        md.push_str("\n## Disk Forecast\n\n");
+        let _ = writeln!(
+            md,
+            "Trend over the last {:.0} days; mounts full within {:.0} days are flagged 🔴\n",
+            forecast.lookback_days, forecast.horizon_days
+        );
+        md.push_str("| Mount | Used | Growth/day (95%) | Full | Confidence |\n|---|---:|---|---|---|\n");
+        for mount in &forecast.mounts {
+            let _ = writeln!(
+                md,
+                "| {}{} ({}) | {:.1}% of {} | {} ({} to {}) | {} | {} (R² {:.2}) |",
+                if mount.flagged { "🔴 " } else { "" },
+                cell(&mount.mount_point),
+                cell(&mount.filesystem),
+                mount.used_percent,
+                format_size(mount.total_bytes as f64),
+                format_size(mount.growth_per_day),
+                format_size(mount.growth_per_day_low),
+                format_size(mount.growth_per_day_high),
+                describe_fill_time(mount, report.timezone),
+                mount.confidence.as_str(),
+                mount.r_squared
+            );
+        }
+    }
+
+    if let Some(ref anomalies) = report.anomalies {
+        md.push_str("\n## Anomalies\n\n");
+        let _ = writeln!(
+            md,
+            "Compared with median ± {:.1} MAD per hour of week ({} baseline slots)\n",
+            anomalies.mad_multiplier, anomalies.baseline_slots
+        );
+        if anomalies.anomalies.is_empty() {
+            md.push_str("✓ All samples within their usual range\n");
+        }
+        for anomaly in &anomalies.anomalies {
+            let _ = writeln!(md, "- {}", describe_anomaly(anomaly, report.timezone));
+        }
+    }
+
+    if let Some(ref collection) = report.collection {
+        md.push_str("\n## Collection Health\n\n");
+        let _ = writeln!(
+            md,
+            "{} runs ({} ok, {} partial, {} failed, {} interrupted); expected every {} minutes, {} missed",
+            collection.total_runs,
+            collection.ok_runs,
+            collection.partial_runs,
+            collection.failed_runs,
+            collection.interrupted_runs,
+            collection.interval_minutes,
+            collection.missed_runs()
+        );
+        if !collection.gaps.is_empty() {
+            md.push_str("\n| Gap From | To | Missed Runs |\n|---|---|---:|\n");
+            for gap in &collection.gaps {
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {} |",
+                    local_time(gap.from, report.timezone),
+                    local_time(gap.to, report.timezone),
+                    gap.missed_runs
+                );
+            }
+        }
+    }
+
+    if let Some(ref kernel) = report.kernel {
+        md.push_str("\n## Kernel Events\n\n");
+        md.push_str("| Event | Count |\n|---|---:|\n");
+        for (event_type, label) in [
+            (EVENT_OOM_KILL, "OOM kills"),
+            (EVENT_SEGFAULT, "Segfaults"),
+            (EVENT_HARDWARE_ERROR, "Hardware errors"),
+            (EVENT_IO_ERROR, "I/O errors"),
+            (EVENT_FS_ERROR, "Filesystem errors"),
+        ] {
+            let _ = writeln!(md, "| {} | {} |", label, kernel.count(event_type));
+        }
+        md.push_str("\n| Time | Type | Event |\n|---|---|---|\n");
+        for event in &kernel.recent_events {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} |",
+                local_time(event.timestamp, report.timezone),
+                cell(&event.event_type),
+                cell(&describe_kernel_event(event))
+            );
+        }
+    }
+
+    if let Some(ref security) = report.security {
+        md.push_str("\n## Security (SSH & sudo)\n\n");
+        let _ = writeln!(
+            md,
+            "{} failed logins ({} invalid user), {} accepted logins, {} sudo commands ({} rejected)",
+            security.failed_logins,
+            security.invalid_user_attempts,
+            security.accepted_logins,
+            security.sudo_commands,
+            security.sudo_failures
+        );
+        if !security.top_attacking_ips.is_empty() {
+            md.push_str("\n| Source IP | Failed Logins | Invalid Users |\n|---|---:|---:|\n");
+            for ip in &security.top_attacking_ips {
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {} |",
+                    cell(&ip.source_ip),
+                    ip.failed_logins,
+                    ip.invalid_users
+                );
+            }
+        }
+    }
+
+    if !metrics.issues.is_empty() {
+        md.push_str("\n## Issues Detected\n\n");
+        for issue in &metrics.issues {
+            let _ = writeln!(md, "- {}", issue.message);
+        }
+    }
+
+    md.push_str("\n## Recommendations\n\n");
+    if report.recommendations.is_empty() {
+        md.push_str("✓ System appears healthy - no immediate action required\n");
+    } else {
+        for rec in &report.recommendations {
+            let _ = writeln!(
+                md,
+                "- {} **{}** (`{}`): {}",
+                rec.severity.icon(),
+                rec.finding,
+                rec.id,
+                rec.action
+            );
+            for command in &rec.commands {
+                let _ = writeln!(md, "  - `{}`", command);
+            }
+        }
+    }
+
+    md
+}
+
+/// Short Markdown summary that fits in a chat message
+pub fn format_markdown_summary(report: &FullReport) -> String {
+    let metrics = &report.metrics;
+    let logs = &report.logs;
+    let checks = &report.system_checks;
+    let mut md = String::new();
+
+    let _ = writeln!(
+        md,
+        "**System report** {} to {}",
+        local_time(metrics.period_start, report.timezone),
+        local_time(metrics.period_end, report.timezone)
+    );
+    let _ = writeln!(
+        md,
+        "CPU {:.1}% avg / {:.1}% peak · Memory {:.1}% / {:.1}% · Disk {:.1}% / {:.1}% · Load peak {:.2}",
+        metrics.avg_cpu_usage,
+        metrics.max_cpu_usage,
+        metrics.avg_memory_used_percent,
+        metrics.max_memory_used_percent,
+        metrics.avg_disk_used_percent,
+        metrics.max_disk_used_percent,
+        metrics.max_load_avg_1min
+    );
+    let _ = writeln!(
+        md,
+        "Logs: {} critical, {} errors, {} warnings",
+        logs.total_critical, logs.total_errors, logs.total_warnings
+    );
+    if checks.total_checks > 0 {
+        let problems: Vec<String> = checks
+            .latest()
+            .into_iter()
+            .filter(|c| c.status.is_problem())
+            .map(|c| format!("{} {}", status_icon(c.status), c.check_name))
+            .collect();
+        let _ = writeln!(
+            md,
+            "Checks: {}",
+            if problems.is_empty() {
+                "all ok".to_string()
+            } else {
+                problems.join(", ")
+            }
+        );
+    }
+
+    let issues: Vec<String> = metrics
+        .issues
+        .iter()
+        .map(|issue| issue.message.clone())
+        .chain(report.recommendations.iter().map(|rec| format!("{} - {}", rec.finding, rec.action)))
+        .collect();
+    if issues.is_empty() {
+        md.push_str("✓ No issues detected\n");
+    } else {
+        for issue in issues.iter().take(MAX_SUMMARY_ITEMS) {
+            let _ = writeln!(md, "- {}", issue);
+        }
+        if issues.len() > MAX_SUMMARY_ITEMS {
+            let _ = writeln!(md, "- ... and {} more", issues.len() - MAX_SUMMARY_ITEMS);
+        }
+    }
+
+    md
+}
+
+fn local_time(time: DateTime<Utc>, timezone: DisplayTimezone) -> String {
+    let local = timezone.convert(time);
+    local.format("%Y-%m-%d %H:%M:%S %Z").to_string()
+}
+
+fn status_icon(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Ok => "✅",
+        CheckStatus::Warning => "⚠️",
+        CheckStatus::Critical => "🔴",
+        CheckStatus::Error => "❌",
+        CheckStatus::Unknown => "❔",
+    }
+}
+
+/// Text safe to put in one table cell
+fn cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// First lines of a log message, noting how many were left out
+fn excerpt(message: &str) -> String {
+    let lines: Vec<&str> = message.lines().collect();
+    if lines.len() <= MAX_EXCERPT_LINES {
+        return message.to_string();
+    }
+    format!(
+        "{}\n... {} more lines",
+        lines[..MAX_EXCERPT_LINES].join("\n"),
+        lines.len() - MAX_EXCERPT_LINES
+    )
+}
+
+/// Fenced code block whose fence is longer than any backtick run inside
+fn fenced(text: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{}text\n{}\n{}\n", fence, text.trim_end(), fence)
+}
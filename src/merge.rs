@@ -0,0 +1,297 @@
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::collector::hostname;
+use crate::db::{attach_uri, open_read_only, Batch, ConnectionOptions, SCHEMA_VERSION};
+use crate::maintenance::open_copy;
+use crate::migrations::{get_schema_version, migrate_to};
+
+/// Schema name the source database is attached under
+const SOURCE: &str = "merge_source";
+
+/// Host a source row is stored under: untagged rows get the source's host,
+/// and rows that came from this machine stay local (empty host)
+const HOST_EXPR: &str = "CASE WHEN s.host = '' THEN ?1 WHEN s.host = ?2 THEN '' ELSE s.host END";
+
+/// Tables merged row by row and the columns that identify a duplicate
+/// (together with the host); rollups are rebuilt locally and not merged
+const MERGE_TABLES: [(&str, &[&str]); 6] = [
+    ("system_metrics", &["timestamp"]),
+    ("log_entries", &["timestamp", "level", "source", "message"]),
+    (
+        "system_checks",
+        &["timestamp", "check_name", "check_type", "status", "message"],
+    ),
+    ("auth_events", &["timestamp", "event_type", "message"]),
+    ("kernel_events", &["timestamp", "event_type", "message"]),
+    ("disk_usage", &["timestamp", "mount_point"]),
+];
+
+/// Rows copied from one table of a source database
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedTable {
+    pub name: String,
+    /// Rows added to this database
+    pub merged: usize,
+    /// Rows skipped because they were already present
+    pub duplicates: usize,
+}
+
+/// What was imported from one source database
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeReport {
+    pub source: PathBuf,
+    /// Host the source's rows were tagged with (empty when they are from this host)
+    pub host: String,
+    pub source_version: i32,
+    /// Whether a temporary copy of the source had to be migrated first
+    pub migrated: bool,
+    pub tables: Vec<MergedTable>,
+}
+
+impl MergeReport {
+    /// Total rows added across all tables
+    pub fn merged(&self) -> usize {
+        self.tables.iter().map(|t| t.merged).sum()
+    }
+
+    /// Total rows skipped as duplicates across all tables
+    pub fn duplicates(&self) -> usize {
+        self.tables.iter().map(|t| t.duplicates).sum()
+    }
+}
+
+/// Import the rows of another systers database into `conn`
+///
+/// Rows are tagged with the source host (`host`, or the hostname recorded in
+/// the source's collection runs) and rows already present are skipped, so
+/// merging the same file twice adds nothing. Sources at an older schema are
+/// migrated in a temporary copy; the source file itself is never modified.
+/// Encrypted sources must use the same key as this database.
+pub fn merge_database(
+    conn: &Connection,
+    source: &Path,
+    host: Option<&str>,
+    options: &ConnectionOptions,
+) -> Result<MergeReport> {
+    let target_version = get_schema_version(conn)?;
+    if target_version != SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Database is at schema v{} but merging needs v{}; run 'systers db migrate' first",
+            target_version,
+            SCHEMA_VERSION
+        ));
+    }
+
+    if !source.is_file() {
+        return Err(anyhow!("Source database not found at {}", source.display()));
+    }
+    if let Some(target) = conn.path() {
+        if Path::new(target).canonicalize().ok() == source.canonicalize().ok() {
+            return Err(anyhow!("Cannot merge {} into itself", source.display()));
+        }
+    }
+
+    let source_conn = open_read_only(source, options)?;
+    let source_version = get_schema_version(&source_conn)?;
+    if source_version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "{} is at schema v{}, newer than this build (v{}); upgrade systers first",
+            source.display(),
+            source_version,
+            SCHEMA_VERSION
+        ));
+    }
+    if source_version == 0 && !has_table(&source_conn, "system_metrics")? {
+        return Err(anyhow!("{} is not a systers database", source.display()));
+    }
+
+    // Older sources are brought up to date in a scratch copy
+    let scratch = tempfile::Builder::new()
+        .prefix("systers-merge-")
+        .tempdir()
+        .context("Failed to create temporary directory")?;
+    let migrated = source_version < SCHEMA_VERSION;
+    let attach_path = if migrated {
+        let copy = scratch.path().join("source.db");
+        let copy_conn = open_copy(source, &copy, options)?;
+        migrate_to(&copy_conn, SCHEMA_VERSION, None)
+            .context(format!("Failed to migrate a copy of {}", source.display()))?;
+        copy
+    } else {
+        source.to_path_buf()
+    };
+
+    let host = match host {
+        Some(host) => host.to_string(),
+        None => recorded_hostname(&source_conn, source_version)?.ok_or_else(|| {
+            anyhow!(
+                "{} has no collection runs to tell which host it came from; pass --host",
+                source.display()
+            )
+        })?,
+    };
+    drop(source_conn);
+
+    // A copy of this host's own database merges as local rows
+    let local_host = hostname();
+    let tag = if host == local_host { String::new() } else { host.clone() };
+
+    conn.execute(
+        &format!("ATTACH DATABASE ?1 AS {}", SOURCE),
+        params![attach_uri(&attach_path)],
+    )
+    .context(format!("Failed to attach {}", attach_path.display()))?;
+    let result = merge_attached(conn, &tag, &local_host);
+    conn.execute(&format!("DETACH DATABASE {}", SOURCE), [])?;
+
+    Ok(MergeReport {
+        source: source.to_path_buf(),
+        host: tag,
+        source_version,
+        migrated,
+        tables: result?,
+    })
+}
+
+/// Copy every table from the attached source in one transaction
+fn merge_attached(conn: &Connection, tag: &str, local_host: &str) -> Result<Vec<MergedTable>> {
+    let batch = Batch::begin(conn)?;
+    let tx = batch.connection();
+
+    // Source run ids are remapped to the ids the runs get (or already have) here
+    tx.execute(
+        "CREATE TEMP TABLE IF NOT EXISTS merge_run_map (
+            source_id INTEGER PRIMARY KEY,
+            run_id INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    tx.execute("DELETE FROM temp.merge_run_map", [])?;
+
+    let mut tables = vec![merge_table(
+        tx,
+        "collection_runs",
+        &["hostname", "mode", "started_at"],
+        tag,
+        local_host,
+    )?];
+    tx.execute(
+        &format!(
+            "INSERT OR IGNORE INTO temp.merge_run_map (source_id, run_id)
+             SELECT s.id, r.id
+             FROM {source}.collection_runs s
+             JOIN main.collection_runs r
+               ON r.hostname = s.hostname AND r.mode = s.mode
+              AND r.started_at = s.started_at AND r.host = {host}",
+            source = SOURCE,
+            host = HOST_EXPR
+        ),
+        params![tag, local_host],
+    )?;
+
+    for (table, key) in MERGE_TABLES {
+        tables.push(merge_table(tx, table, key, tag, local_host)?);
+    }
+
+    tx.execute("DROP TABLE temp.merge_run_map", [])?;
+    batch.commit()?;
+    Ok(tables)
+}
+
+/// Insert the source rows of one table that are not already present
+fn merge_table(
+    conn: &Connection,
+    table: &str,
+    key: &[&str],
+    tag: &str,
+    local_host: &str,
+) -> Result<MergedTable> {
+    let columns: Vec<String> = {
+        let mut stmt = conn.prepare(&format!("PRAGMA main.table_info({})", table))?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        names
+            .into_iter()
+            .filter(|c| !matches!(c.as_str(), "id" | "run_id" | "host"))
+            .collect()
+    };
+    let run_linked = table != "collection_runs";
+
+    let mut insert_columns = columns.clone();
+    let mut select_columns: Vec<String> = columns.iter().map(|c| format!("s.{}", c)).collect();
+    insert_columns.push("host".to_string());
+    select_columns.push(HOST_EXPR.to_string());
+    if run_linked {
+        insert_columns.push("run_id".to_string());
+        select_columns.push(
+            "(SELECT m.run_id FROM temp.merge_run_map m WHERE m.source_id = s.run_id)".to_string(),
+        );
+    }
+
+    let duplicate = key
+        .iter()
+        .map(|c| format!("t.{0} = s.{0}", c))
+        .chain(std::iter::once(format!("t.host = {}", HOST_EXPR)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let merged = conn
+        .execute(
+            &format!(
+                "INSERT INTO main.{table} ({insert})
+                 SELECT {select}
+                 FROM {source}.{table} s
+                 WHERE NOT EXISTS (SELECT 1 FROM main.{table} t WHERE {duplicate})",
+                table = table,
+                insert = insert_columns.join(", "),
+                select = select_columns.join(", "),
+                source = SOURCE,
+                duplicate = duplicate
+            ),
+            params![tag, local_host],
+        )
+        .context(format!("Failed to merge {}", table))?;
+
+    let source_rows: usize = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {}.{}", SOURCE, table),
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(MergedTable {
+        name: table.to_string(),
+        merged,
+        duplicates: source_rows.saturating_sub(merged),
+    })
+}
+
+/// Hostname of the newest run the source recorded itself
+fn recorded_hostname(conn: &Connection, version: i32) -> Result<Option<String>> {
+    // The run ledger arrived in v8 and the host column in v9
+    let query = match version {
+        v if v >= 9 => {
+            "SELECT hostname FROM collection_runs WHERE host = ''
+             ORDER BY started_at DESC LIMIT 1"
+        }
+        8 => "SELECT hostname FROM collection_runs ORDER BY started_at DESC LIMIT 1",
+        _ => return Ok(None),
+    };
+    match conn.query_row(query, [], |row| row.get(0)) {
+        Ok(hostname) => Ok(Some(hostname)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn has_table(conn: &Connection, table: &str) -> Result<bool> {
+    let exists = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        params![table],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
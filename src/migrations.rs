@@ -0,0 +1,1004 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+use crate::maintenance::{backup_path, backup_to};
+
+/// A single schema change with transactional up and down steps
+pub struct Migration {
+    /// Schema version reached after applying this migration
+    pub version: i32,
+    pub description: &'static str,
+    up: fn(&Connection) -> Result<()>,
+    down: fn(&Connection) -> Result<()>,
+}
+
+/// All migrations in the order they are applied
+/// The last entry's version must equal `db::SCHEMA_VERSION`
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial schema (system_metrics, log_entries)",
+        up: create_v1_schema,
+        down: drop_v1_schema,
+    },
+    Migration {
+        version: 2,
+        description: "Store timestamps as INTEGER Unix seconds",
+        up: migrate_v1_to_v2,
+        down: migrate_v2_to_v1,
+    },
+    Migration {
+        version: 3,
+        description: "Add system_checks table",
+        up: migrate_v2_to_v3,
+        down: migrate_v3_to_v2,
+    },
+    Migration {
+        version: 4,
+        description: "Add attributes column to log_entries",
+        up: migrate_v3_to_v4,
+        down: migrate_v4_to_v3,
+    },
+    Migration {
+        version: 5,
+        description: "Add auth_events table",
+        up: migrate_v4_to_v5,
+        down: migrate_v5_to_v4,
+    },
+    Migration {
+        version: 6,
+        description: "Add kernel_events table",
+        up: migrate_v5_to_v6,
+        down: migrate_v6_to_v5,
+    },
+    Migration {
+        version: 7,
+        description: "Add metric_rollups, log_rollups and rollup_state tables",
+        up: migrate_v6_to_v7,
+        down: migrate_v7_to_v6,
+    },
+    Migration {
+        version: 8,
+        description: "Add collection_runs ledger and run_id columns",
+        up: migrate_v7_to_v8,
+        down: migrate_v8_to_v7,
+    },
+    Migration {
+        version: 9,
+        description: "Add host column for rows merged from other hosts",
+        up: migrate_v8_to_v9,
+        down: migrate_v9_to_v8,
+    },
+    Migration {
+        version: 10,
+        description: "Typed check results (status values, numeric value, unit, details)",
+        up: migrate_v9_to_v10,
+        down: migrate_v10_to_v9,
+    },
+    Migration {
+        version: 11,
+        description: "Add disk_usage table (per-mount used and total bytes)",
+        up: migrate_v10_to_v11,
+        down: migrate_v11_to_v10,
+    },
+    Migration {
+        version: 12,
+        description: "Add metric_baselines table (median and MAD per hour of week)",
+        up: migrate_v11_to_v12,
+        down: migrate_v12_to_v11,
+    },
+];
+
+/// Whether a migration has been applied to a database
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i32,
+    pub description: &'static str,
+    pub applied: bool,
+}
+
+/// Migrations known to this build and whether each is applied
+pub fn migration_status(conn: &Connection) -> Result<Vec<MigrationStatus>> {
+    let current = get_schema_version(conn)?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description,
+            applied: m.version <= current,
+        })
+        .collect())
+}
+
+/// Migrations that would be applied to reach `target`, in order
+pub fn pending_migrations(conn: &Connection, target: i32) -> Result<Vec<&'static Migration>> {
+    let current = get_schema_version(conn)?;
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current && m.version <= target)
+        .collect())
+}
+
+/// Record the schema version (a single row is kept)
+fn set_schema_version(conn: &Connection, version: i32) -> Result<()> {
+    conn.execute("DELETE FROM schema_version", [])?;
+    if version > 0 {
+        conn.execute(
+            "INSERT INTO schema_version (version, app_version) VALUES (?1, ?2)",
+            params![version, crate::VERSION],
+        )?;
+    }
+    Ok(())
+}
+
+fn ensure_schema_version_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            app_version TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Copy the database file next to itself before changing its schema
+/// Returns the backup path
+pub fn backup_database(conn: &Connection, db_path: &Path, version: i32) -> Result<PathBuf> {
+    let path = backup_path(db_path, None, &format!("v{}", version));
+    backup_to(conn, &path)?;
+    Ok(path)
+}
+
+/// Apply pending migrations up to `target`, each in its own transaction
+/// An existing database is backed up first when `backup_path` is given
+/// Returns the migrations that were applied
+pub fn migrate_to(
+    conn: &Connection,
+    target: i32,
+    backup_path: Option<&Path>,
+) -> Result<Vec<&'static Migration>> {
+    ensure_schema_version_table(conn)?;
+    let current = get_schema_version(conn)?;
+
+    if current > target {
+        warn!(
+            "Database schema version ({}) is newer than application version ({})",
+            current, target
+        );
+        return Ok(Vec::new());
+    }
+
+    let pending = pending_migrations(conn, target)?;
+    if pending.is_empty() {
+        return Ok(pending);
+    }
+
+    if current == 0 {
+        info!("Creating fresh database with schema v{}", target);
+    } else if let Some(path) = backup_path {
+        let backup = backup_database(conn, path, current)?;
+        info!("Backed up schema v{} database to {}", current, backup.display());
+    }
+
+    for migration in &pending {
+        if current > 0 {
+            info!(
+                "Applying migration {}: {}",
+                migration.version, migration.description
+            );
+        }
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx)
+            .context(format!("Migration {} failed", migration.version))?;
+        set_schema_version(&tx, migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(pending)
+}
+
+/// Undo applied migrations down to schema version `target`, newest first
+/// The database is backed up first when `backup_path` is given
+/// Returns the migrations that were rolled back
+pub fn rollback_to(
+    conn: &Connection,
+    target: i32,
+    backup_path: Option<&Path>,
+) -> Result<Vec<&'static Migration>> {
+    ensure_schema_version_table(conn)?;
+    let current = get_schema_version(conn)?;
+
+    if current > MIGRATIONS.last().map_or(0, |m| m.version) {
+        return Err(anyhow!(
+            "Database schema v{} is newer than this build knows how to roll back",
+            current
+        ));
+    }
+
+    let to_undo: Vec<&'static Migration> = MIGRATIONS
+        .iter()
+        .rev()
+        .filter(|m| m.version <= current && m.version > target)
+        .collect();
+    if to_undo.is_empty() {
+        return Ok(to_undo);
+    }
+
+    if let Some(path) = backup_path {
+        let backup = backup_database(conn, path, current)?;
+        info!("Backed up schema v{} database to {}", current, backup.display());
+    }
+
+    for migration in &to_undo {
+        info!(
+            "Rolling back migration {}: {}",
+            migration.version, migration.description
+        );
+        let tx = conn.unchecked_transaction()?;
+        (migration.down)(&tx)
+            .context(format!("Rollback of migration {} failed", migration.version))?;
+        set_schema_version(&tx, migration.version - 1)?;
+        tx.commit()?;
+    }
+
+    Ok(to_undo)
+}
+
+/// Create the original schema with TEXT (RFC 3339) timestamps
+fn create_v1_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS system_metrics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            cpu_usage REAL NOT NULL,
+            memory_total INTEGER NOT NULL,
+            memory_used INTEGER NOT NULL,
+            memory_available INTEGER NOT NULL,
+            disk_total INTEGER NOT NULL,
+            disk_used INTEGER NOT NULL,
+            process_count INTEGER NOT NULL,
+            load_avg_1min REAL NOT NULL,
+            load_avg_5min REAL NOT NULL,
+            load_avg_15min REAL NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS log_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            level TEXT NOT NULL,
+            source TEXT NOT NULL,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn drop_v1_schema(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS system_metrics", [])?;
+    conn.execute("DROP TABLE IF EXISTS log_entries", [])?;
+    Ok(())
+}
+
+/// Get current schema version from database
+pub fn get_schema_version(conn: &Connection) -> Result<i32> {
+    // Check if schema_version table exists
+    let table_exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='schema_version'",
+            [],
+            |row| {
+                let count: i32 = row.get(0)?;
+                Ok(count > 0)
+            },
+        )?;
+
+    if !table_exists {
+        return Ok(0); // Fresh database
+    }
+
+    // Try to get version (one row is kept per version, so take the newest)
+    match conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+        row.get::<_, Option<i32>>(0)
+    }) {
+        Ok(version) => Ok(version.unwrap_or(0)),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Migrate from schema v1 to v2 (TEXT timestamps to INTEGER timestamps)
+fn migrate_v1_to_v2(conn: &Connection) -> Result<()> {
+    // Create new tables with INTEGER timestamps
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS system_metrics_v2 (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            cpu_usage REAL NOT NULL,
+            memory_total INTEGER NOT NULL,
+            memory_used INTEGER NOT NULL,
+            memory_available INTEGER NOT NULL,
+            disk_total INTEGER NOT NULL,
+            disk_used INTEGER NOT NULL,
+            process_count INTEGER NOT NULL,
+            load_avg_1min REAL NOT NULL,
+            load_avg_5min REAL NOT NULL,
+            load_avg_15min REAL NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS log_entries_v2 (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            level TEXT NOT NULL,
+            source TEXT NOT NULL,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Migrate system_metrics data
+    // SQLite can't parse RFC3339 directly, so we need to convert via Rust
+    let mut stmt = conn.prepare("SELECT * FROM system_metrics")?;
+    let metrics_iter = stmt.query_map([], |row| {
+        let timestamp_str: String = row.get(1)?;
+        Ok((
+            row.get::<_, i32>(0)?, // id
+            timestamp_str,
+            row.get::<_, f32>(2)?,  // cpu_usage
+            row.get::<_, i64>(3)?,  // memory_total
+            row.get::<_, i64>(4)?,  // memory_used
+            row.get::<_, i64>(5)?,  // memory_available
+            row.get::<_, i64>(6)?,  // disk_total
+            row.get::<_, i64>(7)?,  // disk_used
+            row.get::<_, i32>(8)?,  // process_count
+            row.get::<_, f64>(9)?,  // load_avg_1min
+            row.get::<_, f64>(10)?, // load_avg_5min
+            row.get::<_, f64>(11)?, // load_avg_15min
+        ))
+    })?;
+
+    for row in metrics_iter {
+        let (id, ts_str, cpu, mem_tot, mem_used, mem_avail, disk_tot, disk_used, proc_cnt, load1, load5, load15) = row?;
+
+        // Parse RFC3339 timestamp and convert to Unix timestamp
+        let timestamp = if let Ok(dt) = DateTime::parse_from_rfc3339(&ts_str) {
+            dt.timestamp()
+        } else {
+            warn!("Could not parse timestamp '{}', using current time", ts_str);
+            Utc::now().timestamp()
+        };
+
+        conn.execute(
+            "INSERT INTO system_metrics_v2 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![id, timestamp, cpu, mem_tot, mem_used, mem_avail, disk_tot, disk_used, proc_cnt, load1, load5, load15],
+        )?;
+    }
+
+    // Migrate log_entries data
+    let mut stmt = conn.prepare("SELECT * FROM log_entries")?;
+    let logs_iter = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i32>(0)?,     // id
+            row.get::<_, String>(1)?,  // timestamp
+            row.get::<_, String>(2)?,  // level
+            row.get::<_, String>(3)?,  // source
+            row.get::<_, String>(4)?,  // message
+        ))
+    })?;
+
+    for row in logs_iter {
+        let (id, ts_str, level, source, message) = row?;
+
+        // Parse RFC3339 timestamp and convert to Unix timestamp
+        let timestamp = if let Ok(dt) = DateTime::parse_from_rfc3339(&ts_str) {
+            dt.timestamp()
+        } else {
+            warn!("Could not parse log timestamp '{}', using current time", ts_str);
+            Utc::now().timestamp()
+        };
+
+        conn.execute(
+            "INSERT INTO log_entries_v2 VALUES (?, ?, ?, ?, ?)",
+            params![id, timestamp, level, source, message],
+        )?;
+    }
+
+    // Drop old tables
+    conn.execute("DROP TABLE system_metrics", [])?;
+    conn.execute("DROP TABLE log_entries", [])?;
+
+    // Rename new tables
+    conn.execute("ALTER TABLE system_metrics_v2 RENAME TO system_metrics", [])?;
+    conn.execute("ALTER TABLE log_entries_v2 RENAME TO log_entries", [])?;
+
+    // Create indices for better query performance
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_metrics_timestamp
+         ON system_metrics(timestamp)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_logs_timestamp
+         ON log_entries(timestamp)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_logs_level
+         ON log_entries(level)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Migrate from schema v2 to v3 (add system_checks table)
+fn migrate_v2_to_v3(conn: &Connection) -> Result<()> {
+    // Create system_checks table (new in v3)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS system_checks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            check_name TEXT NOT NULL,
+            check_type TEXT NOT NULL,
+            status TEXT NOT NULL,
+            value TEXT,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Create index for better query performance
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_checks_timestamp
+         ON system_checks(timestamp)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_checks_type
+         ON system_checks(check_type)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Migrate from schema v3 to v4 (add attributes column to log_entries)
+fn migrate_v3_to_v4(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE log_entries ADD COLUMN attributes TEXT", [])?;
+
+    Ok(())
+}
+
+/// Create the auth_events table and its indices
+/// The unique index lets repeated scans of the same auth.log skip known events
+fn create_auth_events_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS auth_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            username TEXT,
+            source_ip TEXT,
+            method TEXT,
+            command TEXT,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_auth_timestamp
+         ON auth_events(timestamp)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_auth_unique
+         ON auth_events(timestamp, event_type, message)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Migrate from schema v4 to v5 (add auth_events table)
+fn migrate_v4_to_v5(conn: &Connection) -> Result<()> {
+    create_auth_events_schema(conn)?;
+
+    Ok(())
+}
+
+/// Create the kernel_events table and its indices
+/// The same kernel line often appears in several logs (kern.log and syslog), so
+/// duplicates are skipped through a unique index
+fn create_kernel_events_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS kernel_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            process TEXT,
+            pid INTEGER,
+            device TEXT,
+            details TEXT,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_kernel_timestamp
+         ON kernel_events(timestamp)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_kernel_unique
+         ON kernel_events(timestamp, event_type, message)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Migrate from schema v5 to v6 (add kernel_events table)
+fn migrate_v5_to_v6(conn: &Connection) -> Result<()> {
+    create_kernel_events_schema(conn)?;
+
+    Ok(())
+}
+
+/// Create the rollup tables used for long-term retention
+/// rollup_state records how far each resolution has been built
+fn create_rollup_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS metric_rollups (
+            resolution TEXT NOT NULL,
+            bucket_start INTEGER NOT NULL,
+            metric TEXT NOT NULL,
+            samples INTEGER NOT NULL,
+            avg REAL NOT NULL,
+            min REAL NOT NULL,
+            max REAL NOT NULL,
+            p95 REAL NOT NULL,
+            PRIMARY KEY (resolution, bucket_start, metric)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS log_rollups (
+            resolution TEXT NOT NULL,
+            bucket_start INTEGER NOT NULL,
+            level TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY (resolution, bucket_start, level)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rollup_state (
+            resolution TEXT PRIMARY KEY,
+            built_until INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Migrate from schema v6 to v7 (add rollup tables)
+fn migrate_v6_to_v7(conn: &Connection) -> Result<()> {
+    create_rollup_schema(conn)?;
+
+    Ok(())
+}
+
+/// Tables whose rows are linked to the collection run that stored them
+const RUN_LINKED_TABLES: [&str; 5] = [
+    "system_metrics",
+    "log_entries",
+    "system_checks",
+    "auth_events",
+    "kernel_events",
+];
+
+/// Create the collection run ledger
+/// `collectors` holds a JSON array with the outcome of each collector
+fn create_collection_runs_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS collection_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            mode TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            finished_at INTEGER,
+            app_version TEXT NOT NULL,
+            hostname TEXT NOT NULL,
+            status TEXT NOT NULL,
+            collectors TEXT,
+            rows_written INTEGER NOT NULL DEFAULT 0,
+            error TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_runs_started_at
+         ON collection_runs(started_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let exists = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        params![table],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Migrate from schema v7 to v8 (add collection_runs and run_id columns)
+fn migrate_v7_to_v8(conn: &Connection) -> Result<()> {
+    create_collection_runs_schema(conn)?;
+    for table in RUN_LINKED_TABLES {
+        if table_exists(conn, table)? {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN run_id INTEGER", table), [])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tables whose rows record the host they were merged from
+const HOST_TAGGED_TABLES: [&str; 6] = [
+    "system_metrics",
+    "log_entries",
+    "system_checks",
+    "auth_events",
+    "kernel_events",
+    "collection_runs",
+];
+
+/// Migrate from schema v8 to v9 (tag rows with the host they came from)
+/// Local rows keep an empty host, so the auth and kernel unique indices still
+/// skip repeated events (NULLs would never compare equal)
+fn migrate_v8_to_v9(conn: &Connection) -> Result<()> {
+    for table in HOST_TAGGED_TABLES {
+        if table_exists(conn, table)? {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN host TEXT NOT NULL DEFAULT ''", table),
+                [],
+            )?;
+        }
+    }
+
+    if table_exists(conn, "auth_events")? {
+        conn.execute("DROP INDEX IF EXISTS idx_auth_unique", [])?;
+        conn.execute(
+            "CREATE UNIQUE INDEX idx_auth_unique
+             ON auth_events(timestamp, event_type, message, host)",
+            [],
+        )?;
+    }
+    if table_exists(conn, "kernel_events")? {
+        conn.execute("DROP INDEX IF EXISTS idx_kernel_unique", [])?;
+        conn.execute(
+            "CREATE UNIQUE INDEX idx_kernel_unique
+             ON kernel_events(timestamp, event_type, message, host)",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Unit recorded for the numeric value of each built-in check type
+const CHECK_UNITS: [(&str, &str); 4] = [
+    ("package_manager", "updates"),
+    ("systemd", "failed services"),
+    ("disk_health", "failed disks"),
+    ("disk_usage", "directories"),
+];
+
+/// Move system_checks aside as system_checks_old and create it again, with a
+/// REAL value plus unit and details when `typed` (v10) or a TEXT value (v9)
+/// SQLite cannot change a column's type in place
+fn rebuild_system_checks(conn: &Connection, typed: bool) -> Result<()> {
+    let (value_type, extra_columns) = if typed {
+        ("REAL", ", unit TEXT, details TEXT")
+    } else {
+        ("TEXT", "")
+    };
+    conn.execute("ALTER TABLE system_checks RENAME TO system_checks_old", [])?;
+    conn.execute("DROP INDEX IF EXISTS idx_checks_timestamp", [])?;
+    conn.execute("DROP INDEX IF EXISTS idx_checks_type", [])?;
+    conn.execute(
+        &format!(
+            "CREATE TABLE system_checks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                check_name TEXT NOT NULL,
+                check_type TEXT NOT NULL,
+                status TEXT NOT NULL,
+                value {},
+                message TEXT NOT NULL,
+                run_id INTEGER,
+                host TEXT NOT NULL DEFAULT ''{}
+            )",
+            value_type, extra_columns
+        ),
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX idx_checks_timestamp ON system_checks(timestamp)",
+        [],
+    )?;
+    conn.execute("CREATE INDEX idx_checks_type ON system_checks(check_type)", [])?;
+    Ok(())
+}
+
+/// Migrate from schema v9 to v10 (typed check results)
+/// Statuses are normalised to ok/warning/critical/error/unknown ("info" becomes ok),
+/// numeric values become REAL with the unit of their check type, and values
+/// that are not numbers are kept in `details`
+fn migrate_v9_to_v10(conn: &Connection) -> Result<()> {
+    if !table_exists(conn, "system_checks")? {
+        return Ok(());
+    }
+
+    rebuild_system_checks(conn, true)?;
+
+    let unit_case = CHECK_UNITS
+        .iter()
+        .map(|(check_type, unit)| format!("WHEN '{}' THEN '{}'", check_type, unit))
+        .collect::<Vec<_>>()
+        .join(" ");
+    conn.execute(
+        &format!(
+            "INSERT INTO system_checks
+                (id, timestamp, check_name, check_type, status, value, unit, details,
+                 message, run_id, host)
+             SELECT id, timestamp, check_name, check_type,
+                    CASE lower(trim(status))
+                        WHEN 'ok' THEN 'ok'
+                        WHEN 'info' THEN 'ok'
+                        WHEN 'warning' THEN 'warning'
+                        WHEN 'warn' THEN 'warning'
+                        WHEN 'critical' THEN 'critical'
+                        WHEN 'error' THEN 'error'
+                        ELSE 'unknown'
+                    END,
+                    CASE WHEN is_number THEN CAST(trim(value) AS REAL) END,
+                    CASE WHEN is_number THEN CASE check_type {units} END END,
+                    CASE WHEN value IS NOT NULL AND NOT is_number
+                         THEN json_object('value', value) END,
+                    message, run_id, host
+             FROM (
+                 SELECT *,
+                        trim(value) <> ''
+                        AND ltrim(trim(value), '-') NOT GLOB '*[^0-9.]*'
+                        AND ltrim(trim(value), '-') GLOB '*[0-9]*' AS is_number
+                 FROM system_checks_old
+             )",
+            units = unit_case
+        ),
+        [],
+    )?;
+    conn.execute("DROP TABLE system_checks_old", [])?;
+
+    Ok(())
+}
+
+/// Migrate from schema v10 to v11 (add disk_usage table)
+/// One row per mount point and run, so disk growth can be forecast per filesystem
+fn migrate_v10_to_v11(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS disk_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            mount_point TEXT NOT NULL,
+            filesystem TEXT NOT NULL,
+            total_bytes INTEGER NOT NULL,
+            used_bytes INTEGER NOT NULL,
+            run_id INTEGER,
+            host TEXT NOT NULL DEFAULT ''
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_disk_usage_mount
+         ON disk_usage(mount_point, timestamp)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_disk_usage_timestamp
+         ON disk_usage(timestamp)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Migrate from schema v11 to v12 (add metric_baselines table)
+/// Baselines are derived data; `sysreport baseline rebuild` recomputes them
+fn migrate_v11_to_v12(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS metric_baselines (
+            metric TEXT NOT NULL,
+            hour_of_week INTEGER NOT NULL,
+            samples INTEGER NOT NULL,
+            median REAL NOT NULL,
+            mad REAL NOT NULL,
+            built_at INTEGER NOT NULL,
+            PRIMARY KEY (metric, hour_of_week)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Convert INTEGER timestamps back to RFC 3339 text
+fn migrate_v2_to_v1(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE system_metrics RENAME TO system_metrics_v2", [])?;
+    conn.execute("ALTER TABLE log_entries RENAME TO log_entries_v2", [])?;
+    conn.execute("DROP INDEX IF EXISTS idx_metrics_timestamp", [])?;
+    conn.execute("DROP INDEX IF EXISTS idx_logs_timestamp", [])?;
+    conn.execute("DROP INDEX IF EXISTS idx_logs_level", [])?;
+
+    create_v1_schema(conn)?;
+
+    conn.execute(
+        "INSERT INTO system_metrics
+         SELECT id, strftime('%Y-%m-%dT%H:%M:%S+00:00', timestamp, 'unixepoch'),
+                cpu_usage, memory_total, memory_used, memory_available,
+                disk_total, disk_used, process_count,
+                load_avg_1min, load_avg_5min, load_avg_15min
+         FROM system_metrics_v2",
+        [],
+    )?;
+    conn.execute(
+        "INSERT INTO log_entries
+         SELECT id, strftime('%Y-%m-%dT%H:%M:%S+00:00', timestamp, 'unixepoch'),
+                level, source, message
+         FROM log_entries_v2",
+        [],
+    )?;
+
+    conn.execute("DROP TABLE system_metrics_v2", [])?;
+    conn.execute("DROP TABLE log_entries_v2", [])?;
+    Ok(())
+}
+
+fn migrate_v3_to_v2(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS system_checks", [])?;
+    Ok(())
+}
+
+fn migrate_v4_to_v3(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE log_entries DROP COLUMN attributes", [])?;
+    Ok(())
+}
+
+fn migrate_v5_to_v4(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS auth_events", [])?;
+    Ok(())
+}
+
+fn migrate_v6_to_v5(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS kernel_events", [])?;
+    Ok(())
+}
+
+fn migrate_v7_to_v6(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS metric_rollups", [])?;
+    conn.execute("DROP TABLE IF EXISTS log_rollups", [])?;
+    conn.execute("DROP TABLE IF EXISTS rollup_state", [])?;
+    Ok(())
+}
+
+fn migrate_v8_to_v7(conn: &Connection) -> Result<()> {
+    for table in RUN_LINKED_TABLES {
+        if table_exists(conn, table)? {
+            conn.execute(&format!("ALTER TABLE {} DROP COLUMN run_id", table), [])?;
+        }
+    }
+    conn.execute("DROP TABLE IF EXISTS collection_runs", [])?;
+    Ok(())
+}
+
+/// Merged rows from other hosts are deleted; v8 has no way to tell them apart
+fn migrate_v9_to_v8(conn: &Connection) -> Result<()> {
+    conn.execute("DROP INDEX IF EXISTS idx_auth_unique", [])?;
+    conn.execute("DROP INDEX IF EXISTS idx_kernel_unique", [])?;
+    for table in HOST_TAGGED_TABLES {
+        if table_exists(conn, table)? {
+            conn.execute(&format!("DELETE FROM {} WHERE host != ''", table), [])?;
+            conn.execute(&format!("ALTER TABLE {} DROP COLUMN host", table), [])?;
+        }
+    }
+
+    if table_exists(conn, "auth_events")? {
+        conn.execute(
+            "CREATE UNIQUE INDEX idx_auth_unique
+             ON auth_events(timestamp, event_type, message)",
+            [],
+        )?;
+    }
+    if table_exists(conn, "kernel_events")? {
+        conn.execute(
+            "CREATE UNIQUE INDEX idx_kernel_unique
+             ON kernel_events(timestamp, event_type, message)",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn migrate_v10_to_v9(conn: &Connection) -> Result<()> {
+    if !table_exists(conn, "system_checks")? {
+        return Ok(());
+    }
+
+    rebuild_system_checks(conn, false)?;
+    // Whole numbers go back to their original text form ("3", not "3.0")
+    conn.execute(
+        "INSERT INTO system_checks
+            (id, timestamp, check_name, check_type, status, value, message, run_id, host)
+         SELECT id, timestamp, check_name, check_type, status,
+                CASE
+                    WHEN value IS NULL THEN json_extract(details, '$.value')
+                    WHEN value = CAST(value AS INTEGER) THEN CAST(CAST(value AS INTEGER) AS TEXT)
+                    ELSE CAST(value AS TEXT)
+                END,
+                message, run_id, host
+         FROM system_checks_old",
+        [],
+    )?;
+    conn.execute("DROP TABLE system_checks_old", [])?;
+    Ok(())
+}
+
+fn migrate_v11_to_v10(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS disk_usage", [])?;
+    Ok(())
+}
+
+fn migrate_v12_to_v11(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS metric_baselines", [])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_ordered() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i32 + 1);
+        }
+        assert_eq!(
+            MIGRATIONS.last().map(|m| m.version),
+            Some(crate::db::SCHEMA_VERSION)
+        );
+    }
+}
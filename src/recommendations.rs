@@ -0,0 +1,433 @@
+use crate::config::{
+    CPU_WARNING_THRESHOLD, DISK_WARNING_THRESHOLD, MEMORY_WARNING_THRESHOLD, SWAP_WARNING_THRESHOLD,
+};
+use crate::db::{CheckStatus, SystemCheckResult};
+use crate::kernel::{EVENT_FS_ERROR, EVENT_HARDWARE_ERROR, EVENT_IO_ERROR, EVENT_OOM_KILL};
+use crate::reporter::FullReport;
+use crate::timeline::Severity;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+/// Directory whose filesystem is checked for log space
+const LOG_DIR: &str = "/var/log";
+/// Units, disks or processes named in one recommendation
+const MAX_NAMED: usize = 3;
+
+/// A finding with what to do about it
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    /// Stable identifier, e.g. "failed-units", for filtering and alert rules
+    pub id: &'static str,
+    pub severity: Severity,
+    /// What was found, e.g. "2 systemd units failed: nginx.service, cron.service"
+    pub finding: String,
+    /// What to do about it
+    pub action: String,
+    /// Commands that help, to be run by hand
+    pub commands: Vec<String>,
+}
+
+impl Recommendation {
+    fn new(id: &'static str, severity: Severity, finding: String, action: &str) -> Self {
+        Recommendation {
+            id,
+            severity,
+            finding,
+            action: action.to_string(),
+            commands: Vec::new(),
+        }
+    }
+
+    fn commands<I, S>(mut self, commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.commands.extend(commands.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Match the findings of a report against the rules, most severe first
+/// More than `error_count` log errors make a "multiple errors" recommendation
+pub fn recommend(report: &FullReport, error_count: usize) -> Vec<Recommendation> {
+    let metrics = &report.metrics;
+    let logs = &report.logs;
+    let latest = report.system_checks.latest();
+    let check = |check_type: &str| latest.iter().copied().find(|c| c.check_type == check_type);
+    let mut recommendations = Vec::new();
+
+    if metrics.max_cpu_usage > CPU_WARNING_THRESHOLD {
+        recommendations.push(
+            Recommendation::new(
+                "cpu-high",
+                Severity::Warning,
+                format!("CPU usage peaked at {:.1}%", metrics.max_cpu_usage),
+                "Look for runaway processes and lower their priority or restart them",
+            )
+            .commands(["ps aux --sort=-%cpu | head -n 10", "top -o %CPU"]),
+        );
+    }
+    if metrics.max_memory_used_percent > MEMORY_WARNING_THRESHOLD {
+        recommendations.push(
+            Recommendation::new(
+                "memory-high",
+                Severity::Warning,
+                format!(
+                    "Memory usage peaked at {:.1}%",
+                    metrics.max_memory_used_percent
+                ),
+                "Find the largest processes and restart or limit them, or add RAM",
+            )
+            .commands(["ps aux --sort=-%mem | head -n 10", "free -h"]),
+        );
+    }
+    if let Some(swap) =
+        check("swap").filter(|c| c.value.unwrap_or(0.0) > SWAP_WARNING_THRESHOLD as f64)
+    {
+        recommendations.push(
+            Recommendation::new(
+                "swap-high",
+                Severity::Warning,
+                format!("{:.1}% of swap is in use", swap.value.unwrap_or(0.0)),
+                "The system is short of memory; find what is swapped out and add RAM or reduce the load",
+            )
+            .commands([
+                "free -h",
+                "vmstat 5 5",
+                "grep VmSwap /proc/[0-9]*/status | sort -k2 -n | tail -n 10",
+            ]),
+        );
+    }
+
+    if metrics.max_disk_used_percent > DISK_WARNING_THRESHOLD {
+        recommendations.push(
+            Recommendation::new(
+                "disk-high",
+                Severity::Warning,
+                format!("Disk usage peaked at {:.1}%", metrics.max_disk_used_percent),
+                "Clean up old files or expand storage",
+            )
+            .commands([
+                "df -h",
+                "sudo du -xh / --max-depth=2 | sort -h | tail -n 15",
+            ]),
+        );
+    }
+    if let Some(mount) = report.disk_forecast.as_ref().and_then(|forecast| {
+        forecast
+            .mounts
+            .iter()
+            .filter(|m| Path::new(LOG_DIR).starts_with(&m.mount_point))
+            .max_by_key(|m| m.mount_point.len())
+            .filter(|m| m.used_percent > DISK_WARNING_THRESHOLD as f64)
+    }) {
+        recommendations.push(
+            Recommendation::new(
+                "log-space",
+                if mount.used_percent >= 95.0 {
+                    Severity::Critical
+                } else {
+                    Severity::Warning
+                },
+                format!(
+                    "{} is on {}, which is {:.1}% full",
+                    LOG_DIR, mount.mount_point, mount.used_percent
+                ),
+                "Shrink the journal and rotate or delete old logs before logging stops",
+            )
+            .commands([
+                "sudo du -sh /var/log/* | sort -h | tail -n 10",
+                "sudo journalctl --vacuum-size=500M",
+                "sudo logrotate -f /etc/logrotate.conf",
+            ]),
+        );
+    }
+    if let Some(forecast) = report.disk_forecast.as_ref() {
+        let flagged = forecast.flagged();
+        if !flagged.is_empty() {
+            let mounts: Vec<&str> = flagged.iter().map(|m| m.mount_point.as_str()).collect();
+            recommendations.push(
+                Recommendation::new(
+                    "disk-filling",
+                    Severity::Warning,
+                    format!("Projected to fill up soon: {}", mounts.join(", ")),
+                    "Free or add space before the filesystems are full - see DISK FORECAST",
+                )
+                .commands([format!("df -h {}", mounts.join(" "))]),
+            );
+        }
+    }
+
+    if let Some(systemd) = check("systemd").filter(|c| c.value.unwrap_or(0.0) > 0.0) {
+        let units = string_list(systemd, "failed_service_names");
+        let mut recommendation = Recommendation::new(
+            "failed-units",
+            Severity::Critical,
+            format!(
+                "{} systemd unit(s) failed{}",
+                systemd.value.unwrap_or(0.0) as u64,
+                named(&units)
+            ),
+            "Read the unit logs to find the cause, fix it and restart the units",
+        )
+        .commands(["systemctl --failed".to_string()]);
+        for unit in units.iter().take(MAX_NAMED) {
+            recommendation = recommendation.commands([
+                format!("sudo journalctl -u {} -n 50 --no-pager", unit),
+                format!("sudo systemctl restart {}", unit),
+            ]);
+        }
+        recommendations.push(recommendation);
+    }
+    if let Some(updates) = check("package_manager") {
+        let security = number(updates, "security_updates").unwrap_or(0);
+        if security > 0 {
+            let upgrade = match updates
+                .details
+                .as_ref()
+                .and_then(|d| d.get("package_manager"))
+                .and_then(Value::as_str)
+            {
+                Some("dnf") => "sudo dnf upgrade --security",
+                _ => "sudo apt update && sudo apt upgrade",
+            };
+            recommendations.push(
+                Recommendation::new(
+                    "security-updates",
+                    Severity::Warning,
+                    format!("{} security update(s) pending", security),
+                    "Install the security updates and reboot if the kernel or libc changed",
+                )
+                .commands([upgrade]),
+            );
+        }
+    }
+    if let Some(smart) = check("disk_health") {
+        let disks = smart
+            .details
+            .as_ref()
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let device = |disk: &Value| {
+            disk.get("device")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string()
+        };
+        if smart.status == CheckStatus::Critical {
+            let failing: Vec<String> = disks
+                .iter()
+                .filter(|d| d.get("health_status").and_then(Value::as_str) != Some("PASSED"))
+                .map(device)
+                .collect();
+            recommendations.push(
+                Recommendation::new(
+                    "smart-failing",
+                    Severity::Critical,
+                    format!("SMART health check failed{}", named(&failing)),
+                    "Back up the data on the disk now and plan to replace it",
+                )
+                .commands(
+                    failing
+                        .iter()
+                        .take(MAX_NAMED)
+                        .map(|d| format!("sudo smartctl -a {}", d)),
+                ),
+            );
+        }
+        let reallocated: Vec<(String, u64)> = disks
+            .iter()
+            .filter_map(|d| {
+                let sectors = d.get("reallocated_sectors").and_then(Value::as_u64)?;
+                (sectors > 0).then(|| (device(d), sectors))
+            })
+            .collect();
+        if !reallocated.is_empty() {
+            let described: Vec<String> = reallocated
+                .iter()
+                .map(|(device, sectors)| format!("{} ({})", device, sectors))
+                .collect();
+            recommendations.push(
+                Recommendation::new(
+                    "smart-reallocated",
+                    Severity::Warning,
+                    format!("Disks have reallocated sectors: {}", described.join(", ")),
+                    "The disk is wearing out; run a long self-test, watch whether the count grows and keep backups current",
+                )
+                .commands(reallocated.iter().take(MAX_NAMED).flat_map(|(device, _)| {
+                    [
+                        format!("sudo smartctl -t long {}", device),
+                        format!("sudo smartctl -A {}", device),
+                    ]
+                })),
+            );
+        }
+    }
+
+    if let Some(ref kernel) = report.kernel {
+        let oom_kills = kernel.count(EVENT_OOM_KILL);
+        if oom_kills > 0 {
+            recommendations.push(
+                Recommendation::new(
+                    "oom-kills",
+                    Severity::Critical,
+                    format!("The OOM killer ended {} process(es)", oom_kills),
+                    "Add memory or swap, or cap the largest services with MemoryMax=",
+                )
+                .commands(["journalctl -k | grep -i 'killed process'", "free -h"]),
+            );
+        }
+        let hardware = kernel.count(EVENT_HARDWARE_ERROR)
+            + kernel.count(EVENT_IO_ERROR)
+            + kernel.count(EVENT_FS_ERROR);
+        if hardware > 0 {
+            recommendations.push(
+                Recommendation::new(
+                    "kernel-hardware-errors",
+                    Severity::Critical,
+                    format!(
+                        "The kernel reported {} hardware, disk or filesystem error(s)",
+                        hardware
+                    ),
+                    "Check the disks' SMART status and schedule a filesystem check",
+                )
+                .commands([
+                    "journalctl -k -p err --since '24 hours ago'",
+                    "sudo smartctl --scan",
+                ]),
+            );
+        }
+    }
+
+    if logs.total_critical > 0 {
+        recommendations.push(
+            Recommendation::new(
+                "log-critical",
+                Severity::Critical,
+                format!("{} critical log entries", logs.total_critical),
+                "Review the critical entries now - see LOG ANALYSIS",
+            )
+            .commands(["journalctl -p crit --since '24 hours ago'"]),
+        );
+    }
+    if logs.total_errors > error_count {
+        recommendations.push(
+            Recommendation::new(
+                "log-errors",
+                Severity::Warning,
+                format!(
+                    "{} errors logged (more than {})",
+                    logs.total_errors, error_count
+                ),
+                "Look for a pattern in the errors, such as one service or one time of day",
+            )
+            .commands([
+                "journalctl -p err --since '24 hours ago' | sort | uniq -c | sort -rn | head",
+            ]),
+        );
+    }
+
+    if report
+        .security
+        .as_ref()
+        .is_some_and(|s| !s.brute_force_bursts.is_empty())
+    {
+        recommendations.push(
+            Recommendation::new(
+                "ssh-brute-force",
+                Severity::Warning,
+                "SSH brute-force attempts detected".to_string(),
+                "Block repeat offenders with fail2ban and allow key logins only",
+            )
+            .commands([
+                "sudo apt install fail2ban",
+                "sudo sshd -T | grep -i passwordauthentication",
+            ]),
+        );
+    }
+    if report
+        .anomalies
+        .as_ref()
+        .is_some_and(|a| !a.anomalies.is_empty())
+    {
+        recommendations.push(Recommendation::new(
+            "anomalies",
+            Severity::Info,
+            "Metrics left their usual range for the time of week".to_string(),
+            "Check what ran at those times - see ANOMALIES",
+        ));
+    }
+
+    if let Some(ref collection) = report.collection {
+        if !collection.gaps.is_empty() {
+            recommendations.push(
+                Recommendation::new(
+                    "collection-gaps",
+                    Severity::Warning,
+                    format!(
+                        "{} scheduled collection run(s) are missing",
+                        collection.gaps.len()
+                    ),
+                    "Check the syswriter cron job or systemd timer",
+                )
+                .commands(["systemctl list-timers syswriter.timer", "crontab -l"]),
+            );
+        }
+        if collection.failed_runs + collection.interrupted_runs > 0 {
+            recommendations.push(
+                Recommendation::new(
+                    "collection-failures",
+                    Severity::Warning,
+                    format!(
+                        "{} collection run(s) failed or were interrupted",
+                        collection.failed_runs + collection.interrupted_runs
+                    ),
+                    "See COLLECTION HEALTH and the syswriter logs",
+                )
+                .commands(["journalctl -u syswriter.service --since '24 hours ago'"]),
+            );
+        }
+    }
+
+    recommendations.sort_by_key(|r| std::cmp::Reverse(r.severity));
+    recommendations
+}
+
+/// ": a, b, c and 2 more", or nothing for an empty list
+fn named(names: &[String]) -> String {
+    match names.len() {
+        0 => String::new(),
+        n if n <= MAX_NAMED => format!(": {}", names.join(", ")),
+        n => format!(
+            ": {} and {} more",
+            names[..MAX_NAMED].join(", "),
+            n - MAX_NAMED
+        ),
+    }
+}
+
+fn string_list(check: &SystemCheckResult, key: &str) -> Vec<String> {
+    check
+        .details
+        .as_ref()
+        .and_then(|d| d.get(key))
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn number(check: &SystemCheckResult, key: &str) -> Option<u64> {
+    check
+        .details
+        .as_ref()
+        .and_then(|d| d.get(key))
+        .and_then(Value::as_u64)
+}
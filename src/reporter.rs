@@ -1,10 +1,39 @@
+use crate::anomaly::{describe_anomaly, detect_anomalies, AnomalyReport};
+use crate::auth::{
+    detect_brute_force_bursts, BruteForceBurst, EVENT_ACCEPTED_LOGIN, EVENT_FAILED_LOGIN,
+    EVENT_INVALID_USER, EVENT_SUDO_COMMAND, EVENT_SUDO_FAILURE,
+};
 use crate::config::{
-    CPU_WARNING_THRESHOLD, DISK_WARNING_THRESHOLD, ERROR_COUNT_THRESHOLD, LOAD_WARNING_THRESHOLD,
-    MAX_RECENT_ERRORS_DISPLAY, MEMORY_WARNING_THRESHOLD,
+    BRUTE_FORCE_MAX_GAP_SECONDS, BRUTE_FORCE_MIN_ATTEMPTS, CPU_WARNING_THRESHOLD,
+    DEFAULT_ANOMALY_MAD_MULTIPLIER, DEFAULT_COLLECTION_INTERVAL_MINUTES, DISK_WARNING_THRESHOLD, ERROR_COUNT_THRESHOLD,
+    LOAD_WARNING_THRESHOLD, MAX_RECENT_ERRORS_DISPLAY, MEMORY_WARNING_THRESHOLD,
+};
+use crate::db::{
+    auth_event_seen_before, earliest_auth_event, earliest_data, last_run_before, query_auth_events,
+    query_collection_runs, query_kernel_events, query_log_rollups, query_logs,
+    query_metric_rollups, query_metrics, query_system_checks, rollup_built_until,
+    rollups_exist_between, CheckStatus, CollectionRun, HostFilter, KernelEvent, LogEntry,
+    MetricRollup, Resolution, SystemCheckResult, RUN_FAILED, RUN_MODE_COLLECT, RUN_OK,
+    RUN_PARTIAL, RUN_RUNNING,
+};
+use crate::forecast::{forecast_disks, DiskForecast, DiskForecastReport, ForecastOptions};
+use crate::html::format_html_report;
+use crate::kernel::{
+    EVENT_BOOT, EVENT_FS_ERROR, EVENT_HARDWARE_ERROR, EVENT_IO_ERROR, EVENT_OOM_KILL,
+    EVENT_SEGFAULT,
+};
+use crate::markdown::format_markdown_report;
+use crate::recommendations::{recommend, Recommendation};
+use crate::rollup::{
+    bucket_start, first_bucket_from, metric_values, METRIC_CPU, METRIC_DISK, METRIC_LOAD,
+    METRIC_MEMORY, METRIC_PROCESSES,
 };
-use crate::db::{query_logs, query_metrics, query_system_checks, LogEntry, SystemCheckResult};
+use crate::timeline::Severity;
+use crate::timezone::DisplayTimezone;
+use std::collections::BTreeMap;
+use std::fmt;
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Local, Utc};
+use chrono::{DateTime, Duration, Utc};
 use rusqlite::Connection;
 use serde::Serialize;
 
@@ -21,7 +50,61 @@ pub struct MetricsReport {
     pub max_disk_used_percent: f32,
     pub avg_process_count: usize,
     pub max_load_avg_1min: f64,
-    pub issues: Vec<String>,
+    pub issues: Vec<MetricIssue>,
+    /// Data tier the statistics were computed from
+    pub resolution: Resolution,
+    /// Distribution of each metric (cpu_usage, memory_used_percent, ...)
+    pub statistics: BTreeMap<String, MetricStatistics>,
+}
+
+/// A problem found in the metrics, e.g. a peak above its warning threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricIssue {
+    pub severity: Severity,
+    /// What was found, e.g. "HIGH CPU USAGE: Peak CPU usage reached 96.0%"
+    pub message: String,
+}
+
+impl MetricIssue {
+    fn new(severity: Severity, message: String) -> Self {
+        MetricIssue { severity, message }
+    }
+}
+
+/// The message as the text report shows it, with its severity icon
+impl fmt::Display for MetricIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Info => write!(f, "{}", self.message),
+            _ => write!(f, "{}  {}", self.severity.icon(), self.message),
+        }
+    }
+}
+
+/// Time a metric spent above its warning threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdTime {
+    pub threshold: f64,
+    pub minutes: f64,
+    pub percent_of_period: f64,
+}
+
+/// Time-weighted distribution of one metric over the report period
+/// From rollups, percentiles and the standard deviation are estimated from
+/// bucket averages while min and max stay exact
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricStatistics {
+    pub samples: usize,
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub std_dev: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    /// None for metrics without a warning threshold
+    pub above_threshold: Option<ThresholdTime>,
 }
 
 /// Report statistics for log entries
@@ -44,18 +127,824 @@ pub struct SystemChecksReport {
     pub checks_error: usize,
 }
 
-/// Generate a comprehensive system report
-pub fn generate_report(
+impl SystemChecksReport {
+    /// Newest result of each check, ordered by check name
+    pub fn latest(&self) -> Vec<&SystemCheckResult> {
+        let mut latest: BTreeMap<&str, &SystemCheckResult> = BTreeMap::new();
+        for check in &self.checks {
+            let current = latest.entry(check.check_name.as_str()).or_insert(check);
+            if check.timestamp > current.timestamp {
+                *current = check;
+            }
+        }
+        latest.into_values().collect()
+    }
+}
+
+/// Averaged metric values for one chart bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricPoint {
+    pub timestamp: DateTime<Utc>,
+    pub cpu_usage: f64,
+    pub memory_used_percent: f64,
+    pub disk_used_percent: f64,
+    pub load_avg_1min: f64,
+}
+
+/// Failed login attempts from one source address
+#[derive(Debug, Serialize, Clone)]
+pub struct IpAttemptSummary {
+    pub source_ip: String,
+    pub failed_logins: usize,
+    pub invalid_users: usize,
+}
+
+/// Successful login from a user/address pair never seen before the report period
+#[derive(Debug, Serialize, Clone)]
+pub struct NewLoginSource {
+    pub username: String,
+    pub source_ip: String,
+    pub method: Option<String>,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Sudo usage by a user with no sudo history before the report period
+#[derive(Debug, Serialize, Clone)]
+pub struct SudoUserActivity {
+    pub username: String,
+    pub commands: usize,
+    pub first_command: Option<String>,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Report for SSH and sudo activity parsed from auth.log
+#[derive(Debug, Serialize, Clone)]
+pub struct SecurityReport {
+    pub failed_logins: usize,
+    pub invalid_user_attempts: usize,
+    pub accepted_logins: usize,
+    pub sudo_commands: usize,
+    pub sudo_failures: usize,
+    pub top_attacking_ips: Vec<IpAttemptSummary>,
+    pub brute_force_bursts: Vec<BruteForceBurst>,
+    pub new_login_sources: Vec<NewLoginSource>,
+    pub new_sudo_users: Vec<SudoUserActivity>,
+    /// Oldest stored auth event; "first-time" and "new" are relative to it,
+    /// since auth events are pruned after `retention.days`
+    pub history_since: Option<DateTime<Utc>>,
+}
+
+/// Number of source addresses listed under "top attacking IPs"
+const TOP_ATTACKING_IPS: usize = 5;
+
+/// Generate the security section from a host's stored auth events
+/// Returns None when no auth events were recorded in the period
+pub fn generate_security_report(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Option<SecurityReport>> {
+    let events = query_auth_events(conn, start, end, host)?;
+    if events.is_empty() {
+        return Ok(None);
+    }
+
+    let count_type = |event_type: &str| events.iter().filter(|e| e.event_type == event_type).count();
+
+    // Failed attempts per source address
+    let mut by_ip: Vec<IpAttemptSummary> = Vec::new();
+    for event in &events {
+        let is_failed = event.event_type == EVENT_FAILED_LOGIN;
+        if !is_failed && event.event_type != EVENT_INVALID_USER {
+            continue;
+        }
+        let Some(ref ip) = event.source_ip else { continue };
+
+        let index = match by_ip.iter().position(|s| &s.source_ip == ip) {
+            Some(index) => index,
+            None => {
+                by_ip.push(IpAttemptSummary {
+                    source_ip: ip.clone(),
+                    failed_logins: 0,
+                    invalid_users: 0,
+                });
+                by_ip.len() - 1
+            }
+        };
+        if is_failed {
+            by_ip[index].failed_logins += 1;
+        } else {
+            by_ip[index].invalid_users += 1;
+        }
+    }
+    by_ip.sort_by(|a, b| {
+        (b.failed_logins, b.invalid_users).cmp(&(a.failed_logins, a.invalid_users))
+    });
+    by_ip.truncate(TOP_ATTACKING_IPS);
+
+    let brute_force_bursts = detect_brute_force_bursts(
+        &events,
+        BRUTE_FORCE_MIN_ATTEMPTS,
+        Duration::seconds(BRUTE_FORCE_MAX_GAP_SECONDS),
+    );
+
+    // Logins from user/address pairs with no history before the period
+    let mut new_login_sources: Vec<NewLoginSource> = Vec::new();
+    for event in events.iter().filter(|e| e.event_type == EVENT_ACCEPTED_LOGIN) {
+        let (Some(username), Some(source_ip)) = (&event.username, &event.source_ip) else {
+            continue;
+        };
+        let already_listed = new_login_sources
+            .iter()
+            .any(|s| &s.username == username && &s.source_ip == source_ip);
+        if already_listed
+            || auth_event_seen_before(
+                conn,
+                EVENT_ACCEPTED_LOGIN,
+                username,
+                Some(source_ip),
+                start,
+                host,
+            )?
+        {
+            continue;
+        }
+        new_login_sources.push(NewLoginSource {
+            username: username.clone(),
+            source_ip: source_ip.clone(),
+            method: event.method.clone(),
+            first_seen: event.timestamp,
+        });
+    }
+
+    // Sudo users with no sudo history before the period
+    let mut new_sudo_users: Vec<SudoUserActivity> = Vec::new();
+    for event in events.iter().filter(|e| e.event_type == EVENT_SUDO_COMMAND) {
+        let Some(ref username) = event.username else { continue };
+        if let Some(activity) = new_sudo_users.iter_mut().find(|a| &a.username == username) {
+            activity.commands += 1;
+            continue;
+        }
+        if auth_event_seen_before(conn, EVENT_SUDO_COMMAND, username, None, start, host)? {
+            continue;
+        }
+        new_sudo_users.push(SudoUserActivity {
+            username: username.clone(),
+            commands: 1,
+            first_command: event.command.clone(),
+            first_seen: event.timestamp,
+        });
+    }
+
+    Ok(Some(SecurityReport {
+        failed_logins: count_type(EVENT_FAILED_LOGIN),
+        invalid_user_attempts: count_type(EVENT_INVALID_USER),
+        accepted_logins: count_type(EVENT_ACCEPTED_LOGIN),
+        sudo_commands: count_type(EVENT_SUDO_COMMAND),
+        sudo_failures: count_type(EVENT_SUDO_FAILURE),
+        top_attacking_ips: by_ip,
+        brute_force_bursts,
+        new_login_sources,
+        new_sudo_users,
+        history_since: earliest_auth_event(conn, host)?,
+    }))
+}
+
+/// Report for structured kernel events
+#[derive(Debug, Serialize, Clone)]
+pub struct KernelReport {
+    pub total_events: usize,
+    /// Number of events per type (oom_kill, segfault, ...)
+    pub counts_by_type: BTreeMap<String, usize>,
+    pub recent_events: Vec<KernelEvent>,
+}
+
+impl KernelReport {
+    /// Number of events of one type
+    pub fn count(&self, event_type: &str) -> usize {
+        self.counts_by_type.get(event_type).copied().unwrap_or(0)
+    }
+}
+
+/// Generate the kernel events section from a host's stored kernel events
+/// Returns None when no kernel events were recorded in the period
+pub fn generate_kernel_report(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Option<KernelReport>> {
+    let events = query_kernel_events(conn, start, end, host)?;
+    if events.is_empty() {
+        return Ok(None);
+    }
+
+    let mut counts_by_type = BTreeMap::new();
+    for event in &events {
+        *counts_by_type.entry(event.event_type.clone()).or_insert(0) += 1;
+    }
+
+    Ok(Some(KernelReport {
+        total_events: events.len(),
+        counts_by_type,
+        recent_events: events.into_iter().take(MAX_RECENT_ERRORS_DISPLAY).collect(),
+    }))
+}
+
+/// A stretch of time in which expected collection runs are missing
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionGap {
+    /// Last run before the gap (or the report start)
+    pub from: DateTime<Utc>,
+    /// First run after the gap (or the report end)
+    pub to: DateTime<Utc>,
+    pub missed_runs: i64,
+}
+
+/// A collection run that did not fully succeed
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedRun {
+    pub started_at: DateTime<Utc>,
+    pub status: String,
+    pub errors: Vec<String>,
+}
+
+/// Health of scheduled syswriter runs, from the collection_runs ledger
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionReport {
+    /// Expected minutes between runs
+    pub interval_minutes: i64,
+    pub total_runs: usize,
+    pub ok_runs: usize,
+    pub partial_runs: usize,
+    pub failed_runs: usize,
+    /// Runs that started but never recorded how they ended
+    pub interrupted_runs: usize,
+    pub gaps: Vec<CollectionGap>,
+    /// Most recent failed, partial or interrupted runs first
+    pub failures: Vec<FailedRun>,
+}
+
+impl CollectionReport {
+    /// Total number of expected runs that are missing
+    pub fn missed_runs(&self) -> i64 {
+        self.gaps.iter().map(|g| g.missed_runs).sum()
+    }
+}
+
+/// Options for generating a full report
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Expected time between scheduled syswriter runs
+    pub collection_interval: Duration,
+    /// Lookback and horizon of the disk fill forecast
+    pub forecast: ForecastOptions,
+    /// Scaled MADs a sample may stray from its baseline median before it is an anomaly
+    pub anomaly_mad_multiplier: f64,
+    /// Log errors above which the "multiple errors" recommendation is made
+    pub error_count: usize,
+    /// Time zone timestamps are shown in
+    pub timezone: DisplayTimezone,
+    /// Host whose rows are reported on; merged hosts are read from raw rows
+    /// only, without rollups, baselines or collection runs
+    pub host: HostFilter,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            collection_interval: Duration::minutes(DEFAULT_COLLECTION_INTERVAL_MINUTES as i64),
+            forecast: ForecastOptions::default(),
+            anomaly_mad_multiplier: DEFAULT_ANOMALY_MAD_MULTIPLIER,
+            error_count: ERROR_COUNT_THRESHOLD,
+            timezone: DisplayTimezone::default(),
+            host: HostFilter::Local,
+        }
+    }
+}
+
+/// Generate the collection health section
+/// A gap is reported where runs are further apart than 1.5 intervals.
+/// Returns None when no scheduled runs have been recorded yet
+pub fn generate_collection_report(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    interval: Duration,
+) -> Result<Option<CollectionReport>> {
+    let runs: Vec<CollectionRun> = query_collection_runs(conn, start, end)?
+        .into_iter()
+        .filter(|run| run.mode == RUN_MODE_COLLECT)
+        .collect();
+    let previous = last_run_before(conn, RUN_MODE_COLLECT, start)?;
+    if runs.is_empty() && previous.is_none() {
+        return Ok(None);
+    }
+
+    let interval_secs = interval.num_seconds().max(1);
+    // Expected runs after `last` that fall before the report start; a gap
+    // straddling the start only counts the runs missed inside the window
+    let before_start =
+        |last: DateTime<Utc>| ((start - last).num_seconds() - 1).max(0) / interval_secs;
+    let mut gaps = Vec::new();
+    let mut last = previous;
+    for run in &runs {
+        if let Some(last) = last {
+            let delta = (run.started_at - last).num_seconds();
+            if delta * 2 > interval_secs * 3 {
+                let missed = ((delta as f64 / interval_secs as f64).round() as i64 - 1).max(1)
+                    - before_start(last);
+                if missed >= 1 {
+                    gaps.push(CollectionGap {
+                        from: last.max(start),
+                        to: run.started_at,
+                        missed_runs: missed,
+                    });
+                }
+            }
+        }
+        last = Some(run.started_at);
+    }
+    // Runs that should have happened since the last one
+    if let Some(last) = last {
+        let missed = (end - last).num_seconds() / interval_secs - before_start(last);
+        if missed >= 1 && (end - last).num_seconds() * 2 > interval_secs * 3 {
+            gaps.push(CollectionGap {
+                from: last.max(start),
+                to: end,
+                missed_runs: missed,
+            });
+        }
+    }
+
+    // The newest run may still be in progress
+    let newest_id = runs.last().map(|run| run.id);
+    let interrupted =
+        |run: &CollectionRun| run.status == RUN_RUNNING && Some(run.id) != newest_id;
+    let count = |status: &str| runs.iter().filter(|run| run.status == status).count();
+
+    let failures = runs
+        .iter()
+        .rev()
+        .filter(|run| run.status == RUN_FAILED || run.status == RUN_PARTIAL || interrupted(run))
+        .take(MAX_RECENT_ERRORS_DISPLAY)
+        .map(|run| FailedRun {
+            started_at: run.started_at,
+            status: if interrupted(run) {
+                "interrupted".to_string()
+            } else {
+                run.status.clone()
+            },
+            errors: run.errors(),
+        })
+        .collect();
+
+    Ok(Some(CollectionReport {
+        interval_minutes: interval.num_minutes(),
+        total_runs: runs.len(),
+        ok_runs: count(RUN_OK),
+        partial_runs: count(RUN_PARTIAL),
+        failed_runs: count(RUN_FAILED),
+        interrupted_runs: runs.iter().filter(|run| interrupted(run)).count(),
+        gaps,
+        failures,
+    }))
+}
+
+/// Generate a report with every available section using default options
+pub fn generate_full_report(conn: &Connection, hours_back: i64) -> Result<FullReport> {
+    generate_full_report_with_options(conn, hours_back, &ReportOptions::default())
+}
+
+/// Generate a report with every available section over the last `hours_back` hours
+pub fn generate_full_report_with_options(
     conn: &Connection,
     hours_back: i64,
-) -> Result<(MetricsReport, LogReport, SystemChecksReport)> {
+    options: &ReportOptions,
+) -> Result<FullReport> {
     let end = Utc::now();
-    let start = end - Duration::hours(hours_back);
+    generate_full_report_between(conn, end - Duration::hours(hours_back), end, options)
+}
+
+/// Generate a report with every available section over `start..end`
+pub fn generate_full_report_between(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    options: &ReportOptions,
+) -> Result<FullReport> {
+    let (mut metrics, logs, system_checks) = generate_report_between(conn, start, end, options)?;
+    let host = &options.host;
+    // Mount points of different hosts would be fitted as one
+    let disk_forecast = if *host == HostFilter::All {
+        None
+    } else {
+        forecast_disks(conn, metrics.period_end, &options.forecast, host)?
+    };
+    if let Some(ref forecast) = disk_forecast {
+        for mount in forecast.flagged() {
+            metrics.issues.push(MetricIssue::new(
+                Severity::Warning,
+                format!(
+                    "DISK FILLING UP: {} projected to be full {} (confidence: {})",
+                    mount.mount_point,
+                    describe_fill_time(mount, options.timezone),
+                    mount.confidence.as_str()
+                ),
+            ));
+        }
+    }
+    // Baselines are learned from this host's samples only
+    let anomalies = if host.is_local() {
+        detect_anomalies(
+            conn,
+            metrics.period_start,
+            metrics.period_end,
+            options.anomaly_mad_multiplier,
+        )?
+    } else {
+        None
+    };
+    if let Some(ref anomalies) = anomalies {
+        for anomaly in &anomalies.anomalies {
+            metrics.issues.push(MetricIssue::new(
+                Severity::Warning,
+                format!("ANOMALY: {}", describe_anomaly(anomaly, options.timezone)),
+            ));
+        }
+    }
+    let series = generate_metric_series(
+        conn,
+        metrics.period_start,
+        metrics.period_end,
+        metrics.resolution,
+        host,
+    )?;
+    let security = generate_security_report(conn, metrics.period_start, metrics.period_end, host)?;
+    let kernel = generate_kernel_report(conn, metrics.period_start, metrics.period_end, host)?;
+    // Collection runs are not merged
+    let collection = if host.is_local() {
+        generate_collection_report(
+            conn,
+            metrics.period_start,
+            metrics.period_end,
+            options.collection_interval,
+        )?
+    } else {
+        None
+    };
+
+    let mut report = FullReport {
+        series,
+        security,
+        kernel,
+        collection,
+        disk_forecast,
+        anomalies,
+        host: host.to_string(),
+        timezone: options.timezone,
+        ..FullReport::new(metrics, logs, system_checks)
+    };
+    report.recommendations = recommend(&report, options.error_count);
+    Ok(report)
+}
+
+/// Average a host's metrics of `start..end` into at most `CHART_BUCKETS` points
+/// Rollups of `resolution` are used up to where they were built, raw samples after that
+pub fn generate_metric_series(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    resolution: Resolution,
+    host: &HostFilter,
+) -> Result<Vec<MetricPoint>> {
+    let bucket_secs = ((end - start).num_seconds() / CHART_BUCKETS).max(60);
+    let bucket_of = |time: DateTime<Utc>| {
+        let offset = (time - start).num_seconds().max(0) / bucket_secs;
+        start + Duration::seconds(offset * bucket_secs)
+    };
+    let mut buckets: BTreeMap<DateTime<Utc>, BTreeMap<&str, MetricTotals>> = BTreeMap::new();
+
+    let mut raw_head_end = None;
+    let raw_from = if resolution == Resolution::Raw {
+        start
+    } else {
+        let first_bucket = first_bucket_from(start, resolution);
+        for rollup in query_metric_rollups(conn, resolution, first_bucket, end)? {
+            if let Some(metric) = [METRIC_CPU, METRIC_MEMORY, METRIC_DISK, METRIC_LOAD]
+                .into_iter()
+                .find(|m| *m == rollup.metric)
+            {
+                buckets
+                    .entry(bucket_of(rollup.bucket_start))
+                    .or_default()
+                    .entry(metric)
+                    .or_default()
+                    .add_rollup(&rollup);
+            }
+        }
+        let raw_from =
+            rollup_built_until(conn, resolution)?.map_or(start, |until| until.max(start));
+        raw_head_end = raw_head(start, first_bucket, raw_from);
+        raw_from
+    };
+
+    let mut samples = query_metrics(conn, raw_from, end, host)?;
+    if let Some(head_end) = raw_head_end {
+        samples.extend(query_metrics(conn, start, head_end - Duration::seconds(1), host)?);
+    }
+    for sample in samples {
+        let totals = buckets.entry(bucket_of(sample.timestamp)).or_default();
+        for (metric, value) in metric_values(&sample) {
+            totals.entry(metric).or_default().add_sample(value);
+        }
+    }
+
+    Ok(buckets
+        .into_iter()
+        .filter(|(_, totals)| totals.contains_key(METRIC_CPU))
+        .map(|(timestamp, totals)| {
+            let avg = |metric: &str| totals.get(metric).map_or(0.0, MetricTotals::avg);
+            MetricPoint {
+                timestamp,
+                cpu_usage: avg(METRIC_CPU),
+                memory_used_percent: avg(METRIC_MEMORY),
+                disk_used_percent: avg(METRIC_DISK),
+                load_avg_1min: avg(METRIC_LOAD),
+            }
+        })
+        .collect())
+}
+
+/// Maximum number of points in a metric chart
+const CHART_BUCKETS: i64 = 240;
+
+/// Running totals for one metric in a chart bucket, fed by raw samples and rollups
+#[derive(Debug, Clone, Default)]
+struct MetricTotals {
+    samples: usize,
+    sum: f64,
+}
+
+impl MetricTotals {
+    fn add_sample(&mut self, value: f64) {
+        self.samples += 1;
+        self.sum += value;
+    }
+
+    fn add_rollup(&mut self, rollup: &MetricRollup) {
+        self.samples += rollup.samples;
+        self.sum += rollup.avg * rollup.samples as f64;
+    }
+
+    fn avg(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.sum / self.samples as f64
+        }
+    }
+}
+
+/// Values of one metric, each with the number of seconds it stands for
+#[derive(Debug, Clone)]
+struct WeightedValues {
+    values: Vec<(f64, f64)>,
+    samples: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Default for WeightedValues {
+    fn default() -> Self {
+        WeightedValues {
+            values: Vec::new(),
+            samples: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl WeightedValues {
+    fn add(&mut self, value: f64, min: f64, max: f64, weight: f64) {
+        self.values.push((value, weight));
+        self.samples += 1;
+        self.min = self.min.min(min);
+        self.max = self.max.max(max);
+    }
+
+    fn add_rollup(&mut self, rollup: &MetricRollup, weight: f64) {
+        self.add(rollup.avg, rollup.min, rollup.max, weight);
+        self.samples += rollup.samples.saturating_sub(1);
+    }
+
+    /// Returns None when there are no values
+    fn statistics(&self, threshold: Option<f64>, period_seconds: f64) -> Option<MetricStatistics> {
+        let total: f64 = self.values.iter().map(|(_, w)| w).sum();
+        if self.values.is_empty() || total <= 0.0 {
+            return None;
+        }
+
+        let avg = self.values.iter().map(|(v, w)| v * w).sum::<f64>() / total;
+        let variance = self
+            .values
+            .iter()
+            .map(|(v, w)| w * (v - avg).powi(2))
+            .sum::<f64>()
+            / total;
+
+        let mut sorted = self.values.clone();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let percentile = |pct: f64| {
+            let target = total * pct / 100.0;
+            let mut cumulative = 0.0;
+            for (value, weight) in &sorted {
+                cumulative += weight;
+                if cumulative >= target {
+                    return *value;
+                }
+            }
+            sorted[sorted.len() - 1].0
+        };
 
-    // Query metrics
-    let metrics = query_metrics(conn, start, end)?;
+        let above_threshold = threshold.map(|threshold| {
+            let seconds = self
+                .values
+                .iter()
+                .filter(|(v, _)| *v > threshold)
+                .fold(0.0, |total, (_, w)| total + w);
+            ThresholdTime {
+                threshold,
+                minutes: seconds / 60.0,
+                percent_of_period: (seconds / period_seconds * 100.0).min(100.0),
+            }
+        });
+
+        Some(MetricStatistics {
+            samples: self.samples,
+            min: self.min,
+            avg,
+            max: self.max,
+            std_dev: variance.sqrt(),
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            above_threshold,
+        })
+    }
+}
+
+/// Seconds each sample stands for: half the gap to each neighbour, with each
+/// gap capped at `max_gap` (edge samples mirror their only neighbour)
+fn sample_weights(times: &[DateTime<Utc>], max_gap: f64) -> Vec<f64> {
+    let gaps: Vec<f64> = times
+        .windows(2)
+        .map(|pair| ((pair[1] - pair[0]).num_seconds() as f64).min(max_gap))
+        .collect();
+    if gaps.is_empty() {
+        return vec![max_gap; times.len()];
+    }
+
+    (0..times.len())
+        .map(|i| {
+            let before = if i == 0 { gaps[0] } else { gaps[i - 1] };
+            let after = gaps.get(i).copied().unwrap_or(before);
+            ((before + after) / 2.0).max(1.0)
+        })
+        .collect()
+}
+
+/// End of the part of `start..first_bucket` that is read from raw samples
+/// Rollups only cover whole buckets; none when raw reading already starts at `start`
+fn raw_head(
+    start: DateTime<Utc>,
+    first_bucket: DateTime<Utc>,
+    raw_from: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    (start < first_bucket && first_bucket <= raw_from).then_some(first_bucket)
+}
+
+/// Pick the finest data tier that covers the range starting at `start`
+/// A tier covers the range unless a coarser tier still holds buckets from
+/// before the tier's oldest data (i.e. it has been cleaned up past `start`)
+fn select_resolution(conn: &Connection, start: DateTime<Utc>) -> Result<Resolution> {
+    const TIERS: [Resolution; 3] = [Resolution::Raw, Resolution::Hourly, Resolution::Daily];
 
-    let metrics_report = if metrics.is_empty() {
+    for (index, resolution) in TIERS.iter().enumerate() {
+        let Some(earliest) = earliest_data(conn, *resolution)? else {
+            continue;
+        };
+        if earliest <= start {
+            return Ok(*resolution);
+        }
+
+        let mut older_elsewhere = false;
+        for coarser in &TIERS[index + 1..] {
+            let before = earliest - Duration::seconds(coarser.bucket_seconds());
+            if rollups_exist_between(conn, *coarser, bucket_start(start, *coarser), before)? {
+                older_elsewhere = true;
+                break;
+            }
+        }
+        if !older_elsewhere {
+            return Ok(*resolution);
+        }
+    }
+
+    Ok(Resolution::Raw)
+}
+
+/// Generate a comprehensive system report over `start..end` using default options
+pub fn generate_report(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<(MetricsReport, LogReport, SystemChecksReport)> {
+    generate_report_between(conn, start, end, &ReportOptions::default())
+}
+
+/// Generate a comprehensive system report over `start..end`
+/// Statistics are time-weighted: a sample stands for half the time to each
+/// neighbour, at most 1.5 collection intervals, so bursts of samples (e.g.
+/// from watch mode) do not outweigh sparse scheduled runs
+pub fn generate_report_between(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    options: &ReportOptions,
+) -> Result<(MetricsReport, LogReport, SystemChecksReport)> {
+    let max_weight = options.collection_interval.num_seconds().max(1) as f64 * 1.5;
+
+    // Long ranges are read from rollups once raw samples have been cleaned up;
+    // raw data newer than the last built bucket fills in the rest. Rollups are
+    // built from this host's rows only, so other hosts are always read raw
+    let host = &options.host;
+    let resolution = if host.is_local() {
+        select_resolution(conn, start)?
+    } else {
+        Resolution::Raw
+    };
+    let mut series: BTreeMap<&str, WeightedValues> = BTreeMap::new();
+    let mut log_counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut raw_head_end = None;
+    let raw_from = if resolution == Resolution::Raw {
+        start
+    } else {
+        let first_bucket = first_bucket_from(start, resolution);
+        for rollup in query_metric_rollups(conn, resolution, first_bucket, end)? {
+            if let Some(metric) = [METRIC_CPU, METRIC_MEMORY, METRIC_DISK, METRIC_LOAD, METRIC_PROCESSES]
+                .into_iter()
+                .find(|m| *m == rollup.metric)
+            {
+                let weight = (rollup.samples as f64 * max_weight)
+                    .min(resolution.bucket_seconds() as f64);
+                series.entry(metric).or_default().add_rollup(&rollup, weight);
+            }
+        }
+        for rollup in query_log_rollups(conn, resolution, first_bucket, end)? {
+            *log_counts.entry(rollup.level).or_insert(0) += rollup.count;
+        }
+        let raw_from =
+            rollup_built_until(conn, resolution)?.map_or(start, |until| until.max(start));
+        raw_head_end = raw_head(start, first_bucket, raw_from);
+        raw_from
+    };
+    let is_raw =
+        |time: DateTime<Utc>| time >= raw_from || raw_head_end.is_some_and(|head| time < head);
+
+    // Query metrics (newest first)
+    let mut samples = query_metrics(conn, raw_from, end, host)?;
+    if let Some(head_end) = raw_head_end {
+        samples.extend(query_metrics(conn, start, head_end - Duration::seconds(1), host)?);
+    }
+    samples.reverse();
+    let times: Vec<DateTime<Utc>> = samples.iter().map(|s| s.timestamp).collect();
+    for (sample, weight) in samples.iter().zip(sample_weights(&times, max_weight)) {
+        for (metric, value) in metric_values(sample) {
+            series.entry(metric).or_default().add(value, value, value, weight);
+        }
+    }
+
+    let period_seconds = (end - start).num_seconds().max(1) as f64;
+    let thresholds = [
+        (METRIC_CPU, Some(CPU_WARNING_THRESHOLD as f64)),
+        (METRIC_MEMORY, Some(MEMORY_WARNING_THRESHOLD as f64)),
+        (METRIC_DISK, Some(DISK_WARNING_THRESHOLD as f64)),
+        (METRIC_LOAD, Some(LOAD_WARNING_THRESHOLD)),
+        (METRIC_PROCESSES, None),
+    ];
+    let statistics: BTreeMap<String, MetricStatistics> = thresholds
+        .into_iter()
+        .filter_map(|(metric, threshold)| {
+            let values = series.get(metric)?;
+            Some((metric.to_string(), values.statistics(threshold, period_seconds)?))
+        })
+        .collect();
+
+    let metrics_report = if !statistics.contains_key(METRIC_CPU) {
         MetricsReport {
             period_start: start,
             period_end: end,
@@ -67,66 +956,56 @@ pub fn generate_report(
             max_disk_used_percent: 0.0,
             avg_process_count: 0,
             max_load_avg_1min: 0.0,
-            issues: vec!["No data available for the specified time period".to_string()],
+            issues: vec![MetricIssue::new(
+                Severity::Info,
+                "No data available for the specified time period".to_string(),
+            )],
+            resolution,
+            statistics,
         }
     } else {
+        let stat = |metric: &str, value: fn(&MetricStatistics) -> f64| {
+            statistics.get(metric).map_or(0.0, value)
+        };
+
         // Calculate statistics
-        let count = metrics.len() as f32;
-        let avg_cpu = metrics.iter().map(|m| m.cpu_usage).sum::<f32>() / count;
-        let max_cpu = metrics.iter().map(|m| m.cpu_usage).fold(0.0f32, f32::max);
+        let avg_cpu = stat(METRIC_CPU, |s| s.avg) as f32;
+        let max_cpu = stat(METRIC_CPU, |s| s.max) as f32;
 
-        let avg_mem_pct = metrics
-            .iter()
-            .map(|m| (m.memory_used as f32 / m.memory_total as f32) * 100.0)
-            .sum::<f32>()
-            / count;
-        let max_mem_pct = metrics
-            .iter()
-            .map(|m| (m.memory_used as f32 / m.memory_total as f32) * 100.0)
-            .fold(0.0f32, f32::max);
+        let avg_mem_pct = stat(METRIC_MEMORY, |s| s.avg) as f32;
+        let max_mem_pct = stat(METRIC_MEMORY, |s| s.max) as f32;
 
-        let avg_disk_pct = metrics
-            .iter()
-            .map(|m| (m.disk_used as f32 / m.disk_total as f32) * 100.0)
-            .sum::<f32>()
-            / count;
-        let max_disk_pct = metrics
-            .iter()
-            .map(|m| (m.disk_used as f32 / m.disk_total as f32) * 100.0)
-            .fold(0.0f32, f32::max);
+        let avg_disk_pct = stat(METRIC_DISK, |s| s.avg) as f32;
+        let max_disk_pct = stat(METRIC_DISK, |s| s.max) as f32;
 
-        let avg_proc =
-            (metrics.iter().map(|m| m.process_count).sum::<usize>() as f32 / count) as usize;
-        let max_load = metrics
-            .iter()
-            .map(|m| m.load_avg_1min)
-            .fold(0.0f64, f64::max);
+        let avg_proc = stat(METRIC_PROCESSES, |s| s.avg).round() as usize;
+        let max_load = stat(METRIC_LOAD, |s| s.max);
 
         // Identify issues
         let mut issues = Vec::new();
 
         if max_cpu > CPU_WARNING_THRESHOLD {
-            issues.push(format!(
-                "⚠️  HIGH CPU USAGE: Peak CPU usage reached {:.1}%",
-                max_cpu
+            issues.push(MetricIssue::new(
+                Severity::Warning,
+                format!("HIGH CPU USAGE: Peak CPU usage reached {:.1}%", max_cpu),
             ));
         }
         if max_mem_pct > MEMORY_WARNING_THRESHOLD {
-            issues.push(format!(
-                "⚠️  HIGH MEMORY USAGE: Peak memory usage reached {:.1}%",
-                max_mem_pct
+            issues.push(MetricIssue::new(
+                Severity::Warning,
+                format!("HIGH MEMORY USAGE: Peak memory usage reached {:.1}%", max_mem_pct),
             ));
         }
         if max_disk_pct > DISK_WARNING_THRESHOLD {
-            issues.push(format!(
-                "⚠️  HIGH DISK USAGE: Disk usage reached {:.1}%",
-                max_disk_pct
+            issues.push(MetricIssue::new(
+                Severity::Warning,
+                format!("HIGH DISK USAGE: Disk usage reached {:.1}%", max_disk_pct),
             ));
         }
         if max_load > LOAD_WARNING_THRESHOLD {
-            issues.push(format!(
-                "⚠️  HIGH LOAD: System load average reached {:.2}",
-                max_load
+            issues.push(MetricIssue::new(
+                Severity::Warning,
+                format!("HIGH LOAD: System load average reached {:.2}", max_load),
             ));
         }
 
@@ -142,15 +1021,22 @@ pub fn generate_report(
             avg_process_count: avg_proc,
             max_load_avg_1min: max_load,
             issues,
+            resolution,
+            statistics,
         }
     };
 
     // Query logs
-    let all_logs = query_logs(conn, start, end, None)?;
+    let all_logs = query_logs(conn, start, end, None, host)?;
+
+    for entry in all_logs.iter().filter(|l| is_raw(l.timestamp)) {
+        *log_counts.entry(entry.level.clone()).or_insert(0) += 1;
+    }
+    let level_count = |level: &str| log_counts.get(level).copied().unwrap_or(0);
 
-    let total_errors = all_logs.iter().filter(|l| l.level == "ERROR").count();
-    let total_warnings = all_logs.iter().filter(|l| l.level == "WARNING").count();
-    let total_critical = all_logs.iter().filter(|l| l.level == "CRITICAL").count();
+    let total_errors = level_count("ERROR");
+    let total_warnings = level_count("WARNING");
+    let total_critical = level_count("CRITICAL");
 
     let recent_errors: Vec<LogEntry> = all_logs
         .iter()
@@ -167,21 +1053,14 @@ pub fn generate_report(
     };
 
     // Query system checks
-    let system_checks = query_system_checks(conn, start, end).unwrap_or_else(|_| Vec::new());
+    let system_checks =
+        query_system_checks(conn, start, end, host).unwrap_or_else(|_| Vec::new());
 
-    let checks_ok = system_checks.iter().filter(|c| c.status == "ok").count();
-    let checks_warning = system_checks
-        .iter()
-        .filter(|c| c.status == "warning")
-        .count();
-    let checks_critical = system_checks
-        .iter()
-        .filter(|c| c.status == "critical")
-        .count();
-    let checks_error = system_checks
-        .iter()
-        .filter(|c| c.status == "error")
-        .count();
+    let count = |status: CheckStatus| system_checks.iter().filter(|c| c.status == status).count();
+    let checks_ok = count(CheckStatus::Ok);
+    let checks_warning = count(CheckStatus::Warning);
+    let checks_critical = count(CheckStatus::Critical);
+    let checks_error = count(CheckStatus::Error);
 
     let system_checks_report = SystemChecksReport {
         total_checks: system_checks.len(),
@@ -201,6 +1080,19 @@ pub fn format_report(
     logs: &LogReport,
     system_checks: &SystemChecksReport,
 ) -> String {
+    format_full_report(&FullReport::new(
+        metrics.clone(),
+        logs.clone(),
+        system_checks.clone(),
+    ))
+}
+
+/// Format a report with all of its sections for terminal display
+pub fn format_full_report(report: &FullReport) -> String {
+    let metrics = &report.metrics;
+    let logs = &report.logs;
+    let system_checks = &report.system_checks;
+    let timezone = report.timezone;
     let mut output = String::new();
 
     output.push_str("╔════════════════════════════════════════════════════════════════╗\n");
@@ -210,15 +1102,25 @@ pub fn format_report(
     ));
     output.push_str("╚════════════════════════════════════════════════════════════════╝\n\n");
 
-    // Convert to local time for display
-    let local_start: DateTime<Local> = metrics.period_start.into();
-    let local_end: DateTime<Local> = metrics.period_end.into();
+    // Convert to the display time zone
+    let local_start = timezone.convert(metrics.period_start);
+    let local_end = timezone.convert(metrics.period_end);
 
     output.push_str(&format!(
-        "Report Period: {} to {}\n\n",
+        "Report Period: {} to {}\n",
         local_start.format("%Y-%m-%d %H:%M:%S %Z"),
         local_end.format("%Y-%m-%d %H:%M:%S %Z")
     ));
+    if let Some(host) = report.shown_host() {
+        output.push_str(&format!("Host: {}\n", host));
+    }
+    if metrics.resolution != Resolution::Raw {
+        output.push_str(&format!(
+            "Data Resolution: {} rollups (older raw samples have been cleaned up)\n",
+            metrics.resolution.as_str()
+        ));
+    }
+    output.push('\n');
 
     output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     output.push_str("  SYSTEM METRICS\n");
@@ -226,7 +1128,8 @@ pub fn format_report(
 
     output.push_str("CPU Usage:\n");
     output.push_str(&format!("  Average: {:.1}%\n", metrics.avg_cpu_usage));
-    output.push_str(&format!("  Peak:    {:.1}%\n\n", metrics.max_cpu_usage));
+    output.push_str(&format!("  Peak:    {:.1}%\n", metrics.max_cpu_usage));
+    output.push_str(&format_distribution(metrics.statistics.get(METRIC_CPU), "%", 1));
 
     output.push_str("Memory Usage:\n");
     output.push_str(&format!(
@@ -234,9 +1137,10 @@ pub fn format_report(
         metrics.avg_memory_used_percent
     ));
     output.push_str(&format!(
-        "  Peak:    {:.1}%\n\n",
+        "  Peak:    {:.1}%\n",
         metrics.max_memory_used_percent
     ));
+    output.push_str(&format_distribution(metrics.statistics.get(METRIC_MEMORY), "%", 1));
 
     output.push_str("Disk Usage:\n");
     output.push_str(&format!(
@@ -244,15 +1148,20 @@ pub fn format_report(
         metrics.avg_disk_used_percent
     ));
     output.push_str(&format!(
-        "  Peak:    {:.1}%\n\n",
+        "  Peak:    {:.1}%\n",
         metrics.max_disk_used_percent
     ));
+    output.push_str(&format_distribution(metrics.statistics.get(METRIC_DISK), "%", 1));
 
     output.push_str("System Load:\n");
+    if let Some(load) = metrics.statistics.get(METRIC_LOAD) {
+        output.push_str(&format!("  Average: {:.2}\n", load.avg));
+    }
     output.push_str(&format!(
-        "  Peak (1-min avg): {:.2}\n\n",
+        "  Peak (1-min avg): {:.2}\n",
         metrics.max_load_avg_1min
     ));
+    output.push_str(&format_distribution(metrics.statistics.get(METRIC_LOAD), "", 2));
 
     output.push_str(&format!(
         "Average Process Count: {}\n\n",
@@ -270,15 +1179,20 @@ pub fn format_report(
     if !logs.recent_errors.is_empty() {
         output.push_str("Recent Critical/Error Messages (up to 10):\n");
         for (i, entry) in logs.recent_errors.iter().enumerate() {
-            let local_time: DateTime<Local> = entry.timestamp.into();
+            let local_time = timezone.convert(entry.timestamp);
             output.push_str(&format!(
                 "  {}. [{}] {} ({})\n      {}\n",
                 i + 1,
                 entry.level,
                 local_time.format("%Y-%m-%d %H:%M:%S"),
                 entry.source,
-                entry.message.chars().take(100).collect::<String>()
+                entry.headline().chars().take(100).collect::<String>()
             ));
+            // Multi-line events (stack traces) are collapsed to their headline
+            let trace_lines = entry.continuation_lines();
+            if trace_lines > 0 {
+                output.push_str(&format!("      [+{} trace lines collapsed]\n", trace_lines));
+            }
         }
         output.push('\n');
     }
@@ -304,36 +1218,33 @@ pub fn format_report(
         for check in &system_checks.checks {
             check_types
                 .entry(check.check_name.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(check);
         }
 
         for (check_name, checks) in check_types.iter() {
-            // Show most recent check for each type
-            if let Some(latest_check) = checks.last() {
-                let status_icon = match latest_check.status.as_str() {
-                    "ok" => "✓",
-                    "warning" => "⚠️",
-                    "critical" => "🔴",
-                    "error" => "✗",
-                    _ => "•",
+            // Show most recent check for each type (checks are newest first)
+            if let Some(latest_check) = checks.first() {
+                let status_icon = match latest_check.status {
+                    CheckStatus::Ok => "✓",
+                    CheckStatus::Warning => "⚠️",
+                    CheckStatus::Critical => "🔴",
+                    CheckStatus::Error => "✗",
+                    CheckStatus::Unknown => "•",
                 };
 
-                let local_time: DateTime<Local> = latest_check.timestamp.into();
+                let local_time = timezone.convert(latest_check.timestamp);
                 output.push_str(&format!(
                     "{} {} [{}] ({})\n",
                     status_icon,
                     check_name,
-                    latest_check.status.to_uppercase(),
+                    latest_check.status.as_str().to_uppercase(),
                     local_time.format("%Y-%m-%d %H:%M:%S")
                 ));
                 output.push_str(&format!("   {}\n", latest_check.message));
 
                 // If there are warnings/critical/errors, add them to issues
-                if latest_check.status == "warning"
-                    || latest_check.status == "critical"
-                    || latest_check.status == "error"
-                {
+                if latest_check.status.is_problem() {
                     output.push('\n');
                 }
             }
@@ -341,6 +1252,26 @@ pub fn format_report(
         output.push('\n');
     }
 
+    if let Some(ref forecast) = report.disk_forecast {
+        output.push_str(&format_forecast_section(forecast, timezone));
+    }
+
+    if let Some(ref anomalies) = report.anomalies {
+        output.push_str(&format_anomaly_section(anomalies, timezone));
+    }
+
+    if let Some(ref collection) = report.collection {
+        output.push_str(&format_collection_section(collection, timezone));
+    }
+
+    if let Some(ref kernel) = report.kernel {
+        output.push_str(&format_kernel_section(kernel, timezone));
+    }
+
+    if let Some(ref security) = report.security {
+        output.push_str(&format_security_section(security, timezone));
+    }
+
     if !metrics.issues.is_empty() {
         output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
         output.push_str("  ⚠️  ISSUES DETECTED\n");
@@ -355,30 +1286,378 @@ pub fn format_report(
     output.push_str("  RECOMMENDATIONS\n");
     output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
 
-    let mut recommendations = Vec::new();
+    if report.recommendations.is_empty() {
+        output.push_str("✓ System appears healthy - no immediate action required\n\n");
+    } else {
+        for rec in &report.recommendations {
+            output.push_str(&format!("{} {} [{}]\n", rec.severity.icon(), rec.finding, rec.id));
+            output.push_str(&format!("   → {}\n", rec.action));
+            for command in &rec.commands {
+                output.push_str(&format!("     $ {}\n", command));
+            }
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Min, percentiles, spread and time above threshold under a metric's
+/// average and peak lines; ends the metric's block with a blank line
+fn format_distribution(stats: Option<&MetricStatistics>, unit: &str, decimals: usize) -> String {
+    let Some(stats) = stats else {
+        return "\n".to_string();
+    };
+    let value = |v: f64| format!("{:.*}{}", decimals, v, unit);
+
+    let mut output = format!("  Min:     {}\n", value(stats.min));
+    output.push_str(&format!(
+        "  p50/p90/p95/p99: {} / {} / {} / {}\n",
+        value(stats.p50),
+        value(stats.p90),
+        value(stats.p95),
+        value(stats.p99)
+    ));
+    output.push_str(&format!("  Std Dev: {}\n", value(stats.std_dev)));
+    if let Some(ref above) = stats.above_threshold {
+        output.push_str(&format!(
+            "  Above {}: {:.0} min ({:.1}% of period)\n",
+            value(above.threshold),
+            above.minutes,
+            above.percent_of_period
+        ));
+    }
+    output.push('\n');
+    output
+}
 
-    if metrics.max_cpu_usage > CPU_WARNING_THRESHOLD {
-        recommendations.push("• Investigate high CPU usage - check for runaway processes");
+/// One-line description of a kernel event
+pub(crate) fn describe_kernel_event(event: &KernelEvent) -> String {
+    let process = match (&event.process, event.pid) {
+        (Some(name), Some(pid)) => format!("{} (pid {})", name, pid),
+        (Some(name), None) => name.clone(),
+        _ => "unknown process".to_string(),
+    };
+    let detail = |key: &str| event.details.as_ref().and_then(|d| d.get(key)).cloned();
+
+    match event.event_type.as_str() {
+        EVENT_OOM_KILL => match detail("anon_rss_kb").and_then(|v| v.as_u64()) {
+            Some(rss_kb) => format!("{} killed by OOM killer ({} MB resident)", process, rss_kb / 1024),
+            None => format!("{} killed by OOM killer", process),
+        },
+        EVENT_SEGFAULT => format!(
+            "{} segfault at ip {} error {}",
+            process,
+            detail("ip").and_then(|v| v.as_str().map(String::from)).unwrap_or_default(),
+            detail("error_code").map(|v| v.to_string()).unwrap_or_default()
+        ),
+        EVENT_IO_ERROR => match detail("sector").and_then(|v| v.as_u64()) {
+            Some(sector) => format!(
+                "I/O error on {} (sector {})",
+                event.device.as_deref().unwrap_or("?"),
+                sector
+            ),
+            None => format!("I/O error on {}", event.device.as_deref().unwrap_or("?")),
+        },
+        EVENT_FS_ERROR => {
+            let read_only = detail("remounted_read_only").and_then(|v| v.as_bool()) == Some(true);
+            format!(
+                "{} error on {}{}",
+                detail("filesystem").and_then(|v| v.as_str().map(String::from)).unwrap_or_default(),
+                event.device.as_deref().unwrap_or("?"),
+                if read_only { " - remounted read-only" } else { "" }
+            )
+        }
+        EVENT_BOOT => match detail("kernel").and_then(|v| v.as_str().map(String::from)) {
+            Some(kernel) => format!("System booted (kernel {})", kernel),
+            None => "System booted".to_string(),
+        },
+        _ => event.message.chars().take(100).collect(),
     }
-    if metrics.max_memory_used_percent > MEMORY_WARNING_THRESHOLD {
-        recommendations
-            .push("• Memory usage is high - consider freeing up memory or adding more RAM");
+}
+
+/// When a mount point is projected to be full, with the 95% range
+/// e.g. "in 12.3 days (9.8 days to 16.0 days, 2025-11-17)"
+pub(crate) fn describe_fill_time(mount: &DiskForecast, timezone: DisplayTimezone) -> String {
+    let Some(ref full) = mount.full else {
+        return "never at the current trend".to_string();
+    };
+    if full.days == 0.0 {
+        return "now".to_string();
     }
-    if metrics.max_disk_used_percent > DISK_WARNING_THRESHOLD {
-        recommendations.push("• Disk space is running low - clean up old files or expand storage");
+    let latest = full
+        .latest_days
+        .map_or("never".to_string(), |days| format!("{:.1} days", days));
+    let local_at = timezone.convert(full.at);
+    format!(
+        "in {:.1} days ({:.1} days to {}, {})",
+        full.days,
+        full.earliest_days,
+        latest,
+        local_at.format("%Y-%m-%d")
+    )
+}
+
+/// Size in bytes as MB or GB with one decimal
+pub(crate) fn format_size(bytes: f64) -> String {
+    const MB: f64 = 1_048_576.0;
+    const GB: f64 = 1_073_741_824.0;
+    if bytes.abs() >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else {
+        format!("{:.1} MB", bytes / MB)
     }
-    if logs.total_critical > 0 {
-        recommendations.push("• Critical issues found in logs - review system logs immediately");
+}
+
+/// Format the disk fill forecast section
+fn format_forecast_section(forecast: &DiskForecastReport, timezone: DisplayTimezone) -> String {
+    let mut output = String::new();
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  DISK FORECAST\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+    output.push_str(&format!(
+        "Trend over the last {:.0} days; mounts full within {:.0} days are flagged\n\n",
+        forecast.lookback_days, forecast.horizon_days
+    ));
+    if forecast.mounts.is_empty() {
+        output.push_str("Not enough samples per mount point yet\n\n");
+        return output;
     }
-    if logs.total_errors > ERROR_COUNT_THRESHOLD {
-        recommendations.push("• Multiple errors detected - review system logs for patterns");
+
+    for mount in &forecast.mounts {
+        output.push_str(&format!(
+            "{} {} ({}): {:.1}% of {} used\n",
+            if mount.flagged { "🔴" } else { "✓" },
+            mount.mount_point,
+            mount.filesystem,
+            mount.used_percent,
+            format_size(mount.total_bytes as f64)
+        ));
+        output.push_str(&format!(
+            "   Growth: {}/day (95%: {} to {}), confidence {} (R² {:.2}, {} samples)\n",
+            format_size(mount.growth_per_day),
+            format_size(mount.growth_per_day_low),
+            format_size(mount.growth_per_day_high),
+            mount.confidence.as_str(),
+            mount.r_squared,
+            mount.samples
+        ));
+        if let Some(ref warning) = mount.warning {
+            let local_at = timezone.convert(warning.at);
+            output.push_str(&format!(
+                "   {:.0}% reached: {}\n",
+                warning.percent,
+                if warning.days == 0.0 {
+                    "already".to_string()
+                } else {
+                    format!("in {:.1} days ({})", warning.days, local_at.format("%Y-%m-%d"))
+                }
+            ));
+        }
+        output.push_str(&format!("   Full: {}\n", describe_fill_time(mount, timezone)));
     }
+    output.push('\n');
 
-    if recommendations.is_empty() {
-        output.push_str("✓ System appears healthy - no immediate action required\n\n");
-    } else {
-        for rec in recommendations {
-            output.push_str(&format!("{}\n", rec));
+    output
+}
+
+/// Format the anomaly section
+fn format_anomaly_section(anomalies: &AnomalyReport, timezone: DisplayTimezone) -> String {
+    let mut output = String::new();
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  ANOMALIES\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+    let built = timezone.convert(anomalies.baseline_built_at);
+    output.push_str(&format!(
+        "Baseline: median ± {:.1} MAD per hour of week, {} slots, built {}\n\n",
+        anomalies.mad_multiplier,
+        anomalies.baseline_slots,
+        built.format("%Y-%m-%d %H:%M")
+    ));
+    if anomalies.anomalies.is_empty() {
+        output.push_str("✓ All samples within their usual range\n\n");
+        return output;
+    }
+    for anomaly in &anomalies.anomalies {
+        output.push_str(&format!("📈 {}\n", describe_anomaly(anomaly, timezone)));
+    }
+    output.push('\n');
+
+    output
+}
+
+/// Format the collection health section
+fn format_collection_section(collection: &CollectionReport, timezone: DisplayTimezone) -> String {
+    let mut output = String::new();
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  COLLECTION HEALTH\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+    output.push_str(&format!(
+        "Runs: {} ({} ok, {} partial, {} failed, {} interrupted)\n",
+        collection.total_runs,
+        collection.ok_runs,
+        collection.partial_runs,
+        collection.failed_runs,
+        collection.interrupted_runs
+    ));
+    output.push_str(&format!(
+        "Expected Interval: every {} minutes\n",
+        collection.interval_minutes
+    ));
+    output.push_str(&format!("Missed Runs: {}\n\n", collection.missed_runs()));
+
+    if !collection.gaps.is_empty() {
+        output.push_str("🔴 Gaps in Collection:\n");
+        for gap in &collection.gaps {
+            let from = timezone.convert(gap.from);
+            let to = timezone.convert(gap.to);
+            output.push_str(&format!(
+                "  • {} to {} (~{} missed run{})\n",
+                from.format("%Y-%m-%d %H:%M"),
+                to.format("%Y-%m-%d %H:%M"),
+                gap.missed_runs,
+                if gap.missed_runs == 1 { "" } else { "s" }
+            ));
+        }
+        output.push('\n');
+    }
+
+    if !collection.failures.is_empty() {
+        output.push_str("⚠️  Failed or Partial Runs:\n");
+        for failure in &collection.failures {
+            let local_time = timezone.convert(failure.started_at);
+            output.push_str(&format!(
+                "  • {} [{}]\n",
+                local_time.format("%Y-%m-%d %H:%M:%S"),
+                failure.status
+            ));
+            for error in &failure.errors {
+                output.push_str(&format!("      {}\n", error));
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Format the kernel events section
+fn format_kernel_section(kernel: &KernelReport, timezone: DisplayTimezone) -> String {
+    let mut output = String::new();
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  KERNEL EVENTS\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+    output.push_str(&format!("OOM Kills:         {}\n", kernel.count(EVENT_OOM_KILL)));
+    output.push_str(&format!("Segfaults:         {}\n", kernel.count(EVENT_SEGFAULT)));
+    output.push_str(&format!("Hardware Errors:   {}\n", kernel.count(EVENT_HARDWARE_ERROR)));
+    output.push_str(&format!("I/O Errors:        {}\n", kernel.count(EVENT_IO_ERROR)));
+    output.push_str(&format!("Filesystem Errors: {}\n", kernel.count(EVENT_FS_ERROR)));
+    output.push_str(&format!("Reboots:           {}\n\n", kernel.count(EVENT_BOOT)));
+
+    output.push_str("Recent Kernel Events:\n");
+    for (i, event) in kernel.recent_events.iter().enumerate() {
+        let local_time = timezone.convert(event.timestamp);
+        output.push_str(&format!(
+            "  {}. [{}] {} - {}\n",
+            i + 1,
+            event.event_type,
+            local_time.format("%Y-%m-%d %H:%M:%S"),
+            describe_kernel_event(event)
+        ));
+    }
+    output.push('\n');
+
+    output
+}
+
+/// Format the SSH/sudo security section
+fn format_security_section(security: &SecurityReport, timezone: DisplayTimezone) -> String {
+    let mut output = String::new();
+
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    output.push_str("  SECURITY (SSH & SUDO)\n");
+    output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+    output.push_str(&format!(
+        "Failed Logins:   {} ({} invalid user attempts)\n",
+        security.failed_logins, security.invalid_user_attempts
+    ));
+    output.push_str(&format!("Accepted Logins: {}\n", security.accepted_logins));
+    output.push_str(&format!(
+        "Sudo Commands:   {} ({} rejected)\n\n",
+        security.sudo_commands, security.sudo_failures
+    ));
+
+    if !security.top_attacking_ips.is_empty() {
+        output.push_str("Top Attacking IPs:\n");
+        for ip in &security.top_attacking_ips {
+            output.push_str(&format!(
+                "  {:<40} {} failed, {} invalid user\n",
+                ip.source_ip, ip.failed_logins, ip.invalid_users
+            ));
+        }
+        output.push('\n');
+    }
+
+    if !security.brute_force_bursts.is_empty() {
+        output.push_str(&format!(
+            "🔴 Brute-Force Bursts ({}+ failures, at most {}s apart):\n",
+            BRUTE_FORCE_MIN_ATTEMPTS, BRUTE_FORCE_MAX_GAP_SECONDS
+        ));
+        for burst in &security.brute_force_bursts {
+            let local_start = timezone.convert(burst.start);
+            let local_end = timezone.convert(burst.end);
+            output.push_str(&format!(
+                "  {} - {} attempts ({} to {})\n",
+                burst.source_ip,
+                burst.attempts,
+                local_start.format("%Y-%m-%d %H:%M:%S"),
+                local_end.format("%H:%M:%S")
+            ));
+        }
+        output.push('\n');
+    }
+
+    // Older auth events are pruned, so "first-time" only covers the kept history
+    let history = security
+        .history_since
+        .map(|since| {
+            format!(" (no earlier record since {})", timezone.convert(since).format("%Y-%m-%d"))
+        })
+        .unwrap_or_default();
+
+    if !security.new_login_sources.is_empty() {
+        output.push_str(&format!("⚠️  First-Time Login Sources{}:\n", history));
+        for source in &security.new_login_sources {
+            let local_time = timezone.convert(source.first_seen);
+            output.push_str(&format!(
+                "  {} from {} ({}) at {}\n",
+                source.username,
+                source.source_ip,
+                source.method.as_deref().unwrap_or("unknown method"),
+                local_time.format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+        output.push('\n');
+    }
+
+    if !security.new_sudo_users.is_empty() {
+        output.push_str(&format!("⚠️  New Sudo Activity{}:\n", history));
+        for activity in &security.new_sudo_users {
+            output.push_str(&format!(
+                "  {} ran {} command(s), first: {}\n",
+                activity.username,
+                activity.commands,
+                activity.first_command.as_deref().unwrap_or("(unknown)")
+            ));
         }
         output.push('\n');
     }
@@ -393,6 +1672,64 @@ pub struct FullReport {
     pub metrics: MetricsReport,
     pub logs: LogReport,
     pub system_checks: SystemChecksReport,
+    /// SSH/sudo analysis, present when auth events were recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<SecurityReport>,
+    /// Kernel events, present when any were recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<KernelReport>,
+    /// Collection run health, present once syswriter has recorded runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<CollectionReport>,
+    /// Disk fill forecast, present once per-mount usage has been recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_forecast: Option<DiskForecastReport>,
+    /// Samples outside their learned baseline, present once baselines are built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomalies: Option<AnomalyReport>,
+    /// Suggested actions for the findings, most severe first
+    pub recommendations: Vec<Recommendation>,
+    /// Host the report covers: "local", a merged host's name or "all"
+    pub host: String,
+    /// Metric averages over the period for charts (not included in JSON)
+    #[serde(skip)]
+    pub series: Vec<MetricPoint>,
+    /// Time zone the formatted report shows timestamps in
+    #[serde(skip)]
+    pub timezone: DisplayTimezone,
+}
+
+impl FullReport {
+    /// Combine the core report sections; optional sections start empty and
+    /// recommendations are made from the core sections
+    pub fn new(
+        metrics: MetricsReport,
+        logs: LogReport,
+        system_checks: SystemChecksReport,
+    ) -> Self {
+        let mut report = FullReport {
+            version: crate::VERSION.to_string(),
+            metrics,
+            logs,
+            system_checks,
+            security: None,
+            kernel: None,
+            collection: None,
+            disk_forecast: None,
+            anomalies: None,
+            recommendations: Vec::new(),
+            host: HostFilter::Local.to_string(),
+            series: Vec::new(),
+            timezone: DisplayTimezone::default(),
+        };
+        report.recommendations = recommend(&report, ERROR_COUNT_THRESHOLD);
+        report
+    }
+
+    /// Host named in the report headers; None for this machine's own rows
+    pub fn shown_host(&self) -> Option<&str> {
+        (self.host != HostFilter::Local.to_string()).then_some(self.host.as_str())
+    }
 }
 
 /// Export format for reports
@@ -400,13 +1737,21 @@ pub struct FullReport {
 pub enum ExportFormat {
     Json,
     Text,
+    /// Single self-contained page with inline CSS and SVG charts
+    Html,
+    /// GitHub/GitLab flavoured Markdown for issues, wikis and chat
+    Markdown,
 }
 
-impl ExportFormat {
-    pub fn from_str(s: &str) -> Result<Self> {
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
         match s.to_lowercase().as_str() {
             "json" => Ok(ExportFormat::Json),
             "text" | "txt" => Ok(ExportFormat::Text),
+            "html" | "htm" => Ok(ExportFormat::Html),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
             _ => Err(anyhow::anyhow!("Unsupported format: {}", s)),
         }
     }
@@ -419,18 +1764,19 @@ pub fn export_report(
     system_checks: &SystemChecksReport,
     format: ExportFormat,
 ) -> Result<String> {
+    let full_report = FullReport::new(metrics.clone(), logs.clone(), system_checks.clone());
+    export_full_report(&full_report, format)
+}
+
+/// Export a report with all of its sections in the specified format
+pub fn export_full_report(report: &FullReport, format: ExportFormat) -> Result<String> {
     match format {
         ExportFormat::Json => {
-            let full_report = FullReport {
-                version: crate::VERSION.to_string(),
-                metrics: metrics.clone(),
-                logs: logs.clone(),
-                system_checks: system_checks.clone(),
-            };
-            serde_json::to_string_pretty(&full_report)
-                .context("Failed to serialize report to JSON")
+            serde_json::to_string_pretty(report).context("Failed to serialize report to JSON")
         }
-        ExportFormat::Text => Ok(format_report(metrics, logs, system_checks)),
+        ExportFormat::Text => Ok(format_full_report(report)),
+        ExportFormat::Html => Ok(format_html_report(report)),
+        ExportFormat::Markdown => Ok(format_markdown_report(report)),
     }
 }
 
@@ -448,6 +1794,8 @@ impl Clone for MetricsReport {
             avg_process_count: self.avg_process_count,
             max_load_avg_1min: self.max_load_avg_1min,
             issues: self.issues.clone(),
+            resolution: self.resolution,
+            statistics: self.statistics.clone(),
         }
     }
 }
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use log::debug;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+
+use crate::db::{
+    earliest_data, query_metrics, rollup_built_until, rollup_log_counts, set_rollup_built_until,
+    upsert_metric_rollup, HostFilter, MetricRollup, Resolution, SystemMetrics,
+};
+
+/// Metric names used in the rollup tables
+pub const METRIC_CPU: &str = "cpu_usage";
+pub const METRIC_MEMORY: &str = "memory_used_percent";
+pub const METRIC_DISK: &str = "disk_used_percent";
+pub const METRIC_LOAD: &str = "load_avg_1min";
+pub const METRIC_PROCESSES: &str = "process_count";
+
+/// Values of a sample for each rolled-up metric
+pub fn metric_values(metrics: &SystemMetrics) -> [(&'static str, f64); 5] {
+    [
+        (METRIC_CPU, metrics.cpu_usage as f64),
+        (
+            METRIC_MEMORY,
+            metrics.memory_used as f64 / metrics.memory_total as f64 * 100.0,
+        ),
+        (
+            METRIC_DISK,
+            metrics.disk_used as f64 / metrics.disk_total as f64 * 100.0,
+        ),
+        (METRIC_LOAD, metrics.load_avg_1min),
+        (METRIC_PROCESSES, metrics.process_count as f64),
+    ]
+}
+
+/// Nearest-rank percentile of a set of values (0.0 for no values)
+pub fn percentile(values: &[f64], pct: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Start of the bucket containing a timestamp
+pub fn bucket_start(timestamp: DateTime<Utc>, resolution: Resolution) -> DateTime<Utc> {
+    let width = resolution.bucket_seconds();
+    if width == 0 {
+        return timestamp;
+    }
+    let ts = timestamp.timestamp();
+    Utc.timestamp_opt(ts - ts.rem_euclid(width), 0)
+        .single()
+        .unwrap_or(timestamp)
+}
+
+/// Start of the first bucket that lies entirely at or after `start`
+/// The bucket containing `start` also holds samples from before it, so
+/// readers take the range up to this point from raw samples instead
+pub fn first_bucket_from(start: DateTime<Utc>, resolution: Resolution) -> DateTime<Utc> {
+    let bucket = bucket_start(start, resolution);
+    if bucket < start {
+        bucket + Duration::seconds(resolution.bucket_seconds())
+    } else {
+        bucket
+    }
+}
+
+fn summarize(
+    resolution: Resolution,
+    bucket: DateTime<Utc>,
+    metric: &str,
+    values: &[f64],
+) -> MetricRollup {
+    MetricRollup {
+        resolution,
+        bucket_start: bucket,
+        metric: metric.to_string(),
+        samples: values.len(),
+        avg: values.iter().sum::<f64>() / values.len() as f64,
+        min: values.iter().copied().fold(f64::INFINITY, f64::min),
+        max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        p95: percentile(values, 95.0),
+    }
+}
+
+/// Build rollups for every completed bucket since the last run
+/// Returns the number of buckets that had data
+fn build_resolution(conn: &Connection, resolution: Resolution, now: DateTime<Utc>) -> Result<usize> {
+    let complete_until = bucket_start(now, resolution);
+
+    let from = match rollup_built_until(conn, resolution)? {
+        Some(until) => until,
+        None => match earliest_data(conn, Resolution::Raw)? {
+            Some(earliest) => bucket_start(earliest, resolution),
+            None => return Ok(0),
+        },
+    };
+    if from >= complete_until {
+        return Ok(0);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+
+    // query_metrics includes its end bound, the bucket end is exclusive
+    let samples =
+        query_metrics(&tx, from, complete_until - Duration::seconds(1), &HostFilter::Local)?;
+    let mut buckets: BTreeMap<DateTime<Utc>, BTreeMap<&'static str, Vec<f64>>> = BTreeMap::new();
+    for sample in &samples {
+        let values = buckets
+            .entry(bucket_start(sample.timestamp, resolution))
+            .or_default();
+        for (metric, value) in metric_values(sample) {
+            values.entry(metric).or_default().push(value);
+        }
+    }
+
+    for (bucket, metrics) in &buckets {
+        for (metric, values) in metrics {
+            upsert_metric_rollup(&tx, &summarize(resolution, *bucket, metric, values))?;
+        }
+    }
+
+    let log_rows = rollup_log_counts(&tx, resolution, from, complete_until)?;
+    set_rollup_built_until(&tx, resolution, complete_until)?;
+    tx.commit()?;
+
+    debug!(
+        "Built {} {} metric buckets and {} log count rows",
+        buckets.len(),
+        resolution.as_str(),
+        log_rows
+    );
+    Ok(buckets.len())
+}
+
+/// Incrementally build hourly and daily rollups from raw data
+/// Only completed buckets are built, so this must run before raw data is
+/// cleaned up. Log entries inserted later with timestamps in an already built
+/// bucket are not counted in the rollups.
+/// Returns the number of (hourly, daily) metric buckets written
+pub fn build_rollups(conn: &Connection, now: DateTime<Utc>) -> Result<(usize, usize)> {
+    let hourly = build_resolution(conn, Resolution::Hourly, now)?;
+    let daily = build_resolution(conn, Resolution::Daily, now)?;
+    Ok((hourly, daily))
+}
@@ -1,6 +1,8 @@
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
+use serde::Serialize;
 use std::process::Command;
+use sysinfo::System;
 
 /// Information about an external tool
 #[derive(Debug, Clone)]
@@ -90,7 +92,7 @@ pub fn print_missing_tools_info() {
 }
 
 /// Result of a package update check
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PackageUpdateInfo {
     pub total_packages: usize,
     pub updates_available: usize,
@@ -107,7 +109,7 @@ pub fn check_apt_updates() -> Result<PackageUpdateInfo> {
     // Update package lists quietly
     debug!("Updating apt package lists...");
     let update_result = Command::new("apt")
-        .args(&["update", "-qq"])
+        .args(["update", "-qq"])
         .output()
         .context("Failed to run apt update")?;
 
@@ -117,7 +119,7 @@ pub fn check_apt_updates() -> Result<PackageUpdateInfo> {
 
     // Check for upgradable packages
     let output = Command::new("apt")
-        .args(&["list", "--upgradable"])
+        .args(["list", "--upgradable"])
         .output()
         .context("Failed to run apt list --upgradable")?;
 
@@ -152,7 +154,7 @@ pub fn check_dnf_updates() -> Result<PackageUpdateInfo> {
     }
 
     let output = Command::new("dnf")
-        .args(&["check-update", "-q"])
+        .args(["check-update", "-q"])
         .output()
         .context("Failed to run dnf check-update")?;
 
@@ -163,7 +165,7 @@ pub fn check_dnf_updates() -> Result<PackageUpdateInfo> {
 
     // Check for security updates
     let security_output = Command::new("dnf")
-        .args(&["updateinfo", "list", "security", "-q"])
+        .args(["updateinfo", "list", "security", "-q"])
         .output()
         .context("Failed to run dnf updateinfo")?;
 
@@ -192,7 +194,7 @@ pub fn check_package_updates() -> Result<PackageUpdateInfo> {
 }
 
 /// SMART disk health status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DiskHealthInfo {
     pub device: String,
     pub health_status: String,
@@ -211,7 +213,7 @@ pub fn check_disk_health() -> Result<Vec<DiskHealthInfo>> {
 
     // Get list of disks
     let output = Command::new("lsblk")
-        .args(&["-d", "-n", "-o", "NAME,TYPE"])
+        .args(["-d", "-n", "-o", "NAME,TYPE"])
         .output()
         .context("Failed to list block devices")?;
 
@@ -232,7 +234,7 @@ pub fn check_disk_health() -> Result<Vec<DiskHealthInfo>> {
 
         // This requires sudo, so it might fail
         let output = Command::new("smartctl")
-            .args(&["-H", "-A", &disk])
+            .args(["-H", "-A", &disk])
             .output();
 
         if let Ok(output) = output {
@@ -252,7 +254,7 @@ pub fn check_disk_health() -> Result<Vec<DiskHealthInfo>> {
                     health_status,
                     temperature: None,
                     power_on_hours: None,
-                    reallocated_sectors: None,
+                    reallocated_sectors: parse_reallocated_sectors(&stdout),
                 });
             }
         } else {
@@ -263,8 +265,50 @@ pub fn check_disk_health() -> Result<Vec<DiskHealthInfo>> {
     Ok(results)
 }
 
+/// Raw value of the Reallocated_Sector_Ct attribute in `smartctl -A` output
+pub fn parse_reallocated_sectors(smartctl_output: &str) -> Option<u64> {
+    let line = smartctl_output
+        .lines()
+        .find(|line| line.contains("Reallocated_Sector_Ct"))?;
+    // ID NAME FLAG VALUE WORST THRESH TYPE UPDATED WHEN_FAILED RAW_VALUE
+    let raw = line.split_whitespace().nth(9)?;
+    raw.chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+/// Swap space usage
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapUsageInfo {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+}
+
+impl SwapUsageInfo {
+    /// Used share of the swap space, 0 when there is none
+    pub fn used_percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.used_bytes as f64 / self.total_bytes as f64 * 100.0
+        }
+    }
+}
+
+/// Check swap usage (needs no external tool)
+pub fn check_swap_usage() -> SwapUsageInfo {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    SwapUsageInfo {
+        total_bytes: sys.total_swap(),
+        used_bytes: sys.used_swap(),
+    }
+}
+
 /// Systemd service status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SystemdServiceStatus {
     pub total_services: usize,
     pub active_services: usize,
@@ -280,7 +324,7 @@ pub fn check_systemd_status() -> Result<SystemdServiceStatus> {
 
     // Get failed services
     let output = Command::new("systemctl")
-        .args(&["--failed", "--no-pager", "--plain", "--no-legend"])
+        .args(["--failed", "--no-pager", "--plain", "--no-legend"])
         .output()
         .context("Failed to run systemctl --failed")?;
 
@@ -300,7 +344,7 @@ pub fn check_systemd_status() -> Result<SystemdServiceStatus> {
 
     // Get total service count
     let list_output = Command::new("systemctl")
-        .args(&["list-units", "--type=service", "--all", "--no-pager", "--plain", "--no-legend"])
+        .args(["list-units", "--type=service", "--all", "--no-pager", "--plain", "--no-legend"])
         .output()
         .context("Failed to list services")?;
 
@@ -308,7 +352,7 @@ pub fn check_systemd_status() -> Result<SystemdServiceStatus> {
     let total_services = list_stdout.lines().count();
 
     let active_output = Command::new("systemctl")
-        .args(&["list-units", "--type=service", "--state=active", "--no-pager", "--plain", "--no-legend"])
+        .args(["list-units", "--type=service", "--state=active", "--no-pager", "--plain", "--no-legend"])
         .output()
         .context("Failed to list active services")?;
 
@@ -324,7 +368,7 @@ pub fn check_systemd_status() -> Result<SystemdServiceStatus> {
 }
 
 /// Top directories by size
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DirectorySizeInfo {
     pub path: String,
     pub size_bytes: u64,
@@ -338,7 +382,7 @@ pub fn find_large_directories(base_path: &str, depth: usize, limit: usize) -> Re
     }
 
     let output = Command::new("du")
-        .args(&[
+        .args([
             "-d",
             &depth.to_string(),
             "-x", // Don't cross filesystem boundaries
@@ -373,7 +417,7 @@ pub fn find_large_directories(base_path: &str, depth: usize, limit: usize) -> Re
         .collect();
 
     // Sort by size descending
-    dirs.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.size_bytes));
     dirs.truncate(limit);
 
     Ok(dirs)
@@ -0,0 +1,594 @@
+use crate::config::{
+    CPU_WARNING_THRESHOLD, DISK_WARNING_THRESHOLD, LOAD_WARNING_THRESHOLD, MEMORY_WARNING_THRESHOLD,
+};
+use crate::db::{
+    for_each_log_entry, for_each_metric, query_kernel_events, query_system_checks, CheckStatus,
+    HostFilter, LogEntry,
+};
+use crate::kernel::{
+    EVENT_BOOT, EVENT_FS_ERROR, EVENT_HARDWARE_ERROR, EVENT_IO_ERROR, EVENT_OOM_KILL,
+    EVENT_SEGFAULT,
+};
+use crate::reporter::{describe_kernel_event, ExportFormat};
+use crate::rollup::{metric_values, METRIC_CPU, METRIC_DISK, METRIC_LOAD, METRIC_MEMORY};
+use crate::timezone::DisplayTimezone;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Error and critical log entries that make a burst
+const LOG_BURST_MIN_ENTRIES: usize = 5;
+/// Largest gap between two entries of the same burst
+const LOG_BURST_GAP_SECONDS: i64 = 120;
+/// How far before the period the previous check results are looked up,
+/// so the first result in the period can be compared with something
+const CHECK_HISTORY_DAYS: i64 = 1;
+
+/// Metrics whose threshold crossings are events: name, label, threshold, unit and decimals
+const THRESHOLDS: [(&str, &str, f64, &str, usize); 4] = [
+    (METRIC_CPU, "CPU", CPU_WARNING_THRESHOLD as f64, "%", 1),
+    (
+        METRIC_MEMORY,
+        "Memory",
+        MEMORY_WARNING_THRESHOLD as f64,
+        "%",
+        1,
+    ),
+    (METRIC_DISK, "Disk", DISK_WARNING_THRESHOLD as f64, "%", 1),
+    (METRIC_LOAD, "Load", LOAD_WARNING_THRESHOLD, "", 2),
+];
+
+/// How serious a timeline event is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Severity::Info => "•",
+            Severity::Warning => "⚠️",
+            Severity::Critical => "🔴",
+        }
+    }
+}
+
+/// Where a timeline event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A metric went above its warning threshold
+    Threshold,
+    /// A burst of error log entries, or a single critical one
+    Log,
+    /// A system check changed status
+    CheckChange,
+    Reboot,
+    /// OOM kill, segfault or hardware, I/O or filesystem error
+    Kernel,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Threshold => "metric",
+            EventKind::Log => "logs",
+            EventKind::CheckChange => "check",
+            EventKind::Reboot => "reboot",
+            EventKind::Kernel => "kernel",
+        }
+    }
+}
+
+/// One thing that happened, from any of the collected sources
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEvent {
+    pub timestamp: DateTime<Utc>,
+    /// Last sample or entry of events that lasted a while
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    pub kind: EventKind,
+    pub severity: Severity,
+    /// Short form used in incident stories, e.g. "memory hit 98%"
+    pub label: String,
+    pub description: String,
+    /// Number of the incident the event belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incident: Option<usize>,
+}
+
+impl TimelineEvent {
+    fn new(
+        timestamp: DateTime<Utc>,
+        kind: EventKind,
+        severity: Severity,
+        label: String,
+        description: String,
+    ) -> Self {
+        TimelineEvent {
+            timestamp,
+            end: None,
+            kind,
+            severity,
+            label,
+            description,
+            incident: None,
+        }
+    }
+
+    fn last_seen(&self) -> DateTime<Utc> {
+        self.end.unwrap_or(self.timestamp)
+    }
+}
+
+/// Events close together in time, as a candidate story
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub id: usize,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub severity: Severity,
+    pub events: usize,
+    /// Event labels in order, e.g. "memory hit 98% → OOM killed java → Systemd Services critical"
+    pub story: String,
+}
+
+/// All events of a period in chronological order, grouped into incidents
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeline {
+    pub version: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Largest gap between events of one incident
+    pub gap_minutes: i64,
+    pub incidents: Vec<Incident>,
+    pub events: Vec<TimelineEvent>,
+    /// Host the timeline covers: "local", a merged host's name or "all"
+    pub host: String,
+    /// Time zone the formatted timeline shows timestamps in
+    #[serde(skip)]
+    pub timezone: DisplayTimezone,
+}
+
+/// Merge a host's threshold crossings, log bursts, check changes, reboots and
+/// kernel events of `start..end` into one timeline
+/// Events at most `gap` apart form an incident once there are two of them
+pub fn build_timeline(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+    gap: Duration,
+    timezone: DisplayTimezone,
+) -> Result<Timeline> {
+    let mut events = threshold_events(conn, start, end, host)?;
+    events.extend(log_events(conn, start, end, host)?);
+    events.extend(check_events(conn, start, end, host)?);
+    events.extend(kernel_events(conn, start, end, host)?);
+    events.sort_by_key(|e| e.timestamp);
+
+    let incidents = group_incidents(&mut events, gap);
+
+    Ok(Timeline {
+        version: crate::VERSION.to_string(),
+        period_start: start,
+        period_end: end,
+        gap_minutes: gap.num_minutes(),
+        incidents,
+        events,
+        host: host.to_string(),
+        timezone,
+    })
+}
+
+/// Samples of one metric above its threshold
+struct Crossing {
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+    peak: f64,
+    samples: usize,
+}
+
+/// One event per stretch of samples above a metric's warning threshold
+fn threshold_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<TimelineEvent>> {
+    let mut open: HashMap<&str, Crossing> = HashMap::new();
+    let mut crossings = Vec::new();
+    for_each_metric(conn, start, end, host, |_, sample| {
+        for (metric, value) in metric_values(&sample) {
+            let Some(&(_, _, threshold, _, _)) = THRESHOLDS.iter().find(|t| t.0 == metric) else {
+                continue;
+            };
+            if value > threshold {
+                let crossing = open.entry(metric).or_insert(Crossing {
+                    first: sample.timestamp,
+                    last: sample.timestamp,
+                    peak: value,
+                    samples: 0,
+                });
+                crossing.last = sample.timestamp;
+                crossing.peak = crossing.peak.max(value);
+                crossing.samples += 1;
+            } else if let Some(crossing) = open.remove(metric) {
+                crossings.push((metric, crossing));
+            }
+        }
+        Ok(())
+    })
+    .context("Failed to read metrics for the timeline")?;
+    crossings.extend(open);
+
+    Ok(crossings
+        .into_iter()
+        .filter_map(
+            |(
+                metric,
+                Crossing {
+                    first,
+                    last,
+                    peak,
+                    samples,
+                },
+            )| {
+                let &(_, label, threshold, unit, decimals) =
+                    THRESHOLDS.iter().find(|t| t.0 == metric)?;
+                let mut event = TimelineEvent::new(
+                    first,
+                    EventKind::Threshold,
+                    Severity::Warning,
+                    format!("{} hit {:.*}{}", label.to_lowercase(), decimals, peak, unit),
+                    format!(
+                        "{} above {:.*}{} (peak {:.*}{}, {} sample{})",
+                        label,
+                        decimals,
+                        threshold,
+                        unit,
+                        decimals,
+                        peak,
+                        unit,
+                        samples,
+                        if samples == 1 { "" } else { "s" }
+                    ),
+                );
+                if last > first {
+                    event.end = Some(last);
+                }
+                Some(event)
+            },
+        )
+        .collect())
+}
+
+/// Bursts of error and critical log entries, and critical entries outside a burst
+fn log_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<TimelineEvent>> {
+    let mut clusters: Vec<Vec<LogEntry>> = Vec::new();
+    for_each_log_entry(conn, start, end, host, |_, entry| {
+        if entry.level != "ERROR" && entry.level != "CRITICAL" {
+            return Ok(());
+        }
+        match clusters.last_mut() {
+            Some(cluster)
+                if (entry.timestamp - cluster[cluster.len() - 1].timestamp).num_seconds()
+                    <= LOG_BURST_GAP_SECONDS =>
+            {
+                cluster.push(entry)
+            }
+            _ => clusters.push(vec![entry]),
+        }
+        Ok(())
+    })
+    .context("Failed to read logs for the timeline")?;
+
+    let mut events = Vec::new();
+    for cluster in clusters {
+        if cluster.len() < LOG_BURST_MIN_ENTRIES {
+            for entry in cluster.iter().filter(|e| e.level == "CRITICAL") {
+                let headline: String = entry.headline().chars().take(100).collect();
+                events.push(TimelineEvent::new(
+                    entry.timestamp,
+                    EventKind::Log,
+                    Severity::Critical,
+                    format!("critical log in {}", entry.source),
+                    format!("CRITICAL ({}): {}", entry.source, headline),
+                ));
+            }
+            continue;
+        }
+
+        let first = &cluster[0];
+        let last = &cluster[cluster.len() - 1];
+        let mut sources: BTreeMap<&str, usize> = BTreeMap::new();
+        for entry in &cluster {
+            *sources.entry(entry.source.as_str()).or_default() += 1;
+        }
+        let top_source = sources
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map_or("", |(source, _)| *source);
+        let critical = cluster.iter().any(|e| e.level == "CRITICAL");
+        let minutes = (last.timestamp - first.timestamp).num_minutes().max(1);
+        let headline: String = first.headline().chars().take(100).collect();
+
+        let mut event = TimelineEvent::new(
+            first.timestamp,
+            EventKind::Log,
+            if critical {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            },
+            format!("{} log errors", cluster.len()),
+            format!(
+                "{} error/critical log entries in {} min, mostly {}: {}",
+                cluster.len(),
+                minutes,
+                top_source,
+                headline
+            ),
+        );
+        event.end = Some(last.timestamp);
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+/// Each time a check's status differs from its previous result
+/// A check whose first known result is a problem counts as a change too
+fn check_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<TimelineEvent>> {
+    let history_start = start - Duration::days(CHECK_HISTORY_DAYS);
+    let mut checks = query_system_checks(conn, history_start, end, host)
+        .context("Failed to read system checks for the timeline")?;
+    checks.reverse();
+
+    let mut previous: HashMap<String, CheckStatus> = HashMap::new();
+    let mut events = Vec::new();
+    for check in checks {
+        let before = previous.insert(check.check_name.clone(), check.status);
+        let changed = match before {
+            Some(status) => status != check.status,
+            None => check.status.is_problem(),
+        };
+        if !changed || check.timestamp < start {
+            continue;
+        }
+
+        let severity = match check.status {
+            CheckStatus::Critical | CheckStatus::Error => Severity::Critical,
+            CheckStatus::Warning => Severity::Warning,
+            CheckStatus::Ok | CheckStatus::Unknown => Severity::Info,
+        };
+        let transition = match before {
+            Some(status) => format!("{} -> {}", status, check.status),
+            None => check.status.to_string(),
+        };
+        events.push(TimelineEvent::new(
+            check.timestamp,
+            EventKind::CheckChange,
+            severity,
+            format!("{} {}", check.check_name, check.status),
+            format!("{}: {} - {}", check.check_name, transition, check.message),
+        ));
+    }
+
+    Ok(events)
+}
+
+/// Reboots, OOM kills, segfaults and hardware, I/O and filesystem errors
+fn kernel_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    host: &HostFilter,
+) -> Result<Vec<TimelineEvent>> {
+    let events = query_kernel_events(conn, start, end, host)
+        .context("Failed to read kernel events for the timeline")?;
+
+    Ok(events
+        .iter()
+        .map(|event| {
+            let process = event.process.as_deref().unwrap_or("a process");
+            let device = event.device.as_deref().unwrap_or("a device");
+            let (kind, severity, label) = match event.event_type.as_str() {
+                EVENT_BOOT => (EventKind::Reboot, Severity::Warning, "reboot".to_string()),
+                EVENT_OOM_KILL => (
+                    EventKind::Kernel,
+                    Severity::Critical,
+                    format!("OOM killed {}", process),
+                ),
+                EVENT_SEGFAULT => (
+                    EventKind::Kernel,
+                    Severity::Warning,
+                    format!("{} segfault", process),
+                ),
+                EVENT_HARDWARE_ERROR => (
+                    EventKind::Kernel,
+                    Severity::Critical,
+                    "hardware error".to_string(),
+                ),
+                EVENT_IO_ERROR => (
+                    EventKind::Kernel,
+                    Severity::Critical,
+                    format!("I/O error on {}", device),
+                ),
+                EVENT_FS_ERROR => (
+                    EventKind::Kernel,
+                    Severity::Critical,
+                    format!("filesystem error on {}", device),
+                ),
+                other => (EventKind::Kernel, Severity::Warning, other.to_string()),
+            };
+            TimelineEvent::new(
+                event.timestamp,
+                kind,
+                severity,
+                label,
+                describe_kernel_event(event),
+            )
+        })
+        .collect())
+}
+
+/// Number the runs of two or more events that are at most `gap` apart
+fn group_incidents(events: &mut [TimelineEvent], gap: Duration) -> Vec<Incident> {
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    let mut group_start = 0;
+    let mut last_seen: Option<DateTime<Utc>> = None;
+    for (index, event) in events.iter().enumerate() {
+        if last_seen.is_some_and(|seen| event.timestamp - seen > gap) {
+            groups.push((group_start, index));
+            group_start = index;
+            last_seen = None;
+        }
+        last_seen = Some(last_seen.map_or(event.last_seen(), |seen| seen.max(event.last_seen())));
+    }
+    groups.push((group_start, events.len()));
+
+    let mut incidents = Vec::new();
+    for (from, to) in groups.into_iter().filter(|(from, to)| to - from >= 2) {
+        let id = incidents.len() + 1;
+        let members = &mut events[from..to];
+        let mut story: Vec<&str> = Vec::new();
+        for event in members.iter() {
+            if story.last() != Some(&event.label.as_str()) {
+                story.push(&event.label);
+            }
+        }
+        let incident = Incident {
+            id,
+            start: members[0].timestamp,
+            end: members
+                .iter()
+                .map(|e| e.last_seen())
+                .max()
+                .unwrap_or(members[0].timestamp),
+            severity: members
+                .iter()
+                .map(|e| e.severity)
+                .max()
+                .unwrap_or(Severity::Info),
+            events: members.len(),
+            story: story.join(" → "),
+        };
+        for event in members.iter_mut() {
+            event.incident = Some(id);
+        }
+        incidents.push(incident);
+    }
+
+    incidents
+}
+
+/// Export a timeline as text or JSON
+pub fn export_timeline(timeline: &Timeline, format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(timeline).context("Failed to serialize timeline to JSON")
+        }
+        ExportFormat::Text => Ok(format_timeline(timeline)),
+        _ => Err(anyhow!("Timelines support the text and json formats")),
+    }
+}
+
+/// Format a timeline as a chronological list with incidents framed
+pub fn format_timeline(timeline: &Timeline) -> String {
+    let mut output = String::new();
+
+    output.push_str("╔════════════════════════════════════════════════════════════════╗\n");
+    output.push_str(&format!(
+        "║         SYSTERS v{:<6} - INCIDENT TIMELINE               ║\n",
+        timeline.version
+    ));
+    output.push_str("╚════════════════════════════════════════════════════════════════╝\n\n");
+
+    let local_start = timeline.timezone.convert(timeline.period_start);
+    let local_end = timeline.timezone.convert(timeline.period_end);
+    output.push_str(&format!(
+        "Period: {} to {}\n",
+        local_start.format("%Y-%m-%d %H:%M:%S %Z"),
+        local_end.format("%Y-%m-%d %H:%M:%S %Z")
+    ));
+    if timeline.host != HostFilter::Local.to_string() {
+        output.push_str(&format!("Host: {}\n", timeline.host));
+    }
+    output.push_str(&format!(
+        "{} events, {} incidents (events at most {} minutes apart)\n\n",
+        timeline.events.len(),
+        timeline.incidents.len(),
+        timeline.gap_minutes
+    ));
+
+    if timeline.events.is_empty() {
+        output.push_str("✓ Nothing happened in this period\n");
+        return output;
+    }
+
+    let mut current: Option<usize> = None;
+    for event in &timeline.events {
+        if event.incident != current {
+            if current.is_some() {
+                output.push_str("└\n");
+            }
+            if let Some(incident) = event
+                .incident
+                .and_then(|id| timeline.incidents.iter().find(|i| i.id == id))
+            {
+                let start = timeline.timezone.convert(incident.start);
+                let end = timeline.timezone.convert(incident.end);
+                output.push_str(&format!(
+                    "┌ INCIDENT {} {} {} - {} ({} events)\n",
+                    incident.id,
+                    incident.severity.icon(),
+                    start.format("%Y-%m-%d %H:%M"),
+                    end.format("%H:%M"),
+                    incident.events
+                ));
+                output.push_str(&format!("│ {}\n", incident.story));
+            }
+            current = event.incident;
+        }
+
+        let time = timeline.timezone.convert(event.timestamp);
+        output.push_str(&format!(
+            "{}{}  {} {:<7} {}\n",
+            if current.is_some() { "│ " } else { "  " },
+            time.format("%Y-%m-%d %H:%M:%S"),
+            event.severity.icon(),
+            event.kind.as_str(),
+            event.description
+        ));
+    }
+    if current.is_some() {
+        output.push_str("└\n");
+    }
+
+    output
+}
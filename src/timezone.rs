@@ -0,0 +1,75 @@
+//! Time zone that reports show timestamps in and read wall-clock times in
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Time zone for report timestamps and for local dates in time ranges
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DisplayTimezone {
+    /// The system's local time zone
+    #[default]
+    Local,
+    /// A fixed UTC offset such as +05:30
+    Fixed(FixedOffset),
+    /// An IANA time zone such as Europe/Berlin, following daylight saving time
+    Named(Tz),
+}
+
+impl DisplayTimezone {
+    /// `time` as wall-clock time in this zone
+    pub fn convert(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            DisplayTimezone::Local => time.with_timezone(&Local).fixed_offset(),
+            DisplayTimezone::Fixed(offset) => time.with_timezone(offset),
+            DisplayTimezone::Named(tz) => time.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// The instant a wall-clock time in this zone refers to; the earlier one
+    /// when clocks go back, none when they skip it
+    pub fn resolve(&self, naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            DisplayTimezone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+            DisplayTimezone::Fixed(offset) => offset
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+            DisplayTimezone::Named(tz) => tz
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl std::str::FromStr for DisplayTimezone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(DisplayTimezone::Local);
+        }
+        if s.eq_ignore_ascii_case("utc") || s == "Z" {
+            return Ok(DisplayTimezone::Named(Tz::UTC));
+        }
+        if s.starts_with(['+', '-']) {
+            return s
+                .parse::<FixedOffset>()
+                .map(DisplayTimezone::Fixed)
+                .map_err(|_| {
+                    anyhow::anyhow!("Invalid UTC offset '{}'. Use the form +02:00 or -05:30", s)
+                });
+        }
+        s.parse::<Tz>().map(DisplayTimezone::Named).map_err(|_| {
+            anyhow::anyhow!(
+                "Unknown time zone '{}'. Use local, UTC, an offset like +02:00 or an IANA name like Europe/Berlin",
+                s
+            )
+        })
+    }
+}
@@ -0,0 +1,653 @@
+use crate::db::{
+    query_logs, query_metrics, query_system_checks, CheckStatus, HostFilter, LogEntry,
+    SystemCheckResult, SystemMetrics,
+};
+use crate::rollup::{metric_values, METRIC_CPU, METRIC_DISK, METRIC_LOAD, METRIC_MEMORY};
+use crate::timezone::DisplayTimezone;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Sparkline, SparklineBar,
+    Table, TableState, Tabs,
+};
+use ratatui::Frame;
+use rusqlite::Connection;
+use std::collections::BTreeSet;
+
+/// Charted metrics: name, label and unit
+const CHARTS: [(&str, &str, &str); 4] = [
+    (METRIC_CPU, "CPU", "%"),
+    (METRIC_MEMORY, "Memory", "%"),
+    (METRIC_DISK, "Disk", "%"),
+    (METRIC_LOAD, "Load", ""),
+];
+/// Log levels from least to most severe
+const LEVELS: [&str; 3] = ["WARNING", "ERROR", "CRITICAL"];
+/// Shortest span the charts zoom in to
+const MIN_VIEW_MINUTES: i64 = 60;
+/// Logs shown on each side of the chart cursor after a jump
+const LOG_JUMP_MINUTES: i64 = 15;
+/// Sparkline bars are integers; values are scaled to keep one decimal
+const SPARK_SCALE: f64 = 10.0;
+
+/// Pane shown in the terminal UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Metrics,
+    Logs,
+    Checks,
+}
+
+impl Pane {
+    const ALL: [Pane; 3] = [Pane::Metrics, Pane::Logs, Pane::Checks];
+
+    fn index(self) -> usize {
+        Pane::ALL.iter().position(|p| *p == self).unwrap_or(0)
+    }
+}
+
+/// State of the terminal UI: the loaded history, zoom, cursor and log filters
+/// Kept apart from drawing so it can be driven by key events in tests
+pub struct App {
+    pub pane: Pane,
+    /// Oldest first
+    metrics: Vec<SystemMetrics>,
+    /// Oldest first
+    logs: Vec<LogEntry>,
+    /// Newest first
+    checks: Vec<SystemCheckResult>,
+    sources: Vec<String>,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    /// Time span shown in the charts
+    pub view_start: DateTime<Utc>,
+    pub view_end: DateTime<Utc>,
+    /// Selected time in the charts
+    pub cursor: DateTime<Utc>,
+    /// Index into the charted metrics
+    pub selected_metric: usize,
+    /// Chart width in bars, updated on every draw
+    pub columns: usize,
+    /// Index into `LEVELS` of the least severe level shown
+    pub min_level: usize,
+    pub source: Option<String>,
+    pub search: String,
+    /// Keys go to the search field
+    pub searching: bool,
+    /// Only logs within this range, set by jumping from the charts
+    pub around: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub log_selected: usize,
+    pub check_selected: usize,
+    pub quit: bool,
+    /// Time zone timestamps are shown in
+    pub timezone: DisplayTimezone,
+}
+
+impl App {
+    /// Read a host's metrics, logs and checks of `start..end` with the usual queries
+    pub fn load(
+        conn: &Connection,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        host: &HostFilter,
+    ) -> Result<Self> {
+        let metrics = query_metrics(conn, start, end, host).context("Failed to read metrics")?;
+        let logs = query_logs(conn, start, end, None, host).context("Failed to read logs")?;
+        let checks = query_system_checks(conn, start, end, host)
+            .context("Failed to read system checks")?;
+        Ok(App::new(metrics, logs, checks, start, end))
+    }
+
+    pub fn new(
+        mut metrics: Vec<SystemMetrics>,
+        mut logs: Vec<LogEntry>,
+        mut checks: Vec<SystemCheckResult>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        metrics.sort_by_key(|m| m.timestamp);
+        logs.sort_by_key(|l| l.timestamp);
+        checks.sort_by_key(|c| std::cmp::Reverse(c.timestamp));
+        let sources: BTreeSet<String> = logs.iter().map(|l| l.source.clone()).collect();
+
+        App {
+            pane: Pane::Metrics,
+            metrics,
+            logs,
+            checks,
+            sources: sources.into_iter().collect(),
+            period_start: start,
+            period_end: end,
+            view_start: start,
+            view_end: end,
+            cursor: end,
+            selected_metric: 0,
+            columns: 60,
+            min_level: 0,
+            source: None,
+            search: String::new(),
+            searching: false,
+            around: None,
+            log_selected: 0,
+            check_selected: 0,
+            quit: false,
+            timezone: DisplayTimezone::default(),
+        }
+    }
+
+    /// Time covered by one chart bar
+    fn step(&self) -> Duration {
+        Duration::seconds(
+            ((self.view_end - self.view_start).num_seconds() / self.columns.max(1) as i64).max(1),
+        )
+    }
+
+    /// Chart bar holding a point in time
+    pub fn column_of(&self, time: DateTime<Utc>) -> usize {
+        let offset = (time - self.view_start).num_seconds().max(0) / self.step().num_seconds();
+        (offset as usize).min(self.columns.max(1) - 1)
+    }
+
+    fn visible_metrics(&self) -> &[SystemMetrics] {
+        let from = self
+            .metrics
+            .partition_point(|m| m.timestamp < self.view_start);
+        let to = self
+            .metrics
+            .partition_point(|m| m.timestamp <= self.view_end);
+        &self.metrics[from..to]
+    }
+
+    /// Peak of a metric per chart bar over the visible span; None where there are no samples
+    pub fn buckets(&self, metric: &str) -> Vec<Option<f64>> {
+        let mut buckets = vec![None; self.columns.max(1)];
+        for sample in self.visible_metrics() {
+            let Some((_, value)) = metric_values(sample)
+                .into_iter()
+                .find(|(m, _)| *m == metric)
+            else {
+                continue;
+            };
+            if value.is_finite() {
+                let bucket = &mut buckets[self.column_of(sample.timestamp)];
+                *bucket = Some(bucket.map_or(value, |peak: f64| peak.max(value)));
+            }
+        }
+        buckets
+    }
+
+    /// Move the cursor by whole chart bars, panning the view at its edges
+    pub fn move_cursor(&mut self, bars: i64) {
+        let cursor =
+            (self.cursor + self.step() * bars as i32).clamp(self.period_start, self.period_end);
+        let span = self.view_end - self.view_start;
+        if cursor < self.view_start {
+            self.view_start = cursor;
+            self.view_end = cursor + span;
+        } else if cursor > self.view_end {
+            self.view_end = cursor;
+            self.view_start = cursor - span;
+        }
+        self.cursor = cursor;
+    }
+
+    /// Halve the visible span around the cursor
+    pub fn zoom_in(&mut self) {
+        let span = ((self.view_end - self.view_start) / 2).max(Duration::minutes(MIN_VIEW_MINUTES));
+        self.set_view(self.cursor - span / 2, span);
+    }
+
+    /// Double the visible span around the cursor, up to the whole period
+    pub fn zoom_out(&mut self) {
+        let span = ((self.view_end - self.view_start) * 2).min(self.period_end - self.period_start);
+        self.set_view(self.cursor - span / 2, span);
+    }
+
+    fn set_view(&mut self, start: DateTime<Utc>, span: Duration) {
+        let span = span.min(self.period_end - self.period_start);
+        let start = start.max(self.period_start).min(self.period_end - span);
+        self.view_start = start;
+        self.view_end = start + span;
+    }
+
+    /// Put the cursor on the highest visible value of the selected metric
+    pub fn jump_to_peak(&mut self) {
+        let metric = CHARTS[self.selected_metric].0;
+        let peak = self
+            .visible_metrics()
+            .iter()
+            .filter_map(|m| {
+                metric_values(m)
+                    .into_iter()
+                    .find(|(name, _)| *name == metric)
+                    .map(|(_, value)| (m.timestamp, value))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((timestamp, _)) = peak {
+            self.cursor = timestamp;
+        }
+    }
+
+    /// Show the logs around the cursor
+    pub fn jump_to_logs(&mut self) {
+        let window = Duration::minutes(LOG_JUMP_MINUTES).max(self.step());
+        self.around = Some((self.cursor - window, self.cursor + window));
+        self.pane = Pane::Logs;
+        let logs = self.filtered_logs();
+        self.log_selected = logs
+            .iter()
+            .position(|l| l.timestamp >= self.cursor)
+            .unwrap_or(logs.len().saturating_sub(1));
+    }
+
+    /// Logs passing the level, source, search and time filters, oldest first
+    pub fn filtered_logs(&self) -> Vec<&LogEntry> {
+        let search = self.search.to_lowercase();
+        self.logs
+            .iter()
+            .filter(|l| {
+                LEVELS
+                    .iter()
+                    .position(|level| *level == l.level)
+                    .unwrap_or(0)
+                    >= self.min_level
+            })
+            .filter(|l| {
+                self.source
+                    .as_ref()
+                    .is_none_or(|source| *source == l.source)
+            })
+            .filter(|l| {
+                self.around
+                    .is_none_or(|(from, to)| l.timestamp >= from && l.timestamp <= to)
+            })
+            .filter(|l| {
+                search.is_empty()
+                    || l.message.to_lowercase().contains(&search)
+                    || l.source.to_lowercase().contains(&search)
+            })
+            .collect()
+    }
+
+    pub fn checks(&self) -> &[SystemCheckResult] {
+        &self.checks
+    }
+
+    /// Show only the next more severe levels, wrapping back to all
+    pub fn cycle_level(&mut self) {
+        self.min_level = (self.min_level + 1) % LEVELS.len();
+        self.log_selected = 0;
+    }
+
+    /// Show only the next log source, wrapping back to all
+    pub fn cycle_source(&mut self) {
+        let next = match &self.source {
+            None => 0,
+            Some(source) => self
+                .sources
+                .iter()
+                .position(|s| s == source)
+                .map_or(0, |i| i + 1),
+        };
+        self.source = self.sources.get(next).cloned();
+        self.log_selected = 0;
+    }
+
+    fn clear_filters(&mut self) {
+        self.min_level = 0;
+        self.source = None;
+        self.search.clear();
+        self.around = None;
+        self.log_selected = 0;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        if self.searching {
+            match key.code {
+                KeyCode::Char(c) => self.search.push(c),
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Esc => {
+                    self.search.clear();
+                    self.searching = false;
+                }
+                KeyCode::Enter => self.searching = false,
+                _ => {}
+            }
+            self.log_selected = 0;
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Tab => self.pane = Pane::ALL[(self.pane.index() + 1) % Pane::ALL.len()],
+            KeyCode::BackTab => {
+                self.pane = Pane::ALL[(self.pane.index() + Pane::ALL.len() - 1) % Pane::ALL.len()]
+            }
+            KeyCode::Char('1') => self.pane = Pane::Metrics,
+            KeyCode::Char('2') => self.pane = Pane::Logs,
+            KeyCode::Char('3') => self.pane = Pane::Checks,
+            code => match self.pane {
+                Pane::Metrics => self.metrics_key(code),
+                Pane::Logs => self.logs_key(code),
+                Pane::Checks => self.checks_key(code),
+            },
+        }
+    }
+
+    fn metrics_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(1),
+            KeyCode::Home => self.cursor = self.view_start,
+            KeyCode::End => self.cursor = self.view_end,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected_metric = self.selected_metric.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected_metric = (self.selected_metric + 1).min(CHARTS.len() - 1)
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom_in(),
+            KeyCode::Char('-') => self.zoom_out(),
+            KeyCode::Char('p') => self.jump_to_peak(),
+            KeyCode::Enter => self.jump_to_logs(),
+            _ => {}
+        }
+    }
+
+    fn logs_key(&mut self, code: KeyCode) {
+        let count = self.filtered_logs().len();
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.log_selected = self.log_selected.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.log_selected = (self.log_selected + 1).min(count.saturating_sub(1))
+            }
+            KeyCode::PageUp => self.log_selected = self.log_selected.saturating_sub(20),
+            KeyCode::PageDown => {
+                self.log_selected = (self.log_selected + 20).min(count.saturating_sub(1))
+            }
+            KeyCode::Home => self.log_selected = 0,
+            KeyCode::End => self.log_selected = count.saturating_sub(1),
+            KeyCode::Char('v') => self.cycle_level(),
+            KeyCode::Char('s') => self.cycle_source(),
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Esc | KeyCode::Char('c') => self.clear_filters(),
+            _ => {}
+        }
+    }
+
+    fn checks_key(&mut self, code: KeyCode) {
+        let last = self.checks.len().saturating_sub(1);
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.check_selected = self.check_selected.saturating_sub(1)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.check_selected = (self.check_selected + 1).min(last)
+            }
+            KeyCode::PageUp => self.check_selected = self.check_selected.saturating_sub(20),
+            KeyCode::PageDown => self.check_selected = (self.check_selected + 20).min(last),
+            KeyCode::Home => self.check_selected = 0,
+            KeyCode::End => self.check_selected = last,
+            _ => {}
+        }
+    }
+}
+
+/// Run the terminal UI until the user quits
+pub fn run(mut app: App) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<()> {
+        while !app.quit {
+            terminal
+                .draw(|frame| draw(frame, &mut app))
+                .context("Failed to draw the terminal UI")?;
+            if let Event::Key(key) = event::read().context("Failed to read terminal input")? {
+                app.handle_key(key);
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
+
+/// Draw the pane tabs, the current pane and a key help line
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [tabs_area, body, help_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let title = format!(
+        " systers {} to {} ",
+        app.timezone.convert(app.period_start).format("%Y-%m-%d %H:%M"),
+        app.timezone.convert(app.period_end).format("%Y-%m-%d %H:%M")
+    );
+    let tabs = Tabs::new(["1 Metrics", "2 Logs", "3 Checks"])
+        .select(app.pane.index())
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(tabs, tabs_area);
+
+    let help = match app.pane {
+        Pane::Metrics => {
+            "←/→ move  ↑/↓ metric  +/- zoom  p peak  Enter logs around cursor  Tab pane  q quit"
+        }
+        Pane::Logs if app.searching => "type to search  Enter done  Esc clear",
+        Pane::Logs => "↑/↓ scroll  v level  s source  / search  c clear filters  Tab pane  q quit",
+        Pane::Checks => "↑/↓ scroll  Tab pane  q quit",
+    };
+    frame.render_widget(
+        Paragraph::new(help).style(Style::default().fg(Color::DarkGray)),
+        help_area,
+    );
+
+    match app.pane {
+        Pane::Metrics => draw_metrics(frame, app, body),
+        Pane::Logs => draw_logs(frame, app, body),
+        Pane::Checks => draw_checks(frame, app, body),
+    }
+}
+
+fn draw_metrics(frame: &mut Frame, app: &mut App, area: Rect) {
+    let mut constraints = vec![Constraint::Ratio(1, CHARTS.len() as u32); CHARTS.len()];
+    constraints.push(Constraint::Length(1));
+    let areas = Layout::vertical(constraints).split(area);
+    app.columns = areas[0].width.saturating_sub(2).max(1) as usize;
+    let cursor_column = app.column_of(app.cursor);
+
+    for (index, (metric, label, unit)) in CHARTS.iter().enumerate() {
+        let buckets = app.buckets(metric);
+        let peak = buckets
+            .iter()
+            .flatten()
+            .fold(0.0_f64, |peak, v| peak.max(*v));
+        let at_cursor =
+            buckets[cursor_column].map_or("-".to_string(), |v| format!("{:.1}{}", v, unit));
+        let max = if unit.is_empty() {
+            peak.max(1.0)
+        } else {
+            100.0
+        };
+        let bars: Vec<SparklineBar> = buckets
+            .iter()
+            .enumerate()
+            .map(|(column, value)| {
+                let bar = SparklineBar::from(value.map(|v| (v * SPARK_SCALE).round() as u64));
+                if column == cursor_column {
+                    bar.style(Some(Style::default().fg(Color::Yellow)))
+                } else {
+                    bar
+                }
+            })
+            .collect();
+
+        let border = if index == app.selected_metric {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border)
+            .title(format!(
+                " {}  peak {:.1}{}  cursor {} ",
+                label, peak, unit, at_cursor
+            ));
+        let sparkline = Sparkline::default()
+            .block(block)
+            .data(bars)
+            .max((max * SPARK_SCALE) as u64)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(sparkline, areas[index]);
+    }
+
+    // The label goes left of the marker when it would run off the right edge
+    let label = format!(
+        "{}  ({} per bar)",
+        app.timezone.convert(app.cursor).format("%Y-%m-%d %H:%M"),
+        describe_step(app.step())
+    );
+    let marker = if cursor_column + label.chars().count() + 3 <= app.columns {
+        format!("{}▲ {}", " ".repeat(cursor_column + 1), label)
+    } else {
+        format!("{:>width$}", format!("{} ▲", label), width = cursor_column + 2)
+    };
+    frame.render_widget(Paragraph::new(marker), areas[CHARTS.len()]);
+}
+
+fn draw_logs(frame: &mut Frame, app: &App, area: Rect) {
+    let logs = app.filtered_logs();
+    let mut filters = vec![format!("{}+", LEVELS[app.min_level])];
+    if let Some(ref source) = app.source {
+        filters.push(format!("source {}", source));
+    }
+    if !app.search.is_empty() || app.searching {
+        filters.push(format!("search \"{}\"", app.search));
+    }
+    if let Some((from, to)) = app.around {
+        filters.push(format!(
+            "{} - {}",
+            app.timezone.convert(from).format("%m-%d %H:%M"),
+            app.timezone.convert(to).format("%H:%M")
+        ));
+    }
+
+    let items: Vec<ListItem> = logs
+        .iter()
+        .map(|l| {
+            let color = match l.level.as_str() {
+                "CRITICAL" => Color::Red,
+                "ERROR" => Color::LightRed,
+                _ => Color::Yellow,
+            };
+            let time = app.timezone.convert(l.timestamp);
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{} ", time.format("%m-%d %H:%M:%S"))),
+                Span::styled(format!("{:<8} ", l.level), Style::default().fg(color)),
+                Span::styled(
+                    format!("{} ", l.source),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(l.headline().to_string()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " Logs: {} of {}  [{}] ",
+            logs.len(),
+            app.logs.len(),
+            filters.join(", ")
+        )))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state =
+        ListState::default().with_selected((!logs.is_empty()).then_some(app.log_selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_checks(frame: &mut Frame, app: &App, area: Rect) {
+    let [table_area, detail_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).areas(area);
+
+    let rows: Vec<Row> = app
+        .checks
+        .iter()
+        .map(|c| {
+            let color = match c.status {
+                CheckStatus::Ok => Color::Green,
+                CheckStatus::Warning => Color::Yellow,
+                CheckStatus::Critical => Color::Red,
+                CheckStatus::Error => Color::Magenta,
+                CheckStatus::Unknown => Color::DarkGray,
+            };
+            Row::new(vec![
+                Cell::from(app.timezone.convert(c.timestamp).format("%Y-%m-%d %H:%M").to_string()),
+                Cell::from(c.check_name.clone()),
+                Cell::from(c.status.to_string()).style(Style::default().fg(color)),
+                Cell::from(c.message.clone()),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(20),
+            Constraint::Length(8),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(["Time", "Check", "Status", "Message"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Check history: {} results ", app.checks.len())),
+    )
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state =
+        TableState::default().with_selected((!app.checks.is_empty()).then_some(app.check_selected));
+    frame.render_stateful_widget(table, table_area, &mut state);
+
+    let detail = app
+        .checks
+        .get(app.check_selected)
+        .map_or(String::new(), |c| c.message.clone());
+    frame.render_widget(
+        Paragraph::new(detail)
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL)),
+        detail_area,
+    );
+}
+
+/// Bar width as "5 min" or "2 h"
+fn describe_step(step: Duration) -> String {
+    if step.num_minutes() < 1 {
+        format!("{} s", step.num_seconds())
+    } else if step.num_minutes() < 120 {
+        format!("{} min", step.num_minutes())
+    } else {
+        format!("{} h", step.num_hours())
+    }
+}
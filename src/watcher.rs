@@ -0,0 +1,323 @@
+use anyhow::{anyhow, Context, Result};
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+use log::{debug, info, warn};
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alert::send_alert;
+use crate::collector::parse_log_lines;
+use crate::config::{AlertConfig, LogSourceConfig, WatchConfig};
+use crate::db::{Batch, LogEntry};
+
+/// Delay between checks of the inotify queue when it is empty
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Follows a single log file like `tail -F`
+/// Lines written to a rotated file are read before switching to its
+/// replacement, and a file truncated in place is read again from the start
+pub struct LogTailer {
+    source: LogSourceConfig,
+    file: Option<File>,
+    inode: u64,
+    offset: u64,
+    /// Unterminated last line, kept until the writer finishes it
+    partial: String,
+}
+
+impl LogTailer {
+    /// Start following a source at its current end
+    /// Existing content is left to the regular collection run
+    pub fn new(source: LogSourceConfig) -> Self {
+        let mut tailer = LogTailer {
+            source,
+            file: None,
+            inode: 0,
+            offset: 0,
+            partial: String::new(),
+        };
+        if let Err(e) = tailer.open(true) {
+            debug!("{} not readable yet: {}", tailer.path().display(), e);
+        }
+        tailer
+    }
+
+    /// Path of the followed file
+    pub fn path(&self) -> &Path {
+        &self.source.path
+    }
+
+    fn open(&mut self, at_end: bool) -> Result<()> {
+        let mut file = File::open(&self.source.path).context("Failed to open log file")?;
+        let metadata = file.metadata()?;
+        self.offset = if at_end { metadata.len() } else { 0 };
+        file.seek(SeekFrom::Start(self.offset))?;
+        self.inode = metadata.ino();
+        self.partial.clear();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Read complete lines appended since the last call
+    pub fn read_new_lines(&mut self) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        let current_inode = fs::metadata(&self.source.path).ok().map(|m| m.ino());
+
+        // The path now names a new file: finish the rotated one, then switch
+        if self.file.is_some() && current_inode.is_some_and(|inode| inode != self.inode) {
+            self.read_available(&mut lines)?;
+            if !self.partial.is_empty() {
+                lines.push(std::mem::take(&mut self.partial));
+            }
+            self.file = None;
+        }
+
+        if self.file.is_none() {
+            if current_inode.is_none() {
+                return Ok(lines);
+            }
+            self.open(false)?;
+        }
+
+        if let Some(ref mut file) = self.file {
+            // Truncated in place (e.g. logrotate copytruncate)
+            if file.metadata()?.len() < self.offset {
+                debug!("{} was truncated, reading from start", self.source.path.display());
+                file.seek(SeekFrom::Start(0))?;
+                self.offset = 0;
+                self.partial.clear();
+            }
+        }
+
+        self.read_available(&mut lines)?;
+        Ok(lines)
+    }
+
+    fn read_available(&mut self, lines: &mut Vec<String>) -> Result<()> {
+        let Some(ref mut file) = self.file else {
+            return Ok(());
+        };
+
+        let mut buffer = Vec::new();
+        let read = file.read_to_end(&mut buffer)?;
+        self.offset += read as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&buffer));
+
+        while let Some(end) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=end).collect();
+            lines.push(line.trim_end_matches(['\n', '\r']).to_string());
+        }
+        Ok(())
+    }
+
+    /// Read and classify lines appended since the last call
+    /// Multi-line events are grouped within what was appended at once
+    pub fn read_new_entries(&mut self) -> Result<Vec<LogEntry>> {
+        let lines = self.read_new_lines()?;
+        if lines.is_empty() {
+            return Ok(Vec::new());
+        }
+        parse_log_lines(&self.source, lines)
+    }
+}
+
+/// Watches the directories of the configured log files with inotify
+/// Watching the directory rather than the file also catches rotation
+pub struct LogWatcher {
+    inotify: Inotify,
+    directories: HashMap<WatchDescriptor, PathBuf>,
+    tailers: Vec<LogTailer>,
+    buffer: Vec<u8>,
+}
+
+impl LogWatcher {
+    /// Set up watches for the given sources
+    /// Sources whose directory cannot be watched are skipped with a warning
+    pub fn new(sources: &[LogSourceConfig]) -> Result<Self> {
+        let inotify = Inotify::init().context("Failed to initialize inotify")?;
+        let mut watcher = LogWatcher {
+            inotify,
+            directories: HashMap::new(),
+            tailers: Vec::new(),
+            buffer: vec![0; 4096],
+        };
+
+        let mask = WatchMask::MODIFY | WatchMask::CREATE | WatchMask::MOVED_TO;
+        for source in sources {
+            let directory = log_directory(&source.path);
+            if !watcher.directories.values().any(|d| *d == directory) {
+                match watcher.inotify.watches().add(&directory, mask) {
+                    Ok(descriptor) => {
+                        watcher.directories.insert(descriptor, directory);
+                    }
+                    Err(e) => {
+                        warn!("Cannot watch {}: {}", directory.display(), e);
+                        continue;
+                    }
+                }
+            }
+            info!("Watching {}", source.path.display());
+            watcher.tailers.push(LogTailer::new(source.clone()));
+        }
+
+        if watcher.tailers.is_empty() {
+            return Err(anyhow!("None of the configured log files can be watched"));
+        }
+
+        Ok(watcher)
+    }
+
+    /// Return entries for lines appended since the last call
+    /// Never blocks: returns an empty list when nothing changed
+    pub fn poll(&mut self) -> Result<Vec<LogEntry>> {
+        let mut changed: HashSet<usize> = HashSet::new();
+
+        loop {
+            let events = match self.inotify.read_events(&mut self.buffer) {
+                Ok(events) => events,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e).context("Failed to read inotify events"),
+            };
+
+            let mut any = false;
+            for event in events {
+                any = true;
+                if event.mask.contains(EventMask::Q_OVERFLOW) {
+                    // Events were dropped, so check every file
+                    changed.extend(0..self.tailers.len());
+                    continue;
+                }
+
+                let (Some(directory), Some(name)) = (self.directories.get(&event.wd), event.name)
+                else {
+                    continue;
+                };
+                for (index, tailer) in self.tailers.iter().enumerate() {
+                    if tailer.path().file_name() == Some(name)
+                        && log_directory(tailer.path()) == *directory
+                    {
+                        changed.insert(index);
+                    }
+                }
+            }
+            if !any {
+                break;
+            }
+        }
+
+        let mut changed: Vec<usize> = changed.into_iter().collect();
+        changed.sort_unstable();
+
+        let mut entries = Vec::new();
+        for index in changed {
+            let tailer = &mut self.tailers[index];
+            match tailer.read_new_entries() {
+                Ok(mut new_entries) => entries.append(&mut new_entries),
+                Err(e) => warn!("Could not read {}: {}", tailer.path().display(), e),
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+fn log_directory(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Write buffered entries in a single transaction, linked to a collection
+/// run when `run_id` is given
+/// Returns the number of entries written
+pub fn flush_entries(
+    conn: &Connection,
+    entries: &mut Vec<LogEntry>,
+    run_id: Option<i64>,
+) -> Result<usize> {
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    let batch = match run_id {
+        Some(run_id) => Batch::begin_run(conn, run_id)?,
+        None => Batch::begin(conn)?,
+    };
+    for entry in entries.iter() {
+        batch
+            .insert_log_entry(entry)
+            .context("Failed to insert log entry")?;
+    }
+    batch.commit()?;
+
+    let written = entries.len();
+    entries.clear();
+    Ok(written)
+}
+
+/// Follow log files until `running` is cleared, batching inserts
+/// CRITICAL entries are alerted on immediately and flushed without waiting
+/// for the batch to fill. Stored rows are linked to `run_id` when given.
+/// Returns the number of entries stored
+pub fn run_watch(
+    conn: &Connection,
+    watcher: &mut LogWatcher,
+    options: &WatchConfig,
+    alerts: &AlertConfig,
+    running: &AtomicBool,
+    run_id: Option<i64>,
+) -> Result<usize> {
+    let flush_interval = Duration::from_secs(options.flush_interval_secs);
+    let mut pending: Vec<LogEntry> = Vec::new();
+    // When the oldest buffered entry was seen
+    let mut pending_since = Instant::now();
+    let mut total_written = 0;
+
+    while running.load(Ordering::SeqCst) {
+        let entries = watcher.poll()?;
+        let idle = entries.is_empty();
+
+        let mut critical = false;
+        for entry in entries {
+            if entry.level == "CRITICAL" {
+                critical = true;
+                if let Err(e) = send_alert(&entry, alerts) {
+                    warn!("Alert failed: {:#}", e);
+                }
+            }
+            if pending.is_empty() {
+                pending_since = Instant::now();
+            }
+            pending.push(entry);
+        }
+
+        if critical
+            || pending.len() >= options.batch_size
+            || (!pending.is_empty() && pending_since.elapsed() >= flush_interval)
+        {
+            let written = flush_entries(conn, &mut pending, run_id)?;
+            debug!("Stored {} new log entries", written);
+            total_written += written;
+        }
+
+        if idle {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    let written = flush_entries(conn, &mut pending, run_id)?;
+    if written > 0 {
+        debug!("Stored {} new log entries", written);
+    }
+    total_written += written;
+    info!("Stopped watching log files");
+
+    Ok(total_written)
+}
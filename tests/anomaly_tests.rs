@@ -0,0 +1,123 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, DurationRound, FixedOffset, Local, TimeZone, Timelike, Utc};
+use common::sample_metrics;
+use systers::anomaly::{
+    describe_anomaly, describe_hour_of_week, hour_of_week, rebuild_baselines, Anomaly,
+};
+use systers::db::{init_database, insert_metrics};
+use systers::reporter::{export_full_report, generate_full_report, ExportFormat};
+use systers::timeline::Severity;
+use systers::timezone::DisplayTimezone;
+
+fn local_hour(time: DateTime<Utc>) -> u32 {
+    let local: DateTime<Local> = time.into();
+    local.hour()
+}
+
+/// Test that a nightly spike is learned as normal while an unusual hour is flagged
+#[test]
+fn test_anomalies_against_hour_of_week_baseline() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now().duration_trunc(Duration::hours(1))?;
+
+    // Two consecutive hours in the last day, clear of the nightly backup
+    let offset = (3..20)
+        .find(|h| (0..2).all(|i| !(1..=3).contains(&local_hour(now - Duration::hours(h + i)))))
+        .unwrap();
+    let unusual = [now - Duration::hours(offset + 1), now - Duration::hours(offset)];
+
+    // Four weeks of hourly samples: CPU idles at 10% with a backup at 02:00
+    for h in 0..28 * 24 {
+        let timestamp = now - Duration::hours(h);
+        let cpu = if unusual.contains(&timestamp) {
+            if timestamp == unusual[0] { 70.0 } else { 75.0 }
+        } else if local_hour(timestamp) == 2 {
+            80.0
+        } else {
+            10.0
+        };
+        insert_metrics(&conn, &sample_metrics(timestamp, cpu))?;
+    }
+
+    // No anomaly section until baselines are built
+    assert!(generate_full_report(&conn, 24)?.anomalies.is_none());
+
+    let rebuild = rebuild_baselines(&conn, Utc::now(), Duration::days(28))?;
+    assert_eq!(rebuild.samples, 28 * 24);
+    assert_eq!(rebuild.slots, 4 * 168);
+
+    let report = generate_full_report(&conn, 24)?;
+    let anomalies = &report.anomalies.as_ref().unwrap().anomalies;
+    // The nightly backup stays within its baseline; the two unusual hours form one anomaly
+    assert_eq!(anomalies.len(), 1);
+    let anomaly = &anomalies[0];
+    assert_eq!(anomaly.metric, "cpu_usage");
+    assert_eq!(anomaly.samples, 2);
+    assert_eq!((anomaly.start, anomaly.end), (unusual[0], unusual[1]));
+    assert_eq!((anomaly.value, anomaly.at), (75.0, unusual[1]));
+    assert_eq!(anomaly.median, 10.0);
+    // MAD is 0 here, so the range is the CPU minimum of +-5 points
+    assert_eq!((anomaly.expected_low, anomaly.expected_high), (5.0, 15.0));
+    assert_eq!(anomaly.hour_of_week, hour_of_week(unusual[1]));
+
+    let slot = describe_hour_of_week(anomaly.hour_of_week);
+    assert!(report.metrics.issues.iter().any(|issue| {
+        issue.severity == Severity::Warning
+            && issue.message.starts_with("ANOMALY: CPU 75.0% at")
+            && issue
+                .message
+                .contains(&format!("(expected 5.0-15.0% for {}; 2 samples from", slot))
+    }));
+    let text = export_full_report(&report, ExportFormat::Text)?;
+    assert!(text.contains("ANOMALIES"));
+    assert!(text.contains("2 samples from"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&export_full_report(&report, ExportFormat::Json)?)?;
+    assert_eq!(json["anomalies"]["baseline_slots"], 672);
+    assert_eq!(json["anomalies"]["anomalies"][0]["expected_high"], 15.0);
+
+    Ok(())
+}
+
+/// Test the hour-of-week slot labels
+#[test]
+fn test_describe_hour_of_week() {
+    assert_eq!(describe_hour_of_week(0), "Mon 00:00");
+    assert_eq!(describe_hour_of_week(2 * 24 + 14), "Wed 14:00");
+    assert_eq!(describe_hour_of_week(167), "Sun 23:00");
+}
+
+/// Test that the slot in an anomaly description is named in the display zone
+#[test]
+fn test_describe_anomaly_slot_in_display_timezone() {
+    // Wednesday 2025-11-05 14:20 UTC
+    let at = Utc.with_ymd_and_hms(2025, 11, 5, 14, 20, 0).unwrap();
+    let anomaly = Anomaly {
+        metric: "cpu_usage".to_string(),
+        start: at,
+        end: at,
+        samples: 1,
+        value: 96.0,
+        at,
+        hour_of_week: hour_of_week(at),
+        median: 10.0,
+        expected_low: 4.0,
+        expected_high: 16.0,
+    };
+
+    let utc = DisplayTimezone::Fixed(FixedOffset::east_opt(0).unwrap());
+    assert_eq!(
+        describe_anomaly(&anomaly, utc),
+        "CPU 96.0% at 2025-11-05 14:20 (expected 4.0-16.0% for Wed 14:00)"
+    );
+    let tokyo = DisplayTimezone::Fixed(FixedOffset::east_opt(9 * 3600).unwrap());
+    assert_eq!(
+        describe_anomaly(&anomaly, tokyo),
+        "CPU 96.0% at 2025-11-05 23:20 (expected 4.0-16.0% for Wed 23:00)"
+    );
+    let honolulu = DisplayTimezone::Fixed(FixedOffset::west_opt(10 * 3600).unwrap());
+    assert!(describe_anomaly(&anomaly, honolulu).ends_with("for Wed 04:00)"));
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use std::fs::File;
+use std::io::Write;
+use systers::auth::{
+    collect_auth_events, detect_brute_force_bursts, is_auth_log, parse_auth_line,
+    EVENT_ACCEPTED_LOGIN, EVENT_FAILED_LOGIN, EVENT_INVALID_USER, EVENT_SUDO_COMMAND,
+    EVENT_SUDO_FAILURE,
+};
+use systers::db::{init_database, insert_auth_event, AuthEvent};
+use systers::reporter::{format_full_report, generate_full_report};
+use tempfile::TempDir;
+
+/// Build a failed login event for burst tests
+fn failed_login(ip: &str, seconds: i64) -> AuthEvent {
+    AuthEvent {
+        timestamp: Utc.timestamp_opt(1_762_372_800 + seconds, 0).unwrap(),
+        event_type: EVENT_FAILED_LOGIN.to_string(),
+        username: Some("root".to_string()),
+        source_ip: Some(ip.to_string()),
+        method: Some("password".to_string()),
+        command: None,
+        message: format!("Failed password for root from {} ({})", ip, seconds),
+    }
+}
+
+/// Test parsing of sshd and sudo lines
+#[test]
+fn test_parse_auth_lines() {
+    let now = Utc::now();
+
+    let failed = parse_auth_line(
+        "Nov  5 20:00:01 web sshd[811]: Failed password for invalid user admin from 203.0.113.9 port 4242 ssh2",
+        now,
+    )
+    .unwrap();
+    assert_eq!(failed.event_type, EVENT_FAILED_LOGIN);
+    assert_eq!(failed.username.as_deref(), Some("admin"));
+    assert_eq!(failed.source_ip.as_deref(), Some("203.0.113.9"));
+    assert_eq!(failed.method.as_deref(), Some("password"));
+    // Syslog timestamps are parsed rather than replaced by the fallback
+    assert_ne!(failed.timestamp, now);
+
+    let accepted = parse_auth_line(
+        "2025-11-05T20:01:00+00:00 web sshd[812]: Accepted publickey for alice from 198.51.100.7 port 51234 ssh2: ED25519 SHA256:abc",
+        now,
+    )
+    .unwrap();
+    assert_eq!(accepted.event_type, EVENT_ACCEPTED_LOGIN);
+    assert_eq!(accepted.username.as_deref(), Some("alice"));
+    assert_eq!(accepted.method.as_deref(), Some("publickey"));
+    assert_eq!(accepted.timestamp.timestamp(), 1_762_372_860);
+
+    let invalid = parse_auth_line(
+        "Nov  5 20:00:00 web sshd[811]: Invalid user admin from 203.0.113.9 port 4242",
+        now,
+    )
+    .unwrap();
+    assert_eq!(invalid.event_type, EVENT_INVALID_USER);
+
+    let sudo = parse_auth_line(
+        "Nov  5 20:02:00 web sudo:    alice : TTY=pts/0 ; PWD=/home/alice ; USER=root ; COMMAND=/usr/bin/apt update",
+        now,
+    )
+    .unwrap();
+    assert_eq!(sudo.event_type, EVENT_SUDO_COMMAND);
+    assert_eq!(sudo.command.as_deref(), Some("/usr/bin/apt update"));
+
+    let rejected = parse_auth_line(
+        "Nov  5 20:03:00 web sudo:      bob : user NOT in sudoers ; TTY=pts/1 ; PWD=/home/bob ; USER=root ; COMMAND=/bin/bash",
+        now,
+    )
+    .unwrap();
+    assert_eq!(rejected.event_type, EVENT_SUDO_FAILURE);
+
+    assert!(parse_auth_line("Nov  5 20:04:00 web CRON[900]: pam_unix(cron:session): session opened", now).is_none());
+}
+
+/// Test brute-force burst detection groups close failures per address
+#[test]
+fn test_detect_brute_force_bursts() {
+    let mut events: Vec<AuthEvent> = (0..12).map(|i| failed_login("203.0.113.9", i * 5)).collect();
+    // A slow scanner that never gets close enough together
+    events.extend((0..12).map(|i| failed_login("198.51.100.1", i * 600)));
+    // A second burst from the first address an hour later
+    events.extend((0..3).map(|i| failed_login("203.0.113.9", 3600 + i)));
+
+    let bursts = detect_brute_force_bursts(&events, 10, Duration::seconds(60));
+
+    assert_eq!(bursts.len(), 1);
+    assert_eq!(bursts[0].source_ip, "203.0.113.9");
+    assert_eq!(bursts[0].attempts, 12);
+    assert_eq!((bursts[0].end - bursts[0].start).num_seconds(), 55);
+}
+
+/// Test auth log detection, collection, storage and the security report section
+#[test]
+fn test_security_report_from_auth_log() -> Result<()> {
+    assert!(is_auth_log("/var/log/auth.log"));
+    assert!(is_auth_log("/var/log/secure"));
+    assert!(!is_auth_log("/var/log/syslog"));
+
+    let temp_dir = TempDir::new()?;
+    let log_path = temp_dir.path().join("auth.log");
+    let mut log_file = File::create(&log_path)?;
+    let now = Utc::now();
+    for i in 0..15 {
+        let ts = (now - Duration::minutes(30) + Duration::seconds(i * 2)).to_rfc3339();
+        writeln!(log_file, "{} web sshd[900]: Failed password for root from 203.0.113.9 port {} ssh2", ts, 40000 + i)?;
+    }
+    let ts = (now - Duration::minutes(10)).to_rfc3339();
+    writeln!(log_file, "{} web sshd[901]: Accepted password for alice from 198.51.100.7 port 5000 ssh2", ts)?;
+    writeln!(log_file, "{} web sudo:    alice : TTY=pts/0 ; PWD=/home/alice ; USER=root ; COMMAND=/bin/cat /etc/shadow", ts)?;
+    log_file.sync_all()?;
+
+    let conn = init_database(":memory:")?;
+    let events = collect_auth_events(&log_path, 1000)?;
+    assert_eq!(events.len(), 17);
+    for event in &events {
+        assert!(insert_auth_event(&conn, event)?);
+    }
+    // Scanning the same file again does not duplicate events
+    assert!(!insert_auth_event(&conn, &events[0])?);
+
+    let report = generate_full_report(&conn, 24)?;
+    let security = report.security.as_ref().expect("security section");
+
+    assert_eq!(security.failed_logins, 15);
+    assert_eq!(security.accepted_logins, 1);
+    assert_eq!(security.top_attacking_ips[0].source_ip, "203.0.113.9");
+    assert_eq!(security.brute_force_bursts.len(), 1);
+    assert_eq!(security.new_login_sources.len(), 1);
+    assert_eq!(security.new_sudo_users[0].username, "alice");
+
+    let text = format_full_report(&report);
+    assert!(text.contains("SECURITY (SSH & SUDO)"));
+    assert!(text.contains("Brute-Force Bursts"));
+    assert!(text.contains("alice from 198.51.100.7"));
+    // "First-time" only reaches back to the oldest kept auth event
+    assert!(security.history_since.is_some());
+    assert!(text.contains("First-Time Login Sources (no earlier record since "));
+
+    Ok(())
+}
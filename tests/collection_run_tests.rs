@@ -0,0 +1,147 @@
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use systers::db::{
+    finish_collection_run, init_database, query_collection_runs, start_collection_run, Batch,
+    CollectorOutcome, LogEntry, RUN_MODE_COLLECT, RUN_MODE_WATCH, RUN_OK, RUN_PARTIAL,
+};
+use systers::reporter::{format_full_report, generate_collection_report, generate_full_report};
+
+/// Test that a run records its collectors and links the rows it stored
+#[test]
+fn test_run_ledger_links_rows() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let started = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+
+    let run_id = start_collection_run(&conn, RUN_MODE_COLLECT, started, "web-01")?;
+    let batch = Batch::begin_run(&conn, run_id)?;
+    batch.insert_log_entry(&LogEntry {
+        timestamp: started,
+        level: "ERROR".to_string(),
+        source: "syslog".to_string(),
+        message: "disk failure".to_string(),
+        attributes: None,
+    })?;
+    batch.commit()?;
+    finish_collection_run(
+        &conn,
+        run_id,
+        started + Duration::seconds(2),
+        &[
+            CollectorOutcome::ok("logs", 1),
+            CollectorOutcome::failed("kernel", "journalctl not found", 0),
+        ],
+        None,
+    )?;
+
+    let linked: i64 = conn.query_row("SELECT run_id FROM log_entries", [], |row| row.get(0))?;
+    assert_eq!(linked, run_id);
+
+    let runs = query_collection_runs(&conn, started, started + Duration::hours(1))?;
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].status, RUN_PARTIAL);
+    assert_eq!(runs[0].hostname, "web-01");
+    assert_eq!(runs[0].rows_written, 1);
+    assert_eq!(runs[0].finished_at, Some(started + Duration::seconds(2)));
+    assert_eq!(runs[0].errors(), vec!["kernel: journalctl not found"]);
+
+    Ok(())
+}
+
+/// Test that missing hourly runs, failures and interrupted runs are reported
+#[test]
+fn test_collection_gaps_and_failures() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let start = Utc.with_ymd_and_hms(2025, 11, 5, 0, 0, 0).unwrap();
+    let end = start + Duration::hours(12);
+
+    // Hourly runs at 00:00-03:00, none from 04:00 to 07:00, then 08:00-09:00
+    for hour in [0, 1, 2, 3, 8, 9] {
+        let started = start + Duration::hours(hour);
+        let run_id = start_collection_run(&conn, RUN_MODE_COLLECT, started, "web-01")?;
+        match hour {
+            // Crashed without recording how it ended
+            2 => {}
+            8 => finish_collection_run(&conn, run_id, started, &[], Some("disk I/O error"))?,
+            _ => finish_collection_run(
+                &conn,
+                run_id,
+                started,
+                &[CollectorOutcome::ok("metrics", 1)],
+                None,
+            )?,
+        }
+    }
+    // Watch sessions are not scheduled runs
+    start_collection_run(&conn, RUN_MODE_WATCH, start + Duration::hours(5), "web-01")?;
+
+    let report = generate_collection_report(&conn, start, end, Duration::hours(1))?
+        .expect("runs were recorded");
+
+    assert_eq!(report.total_runs, 6);
+    assert_eq!(report.ok_runs, 4);
+    assert_eq!(report.failed_runs, 1);
+    assert_eq!(report.interrupted_runs, 1);
+
+    // 03:00 -> 08:00 misses four runs, 09:00 -> 12:00 misses three
+    assert_eq!(report.gaps.len(), 2);
+    assert_eq!(report.gaps[0].from, start + Duration::hours(3));
+    assert_eq!(report.gaps[0].to, start + Duration::hours(8));
+    assert_eq!(report.gaps[0].missed_runs, 4);
+    assert_eq!(report.gaps[1].missed_runs, 3);
+    assert_eq!(report.missed_runs(), 7);
+
+    // Newest failure first
+    assert_eq!(report.failures.len(), 2);
+    assert_eq!(report.failures[0].status, "failed");
+    assert_eq!(report.failures[0].errors, vec!["disk I/O error"]);
+    assert_eq!(report.failures[1].status, "interrupted");
+
+    // A window after the last run is one long gap
+    let later = end + Duration::hours(24);
+    let report = generate_collection_report(&conn, end, later, Duration::hours(1))?
+        .expect("an earlier run exists");
+    assert_eq!(report.total_runs, 0);
+    assert_eq!(report.gaps.len(), 1);
+    assert_eq!(report.gaps[0].from, end);
+    // 10:00 and 11:00 were due before the window; 12:00 to 36:00 inside it
+    assert_eq!(report.gaps[0].missed_runs, 25);
+
+    // A gap straddling the start only counts the runs missed inside the window:
+    // of 04:00-07:00, 04:00 falls before 05:00
+    let report =
+        generate_collection_report(&conn, start + Duration::hours(5), end, Duration::hours(1))?
+            .expect("runs were recorded");
+    assert_eq!(report.gaps[0].from, start + Duration::hours(5));
+    assert_eq!(report.gaps[0].missed_runs, 3);
+
+    Ok(())
+}
+
+/// Test that the text report shows the collection health section
+#[test]
+fn test_collection_section_in_text_report() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+
+    let day_ago = now - Duration::hours(24);
+    assert!(generate_collection_report(&conn, day_ago, now, Duration::hours(1))?.is_none());
+
+    let run_id = start_collection_run(&conn, RUN_MODE_COLLECT, now - Duration::hours(5), "db-01")?;
+    finish_collection_run(&conn, run_id, now, &[], None)?;
+    let runs = query_collection_runs(&conn, now - Duration::hours(6), now)?;
+    assert_eq!(runs[0].status, RUN_OK);
+
+    let mut report = generate_full_report(&conn, 24)?;
+    assert!(report.collection.is_some());
+    report.collection = generate_collection_report(&conn, day_ago, now, Duration::hours(1))?;
+
+    let text = format_full_report(&report);
+    assert!(text.contains("COLLECTION HEALTH"));
+    assert!(text.contains("Gaps in Collection"));
+    assert!(text.contains("Check the syswriter cron job or systemd timer"));
+
+    let json = serde_json::to_value(&report)?;
+    assert_eq!(json["collection"]["total_runs"], 1);
+
+    Ok(())
+}
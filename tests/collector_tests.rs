@@ -201,8 +201,8 @@ fn test_scan_system_logs_with_custom_paths() -> Result<()> {
     assert_eq!(critical_count, 1);
 
     // Verify sources are set correctly
-    let has_log1_source = entries.iter().any(|e| e.source == log1_path.to_string_lossy().to_string());
-    let has_log2_source = entries.iter().any(|e| e.source == log2_path.to_string_lossy().to_string());
+    let has_log1_source = entries.iter().any(|e| e.source == log1_path.to_string_lossy());
+    let has_log2_source = entries.iter().any(|e| e.source == log2_path.to_string_lossy());
 
     assert!(has_log1_source);
     assert!(has_log2_source);
@@ -234,3 +234,146 @@ fn test_scan_system_logs_with_nonexistent_paths() -> Result<()> {
 
     Ok(())
 }
+
+/// Test JSON log parsing with custom keys, level mapping and attributes
+#[test]
+fn test_collect_json_log_entries() -> Result<()> {
+    use systers::collector::collect_json_log_entries;
+    use systers::config::JsonLogConfig;
+
+    let temp_dir = TempDir::new()?;
+    let log_file_path = temp_dir.path().join("app.json");
+    let mut log_file = File::create(&log_file_path)?;
+
+    writeln!(log_file, r#"{{"severity":"info","msg":"started","error_count":3}}"#)?;
+    writeln!(log_file, r#"{{"severity":"err","msg":"db timeout","ts":"2025-11-05T20:00:01Z","request_id":"abc"}}"#)?;
+    writeln!(log_file, r#"{{"severity":"fatal","msg":"giving up","ts":1762372801000}}"#)?;
+    writeln!(log_file, r#"{{"severity":"warn","msg":"slow query","ctx":{{"user":"bob"}}}}"#)?;
+    writeln!(log_file, "not json at all: error")?;
+    log_file.sync_all()?;
+
+    let fields = JsonLogConfig {
+        level_key: "severity".to_string(),
+        timestamp_key: "ts".to_string(),
+        message_key: "msg".to_string(),
+        attributes: vec!["request_id".to_string(), "ctx.user".to_string()],
+    };
+    let entries = collect_json_log_entries(&log_file_path, &fields, 100)?;
+
+    // The info line (despite its error_count field) and the non-JSON line are skipped
+    assert_eq!(entries.len(), 3);
+
+    assert_eq!(entries[0].level, "ERROR");
+    assert_eq!(entries[0].message, "db timeout");
+    assert_eq!(entries[0].timestamp.to_rfc3339(), "2025-11-05T20:00:01+00:00");
+    assert_eq!(entries[0].attributes.as_ref().unwrap()["request_id"], "abc");
+
+    assert_eq!(entries[1].level, "CRITICAL");
+    assert_eq!(entries[1].timestamp.timestamp(), 1_762_372_801);
+    assert!(entries[1].attributes.is_none());
+
+    assert_eq!(entries[2].level, "WARNING");
+    assert_eq!(entries[2].attributes.as_ref().unwrap()["ctx.user"], "bob");
+
+    Ok(())
+}
+
+/// Test that configured sources replace plain paths with the same location
+#[test]
+fn test_resolve_log_sources() -> Result<()> {
+    use systers::collector::resolve_log_sources;
+    use systers::config::{LogFormat, LogSourceConfig};
+
+    let mut json_source = LogSourceConfig::plain("/var/log/app.json");
+    json_source.format = LogFormat::Json;
+    let extra_source = LogSourceConfig::plain("/var/log/extra.log");
+
+    let paths = vec!["/var/log/syslog", "/var/log/app.json"];
+    let sources = resolve_log_sources(Some(&paths), &[json_source, extra_source]);
+
+    assert_eq!(sources.len(), 3);
+    assert_eq!(sources[0].format, LogFormat::Plain);
+    assert_eq!(sources[1].format, LogFormat::Json);
+    assert_eq!(sources[2].path.to_string_lossy(), "/var/log/extra.log");
+
+    Ok(())
+}
+
+/// Test that stack traces are merged into a single entry
+#[test]
+fn test_collect_log_events_multiline() -> Result<()> {
+    use systers::collector::collect_log_events;
+    use systers::config::MultilineConfig;
+
+    let temp_dir = TempDir::new()?;
+    let log_file_path = temp_dir.path().join("app.log");
+    let mut log_file = File::create(&log_file_path)?;
+
+    writeln!(log_file, "2025-11-05T20:00:01Z ERROR Request handler failed")?;
+    writeln!(log_file, "java.lang.IllegalStateException: connection error")?;
+    writeln!(log_file, "    at com.example.Handler.run(Handler.java:42)")?;
+    writeln!(log_file, "Caused by: java.io.IOException: disk gone")?;
+    writeln!(log_file, "    ... 12 more")?;
+    writeln!(log_file, "2025-11-05T20:00:02Z INFO Request served")?;
+    writeln!(log_file, "2025-11-05T20:00:03Z WARNING slow response")?;
+    log_file.sync_all()?;
+
+    // Events start with a timestamp; everything else belongs to the previous event
+    let multiline = MultilineConfig {
+        start_pattern: Some(r"^\d{4}-\d{2}-\d{2}T".to_string()),
+        ..MultilineConfig::default()
+    };
+    let entries = collect_log_events(&log_file_path, Some(&multiline), 100)?;
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].level, "ERROR");
+    assert_eq!(entries[0].headline(), "2025-11-05T20:00:01Z ERROR Request handler failed");
+    assert_eq!(entries[0].continuation_lines(), 4);
+    assert_eq!(entries[1].level, "WARNING");
+
+    // The default continuation pattern groups the indented and "Caused by:" lines
+    let entries = collect_log_events(&log_file_path, Some(&MultilineConfig::default()), 100)?;
+    let trace = entries
+        .iter()
+        .find(|e| e.message.contains("Caused by:"))
+        .expect("trace should be grouped");
+    assert_eq!(trace.headline(), "2025-11-05T20:00:01Z ERROR Request handler failed");
+    assert_eq!(trace.continuation_lines(), 4);
+
+    Ok(())
+}
+
+/// Test that a Python traceback keeps its closing exception line and is
+/// classified by its headline and exception, not by its frames
+#[test]
+fn test_multiline_python_traceback() -> Result<()> {
+    use systers::collector::collect_log_events;
+    use systers::config::MultilineConfig;
+
+    let temp_dir = TempDir::new()?;
+    let log_file_path = temp_dir.path().join("worker.log");
+    let mut log_file = File::create(&log_file_path)?;
+
+    writeln!(log_file, "Traceback (most recent call last):")?;
+    writeln!(log_file, "  File \"worker.py\", line 12, in run")?;
+    writeln!(log_file, "    parse(payload)")?;
+    writeln!(log_file, "ValueError: invalid literal for int() with base 10: 'x'")?;
+    writeln!(log_file, "2025-11-05T20:00:02Z INFO retrying job 7")?;
+    writeln!(log_file, "  File \"error_handler.py\", line 3, in retry")?;
+    writeln!(log_file, "2025-11-05T20:00:03Z WARNING queue is 90% full")?;
+    log_file.sync_all()?;
+
+    let entries = collect_log_events(&log_file_path, Some(&MultilineConfig::default()), 100)?;
+
+    assert_eq!(entries.len(), 2);
+    // The exception line stays with its traceback and makes it an error
+    assert_eq!(entries[0].level, "ERROR");
+    assert_eq!(entries[0].headline(), "Traceback (most recent call last):");
+    assert_eq!(entries[0].continuation_lines(), 3);
+    assert!(entries[0].message.ends_with("ValueError: invalid literal for int() with base 10: 'x'"));
+    // The INFO event with an "error" frame is not escalated and not stored
+    assert_eq!(entries[1].level, "WARNING");
+    assert_eq!(entries[1].headline(), "2025-11-05T20:00:03Z WARNING queue is 90% full");
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use systers::db::SystemMetrics;
+
+/// Metrics sample at `timestamp` with the given CPU usage; memory is 40% and
+/// disk 50% used
+pub fn sample_metrics(timestamp: DateTime<Utc>, cpu_usage: f32) -> SystemMetrics {
+    SystemMetrics {
+        timestamp,
+        cpu_usage,
+        memory_total: 100,
+        memory_used: 40,
+        memory_available: 60,
+        disk_total: 100,
+        disk_used: 50,
+        process_count: 120,
+        load_avg_1min: 0.5,
+        load_avg_5min: 0.5,
+        load_avg_15min: 0.5,
+    }
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use common::sample_metrics;
+use systers::compare::{compare_reports, export_comparison, previous_period};
+use systers::db::{
+    init_database, insert_log_entry, insert_metrics, insert_system_check, CheckStatus, LogEntry,
+    SystemCheckResult,
+};
+use systers::reporter::{generate_full_report_between, ExportFormat, ReportOptions};
+
+fn check(timestamp: chrono::DateTime<Utc>, name: &str, status: CheckStatus) -> SystemCheckResult {
+    SystemCheckResult {
+        timestamp,
+        check_name: name.to_string(),
+        check_type: "test".to_string(),
+        status,
+        value: None,
+        unit: None,
+        details: None,
+        message: format!("{} is {}", name, status),
+    }
+}
+
+/// Test a busier current week is reported as a regression against the previous one
+#[test]
+fn test_compare_with_previous_period() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let end = Utc.with_ymd_and_hms(2025, 11, 17, 0, 0, 0).unwrap();
+    let start = end - Duration::days(7);
+    let (baseline_start, baseline_end) = previous_period(start, end);
+    assert_eq!(baseline_start, end - Duration::days(14));
+    assert_eq!(baseline_end, start);
+
+    // Hourly samples on the half hour, clear of the shared boundary
+    for hour in 1..=(7 * 24) {
+        let offset = Duration::hours(hour) - Duration::minutes(30);
+        insert_metrics(&conn, &sample_metrics(start - offset, 20.0))?;
+        insert_metrics(&conn, &sample_metrics(end - offset, 60.0))?;
+    }
+    for (timestamp, errors) in [(start - Duration::days(1), 1), (end - Duration::days(1), 10)] {
+        for i in 0..errors {
+            insert_log_entry(
+                &conn,
+                &LogEntry {
+                    timestamp: timestamp + Duration::minutes(i),
+                    level: "ERROR".to_string(),
+                    source: "app".to_string(),
+                    message: format!("failure {}", i),
+                    attributes: None,
+                },
+            )?;
+        }
+    }
+    let before = start - Duration::days(1);
+    let after = end - Duration::days(1);
+    insert_system_check(&conn, &check(before, "Systemd Services", CheckStatus::Ok))?;
+    insert_system_check(&conn, &check(after, "Systemd Services", CheckStatus::Warning))?;
+    insert_system_check(&conn, &check(before, "Disk", CheckStatus::Warning))?;
+    insert_system_check(&conn, &check(after, "Disk", CheckStatus::Ok))?;
+    insert_system_check(&conn, &check(after, "Updates", CheckStatus::Ok))?;
+
+    let options = ReportOptions::default();
+    let baseline = generate_full_report_between(&conn, baseline_start, baseline_end, &options)?;
+    let current = generate_full_report_between(&conn, start, end, &options)?;
+    let comparison = compare_reports(baseline, current);
+
+    let delta = |metric: &str, statistic: &str| {
+        comparison
+            .metrics
+            .iter()
+            .find(|d| d.metric == metric && d.statistic == statistic)
+            .unwrap()
+    };
+    let cpu = delta("cpu_usage", "avg");
+    assert_eq!((cpu.baseline, cpu.current, cpu.change), (20.0, 60.0, 40.0));
+    assert_eq!(cpu.percent_change, Some(200.0));
+    assert!(cpu.regression);
+    // A rise of the median alone is not flagged
+    assert!(!delta("cpu_usage", "p50").regression);
+    let memory = delta("memory_used_percent", "avg");
+    assert_eq!(memory.change, 0.0);
+    assert!(!memory.regression);
+
+    let errors = comparison.log_counts.iter().find(|d| d.name == "errors").unwrap();
+    assert_eq!((errors.baseline, errors.current, errors.change), (1, 10, 9));
+    assert!(errors.regression);
+
+    // Only checks whose status changed are listed; getting better is not a regression
+    let changes: Vec<_> = comparison
+        .checks
+        .iter()
+        .map(|c| (c.check_name.as_str(), c.baseline, c.current, c.regression))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("Disk", Some(CheckStatus::Warning), Some(CheckStatus::Ok), false),
+            ("Systemd Services", Some(CheckStatus::Ok), Some(CheckStatus::Warning), true),
+            ("Updates", None, Some(CheckStatus::Ok), false),
+        ]
+    );
+
+    // CPU average, p90, p95, p99 and peak, the error count and one check
+    assert_eq!(comparison.regressions(), 7);
+
+    let text = export_comparison(&comparison, ExportFormat::Text)?;
+    assert!(text.contains("PERIOD COMPARISON"));
+    assert!(text.contains("Systemd Services: ok -> warning  🔴"));
+    assert!(text.contains("🔴 7 significant regressions"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&export_comparison(&comparison, ExportFormat::Json)?)?;
+    assert_eq!(json["baseline"]["metrics"]["avg_cpu_usage"], 20.0);
+    assert_eq!(json["current"]["metrics"]["avg_cpu_usage"], 60.0);
+    assert_eq!(json["log_counts"][1]["change"], 9);
+    assert_eq!(json["checks"][1]["current"], "warning");
+
+    assert!(export_comparison(&comparison, ExportFormat::Html).is_err());
+
+    Ok(())
+}
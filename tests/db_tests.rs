@@ -1,9 +1,12 @@
 use anyhow::Result;
 use chrono::{Duration, Utc};
+use std::time::Duration as StdDuration;
 use systers::db::{
-    cleanup_old_data, init_database, insert_log_entry, insert_metrics, query_logs, query_metrics,
+    cleanup_old_data, init_database, init_database_with_options, insert_log_entry, insert_metrics,
+    open_read_only, query_logs, query_metrics, try_collection_lock, try_shared_writer_lock,
+    try_writer_lock, writer_lock_holder, ConnectionOptions, HostFilter,
 };
-use systers::db::{LogEntry, SystemMetrics};
+use systers::db::{Batch, CheckStatus, LogEntry, SystemCheckResult, SystemMetrics};
 
 /// Test database initialization with in-memory database
 #[test]
@@ -20,11 +23,16 @@ fn test_init_database() -> Result<()> {
     assert!(tables.contains(&"system_metrics".to_string()));
     assert!(tables.contains(&"log_entries".to_string()));
     assert!(tables.contains(&"system_checks".to_string()));
+    assert!(tables.contains(&"auth_events".to_string()));
+    assert!(tables.contains(&"kernel_events".to_string()));
+    assert!(tables.contains(&"metric_rollups".to_string()));
+    assert!(tables.contains(&"log_rollups".to_string()));
+    assert!(tables.contains(&"collection_runs".to_string()));
 
     // Verify schema version is set
     let version: i32 =
         conn.query_row("SELECT version FROM schema_version", [], |row| row.get(0))?;
-    assert_eq!(version, 3);
+    assert_eq!(version, 12);
 
     // Verify app version is set
     let app_version: String =
@@ -63,7 +71,7 @@ fn test_metrics_insert_and_query() -> Result<()> {
     // Query back
     let start = now - Duration::hours(1);
     let end = now + Duration::hours(1);
-    let results = query_metrics(&conn, start, end)?;
+    let results = query_metrics(&conn, start, end, &HostFilter::Local)?;
 
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].cpu_usage, 45.5);
@@ -81,7 +89,7 @@ fn test_query_metrics_empty() -> Result<()> {
     // Query with no data
     let start = now - Duration::hours(1);
     let end = now + Duration::hours(1);
-    let results = query_metrics(&conn, start, end)?;
+    let results = query_metrics(&conn, start, end, &HostFilter::Local)?;
 
     assert_eq!(results.len(), 0);
 
@@ -100,6 +108,7 @@ fn test_logs_insert_and_query() -> Result<()> {
         level: "ERROR".to_string(),
         source: "test".to_string(),
         message: "Test error message".to_string(),
+        attributes: None,
     };
 
     let warning_entry = LogEntry {
@@ -107,6 +116,7 @@ fn test_logs_insert_and_query() -> Result<()> {
         level: "WARNING".to_string(),
         source: "test".to_string(),
         message: "Test warning message".to_string(),
+        attributes: None,
     };
 
     // Insert logs
@@ -116,16 +126,16 @@ fn test_logs_insert_and_query() -> Result<()> {
     // Query all logs
     let start = now - Duration::hours(1);
     let end = now + Duration::hours(1);
-    let all_logs = query_logs(&conn, start, end, None)?;
+    let all_logs = query_logs(&conn, start, end, None, &HostFilter::Local)?;
     assert_eq!(all_logs.len(), 2);
 
     // Query only errors
-    let error_logs = query_logs(&conn, start, end, Some("ERROR"))?;
+    let error_logs = query_logs(&conn, start, end, Some("ERROR"), &HostFilter::Local)?;
     assert_eq!(error_logs.len(), 1);
     assert_eq!(error_logs[0].level, "ERROR");
 
     // Query only warnings
-    let warning_logs = query_logs(&conn, start, end, Some("WARNING"))?;
+    let warning_logs = query_logs(&conn, start, end, Some("WARNING"), &HostFilter::Local)?;
     assert_eq!(warning_logs.len(), 1);
     assert_eq!(warning_logs[0].level, "WARNING");
 
@@ -159,13 +169,13 @@ fn test_query_metrics_time_range() -> Result<()> {
     // Query all
     let start = now - Duration::hours(6);
     let end = now + Duration::hours(1);
-    let all_results = query_metrics(&conn, start, end)?;
+    let all_results = query_metrics(&conn, start, end, &HostFilter::Local)?;
     assert_eq!(all_results.len(), 5);
 
     // Query limited time range
     let start = now - Duration::hours(2);
     let end = now + Duration::hours(1);
-    let limited_results = query_metrics(&conn, start, end)?;
+    let limited_results = query_metrics(&conn, start, end, &HostFilter::Local)?;
     assert_eq!(limited_results.len(), 3);
 
     Ok(())
@@ -240,6 +250,7 @@ fn test_cleanup_old_data() -> Result<()> {
             level: "ERROR".to_string(),
             source: "test".to_string(),
             message: "Old error".to_string(),
+            attributes: None,
         };
         insert_log_entry(&conn, &log)?;
     }
@@ -256,7 +267,7 @@ fn test_cleanup_old_data() -> Result<()> {
     // Verify remaining data
     let start = now - Duration::days(100);
     let end = now + Duration::days(1);
-    let remaining_metrics = query_metrics(&conn, start, end)?;
+    let remaining_metrics = query_metrics(&conn, start, end, &HostFilter::Local)?;
 
     // Should only have 5 recent metrics left
     assert_eq!(remaining_metrics.len(), 5);
@@ -264,4 +275,225 @@ fn test_cleanup_old_data() -> Result<()> {
     Ok(())
 }
 
-//}
+/// Test that JSON attributes survive a round trip through the database
+#[test]
+fn test_log_attributes_round_trip() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+
+    let entry = LogEntry {
+        timestamp: now,
+        level: "ERROR".to_string(),
+        source: "app.json".to_string(),
+        message: "db timeout".to_string(),
+        attributes: Some(serde_json::json!({"request_id": "abc", "attempt": 3})),
+    };
+    insert_log_entry(&conn, &entry)?;
+
+    let logs = query_logs(
+        &conn,
+        now - Duration::hours(1),
+        now + Duration::hours(1),
+        None,
+        &HostFilter::Local,
+    )?;
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].attributes, entry.attributes);
+
+    Ok(())
+}
+
+/// Test that a v3 database gains the attributes column and is not migrated twice
+#[test]
+fn test_migrate_v3_to_v4() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let db_path = temp_dir.path().join("v3.db");
+
+    {
+        let conn = rusqlite::Connection::open(&db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, app_version TEXT);
+             INSERT INTO schema_version VALUES (3, '0.4.0');
+             CREATE TABLE log_entries (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 timestamp INTEGER NOT NULL,
+                 level TEXT NOT NULL,
+                 source TEXT NOT NULL,
+                 message TEXT NOT NULL
+             );
+             INSERT INTO log_entries (timestamp, level, source, message)
+                 VALUES (1762372801, 'ERROR', 'syslog', 'old error');",
+        )?;
+    }
+
+    init_database(&db_path)?;
+    // Opening again must not try to add the column a second time
+    let conn = init_database(&db_path)?;
+
+    let attributes: Option<String> =
+        conn.query_row("SELECT attributes FROM log_entries", [], |row| row.get(0))?;
+    assert!(attributes.is_none());
+
+    Ok(())
+}
+
+/// Test that file databases use WAL and read-only connections cannot write
+#[test]
+fn test_wal_mode_and_read_only_open() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+
+    let options = ConnectionOptions {
+        busy_timeout: StdDuration::from_millis(250),
+        key: None,
+    };
+    let writer = init_database_with_options(&db_path, &options)?;
+    let mode: String = writer.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+    assert_eq!(mode, "wal");
+
+    let reader = open_read_only(&db_path, &options)?;
+    // Readers see committed data while the writer holds an open transaction
+    writer.execute_batch("BEGIN IMMEDIATE")?;
+    insert_log_entry(
+        &writer,
+        &LogEntry {
+            timestamp: Utc::now(),
+            level: "ERROR".to_string(),
+            source: "test".to_string(),
+            message: "uncommitted".to_string(),
+            attributes: None,
+        },
+    )?;
+    let rows: i64 = reader.query_row("SELECT COUNT(*) FROM log_entries", [], |row| row.get(0))?;
+    assert_eq!(rows, 0);
+    writer.execute_batch("COMMIT")?;
+
+    assert!(reader.execute("DELETE FROM log_entries", []).is_err());
+
+    Ok(())
+}
+
+/// Test that only one process can hold the writer lock
+#[test]
+fn test_writer_lock_is_exclusive() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+
+    let lock = try_writer_lock(&db_path)?.expect("lock should be free");
+    assert_eq!(writer_lock_holder(&db_path), Some(std::process::id()));
+    assert!(try_writer_lock(&db_path)?.is_none());
+
+    drop(lock);
+    assert!(try_writer_lock(&db_path)?.is_some());
+
+    Ok(())
+}
+
+/// Test that syswriter runs share the writer lock with each other but not
+/// with an exclusive holder, and that only one of them collects at a time
+#[test]
+fn test_shared_writer_lock() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+
+    // A watcher and a collection run together
+    let watcher = try_shared_writer_lock(&db_path)?.expect("lock should be free");
+    let collection = try_shared_writer_lock(&db_path)?.expect("shared lock should be free");
+    let collecting = try_collection_lock(&db_path)?.expect("collection lock should be free");
+
+    // A second collection skips, and rollback/vacuum/merge refuse to start
+    assert!(try_collection_lock(&db_path)?.is_none());
+    assert!(try_writer_lock(&db_path)?.is_none());
+
+    // Once the collection finishes, the watcher still keeps them out
+    drop(collecting);
+    drop(collection);
+    assert!(try_collection_lock(&db_path)?.is_some());
+    assert!(try_writer_lock(&db_path)?.is_none());
+
+    // And an exclusive holder keeps syswriter out
+    drop(watcher);
+    let exclusive = try_writer_lock(&db_path)?.expect("lock should be free");
+    assert!(try_shared_writer_lock(&db_path)?.is_none());
+    drop(exclusive);
+    assert!(try_shared_writer_lock(&db_path)?.is_some());
+
+    Ok(())
+}
+
+/// Test that new databases, their sidecars and the lock file are owner-only,
+/// and that looser permissions on an existing database are reported
+#[cfg(unix)]
+#[test]
+fn test_database_file_permissions() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use systers::db::{loose_permissions, sidecar_paths, writer_lock_path};
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+
+    let conn = init_database(&db_path)?;
+    let _lock = try_writer_lock(&db_path)?.expect("lock should be free");
+    let mut files = vec![db_path.clone(), writer_lock_path(&db_path)];
+    files.extend(sidecar_paths(&db_path).into_iter().filter(|p| p.exists()));
+    assert!(files.len() >= 4, "expected WAL and SHM files: {:?}", files);
+    for path in &files {
+        let mode = std::fs::metadata(path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+    }
+    assert!(loose_permissions(&db_path).is_empty());
+    drop(conn);
+
+    // Existing permissions are reported, not changed (SQLite recreates the
+    // sidecars with the database file's mode)
+    std::fs::set_permissions(&db_path, std::fs::Permissions::from_mode(0o644))?;
+    let _conn = init_database(&db_path)?;
+    let loose = loose_permissions(&db_path);
+    assert_eq!(loose[0], (db_path.clone(), 0o644));
+    assert!(loose.iter().all(|(_, mode)| *mode == 0o644));
+
+    Ok(())
+}
+
+/// Test that a batch commits all of its writes or none of them
+#[test]
+fn test_batch_commit_and_rollback() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+    let entry = LogEntry {
+        timestamp: now,
+        level: "ERROR".to_string(),
+        source: "test".to_string(),
+        message: "batched".to_string(),
+        attributes: None,
+    };
+    let range = (now - Duration::hours(1), now + Duration::hours(1));
+
+    // Dropped without commit: nothing is kept
+    {
+        let batch = Batch::begin(&conn)?;
+        batch.insert_log_entry(&entry)?;
+        batch.insert_system_check(&SystemCheckResult {
+            timestamp: now,
+            check_name: "Disk".to_string(),
+            check_type: "disk".to_string(),
+            status: CheckStatus::Ok,
+            value: None,
+            unit: None,
+            details: None,
+            message: "fine".to_string(),
+        })?;
+    }
+    assert!(query_logs(&conn, range.0, range.1, None, &HostFilter::Local)?.is_empty());
+    let checks: i64 = conn.query_row("SELECT COUNT(*) FROM system_checks", [], |row| row.get(0))?;
+    assert_eq!(checks, 0);
+
+    let batch = Batch::begin(&conn)?;
+    for _ in 0..3 {
+        batch.insert_log_entry(&entry)?;
+    }
+    batch.commit()?;
+    assert_eq!(query_logs(&conn, range.0, range.1, None, &HostFilter::Local)?.len(), 3);
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "encryption")]
+
+use anyhow::Result;
+use chrono::Utc;
+use systers::db::{
+    init_database_with_options, insert_log_entry, open_read_only, ConnectionOptions, LogEntry,
+};
+use systers::maintenance::backup_to;
+use tempfile::TempDir;
+
+fn keyed(key: &str) -> ConnectionOptions {
+    ConnectionOptions {
+        key: Some(key.to_string()),
+        ..ConnectionOptions::default()
+    }
+}
+
+/// Test that an encrypted database only opens with its key, and backups keep it
+#[test]
+fn test_encrypted_database_needs_key() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+
+    let conn = init_database_with_options(&db_path, &keyed("s3cret"))?;
+    insert_log_entry(
+        &conn,
+        &LogEntry {
+            timestamp: Utc::now(),
+            level: "ERROR".to_string(),
+            source: "auth".to_string(),
+            message: "Failed password for root".to_string(),
+            attributes: None,
+        },
+    )?;
+    let backup_path = temp_dir.path().join("backup.db");
+    backup_to(&conn, &backup_path)?;
+    drop(conn);
+
+    let header = std::fs::read(&db_path)?;
+    assert!(!header.starts_with(b"SQLite format 3"));
+
+    assert!(open_read_only(&db_path, &ConnectionOptions::default())
+        .and_then(|c| Ok(c.query_row("SELECT COUNT(*) FROM log_entries", [], |r| r.get::<_, i64>(0))?))
+        .is_err());
+    assert!(open_read_only(&db_path, &keyed("wrong")).is_err());
+
+    for path in [&db_path, &backup_path] {
+        let reader = open_read_only(path, &keyed("s3cret"))?;
+        let rows: i64 = reader.query_row("SELECT COUNT(*) FROM log_entries", [], |r| r.get(0))?;
+        assert_eq!(rows, 1);
+    }
+
+    Ok(())
+}
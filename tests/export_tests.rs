@@ -0,0 +1,217 @@
+mod common;
+
+use anyhow::Result;
+use arrow_array::{Array, StringArray, TimestampSecondArray};
+use chrono::{Duration, Local, TimeZone, Utc};
+use common::sample_metrics;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use systers::db::{
+    init_database, insert_log_entry, insert_metrics, insert_system_check, CheckStatus, LogEntry,
+    HostFilter, SystemCheckResult,
+};
+use systers::export::{export_table, parse_time_bound, DataFormat, ExportTable};
+use systers::timezone::DisplayTimezone;
+use tempfile::TempDir;
+
+/// Test that CSV export is oldest first, limited to the range and quotes messages
+#[test]
+fn test_export_metrics_and_logs_csv() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let start = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    for i in 0..3 {
+        insert_metrics(&conn, &sample_metrics(start + Duration::hours(i), 10.0 * i as f32))?;
+    }
+    insert_log_entry(
+        &conn,
+        &LogEntry {
+            timestamp: start,
+            level: "ERROR".to_string(),
+            source: "app".to_string(),
+            message: "failed, retrying\n  at main.rs:10".to_string(),
+            attributes: Some(serde_json::json!({"user": "alice"})),
+        },
+    )?;
+
+    let mut out = Vec::new();
+    let rows = export_table(
+        &conn,
+        ExportTable::Metrics,
+        start,
+        start + Duration::hours(1),
+        &HostFilter::Local,
+        DataFormat::Csv,
+        &mut out,
+    )?;
+    assert_eq!(rows, 2);
+    let text = String::from_utf8(out)?;
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("timestamp,cpu_usage,memory_total"));
+    assert!(lines[1].starts_with("2025-11-05T20:00:00+00:00,0,"));
+    assert!(lines[2].starts_with("2025-11-05T21:00:00+00:00,10,"));
+
+    let mut out = Vec::new();
+    export_table(
+        &conn,
+        ExportTable::Logs,
+        start,
+        start,
+        &HostFilter::Local,
+        DataFormat::Csv,
+        &mut out,
+    )?;
+    let mut reader = csv::Reader::from_reader(out.as_slice());
+    let record = reader.records().next().unwrap()?;
+    assert_eq!(&record[3], "failed, retrying\n  at main.rs:10");
+    assert_eq!(&record[4], r#"{"user":"alice"}"#);
+
+    Ok(())
+}
+
+/// Test that JSON lines export writes one object per row
+#[test]
+fn test_export_checks_jsonl() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let timestamp = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    for (status, failed) in [(CheckStatus::Ok, None), (CheckStatus::Warning, Some(2.0))] {
+        insert_system_check(
+            &conn,
+            &SystemCheckResult {
+                timestamp,
+                check_name: "nginx".to_string(),
+                check_type: "service".to_string(),
+                status,
+                value: failed,
+                unit: failed.map(|_| "failed services".to_string()),
+                details: failed.map(|_| serde_json::json!({ "failed": ["nginx", "php-fpm"] })),
+                message: format!("nginx is {}", status),
+            },
+        )?;
+    }
+
+    let mut out = Vec::new();
+    let rows = export_table(
+        &conn,
+        ExportTable::Checks,
+        timestamp,
+        timestamp,
+        &HostFilter::Local,
+        DataFormat::Jsonl,
+        &mut out,
+    )?;
+    assert_eq!(rows, 2);
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<serde_json::Result<_>>()?;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["status"], "ok");
+    assert_eq!(lines[1]["status"], "warning");
+    assert_eq!(lines[0]["value"], serde_json::Value::Null);
+    assert_eq!(lines[1]["value"], 2.0);
+    assert_eq!(lines[1]["unit"], "failed services");
+    assert_eq!(lines[1]["details"]["failed"][1], "php-fpm");
+
+    Ok(())
+}
+
+/// Test that Parquet export spans several batches and reads back with a UTC timestamp column
+#[test]
+fn test_export_logs_parquet() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let start = Utc.with_ymd_and_hms(2025, 11, 5, 0, 0, 0).unwrap();
+    let total = 10_000;
+    for i in 0..total {
+        insert_log_entry(
+            &conn,
+            &LogEntry {
+                timestamp: start + Duration::seconds(i),
+                level: "INFO".to_string(),
+                source: "app".to_string(),
+                message: format!("request {}", i),
+                attributes: None,
+            },
+        )?;
+    }
+
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("logs.parquet");
+    let rows = export_table(
+        &conn,
+        ExportTable::Logs,
+        start,
+        start + Duration::days(1),
+        &HostFilter::Local,
+        DataFormat::Parquet,
+        File::create(&path)?,
+    )?;
+    assert_eq!(rows, total as usize);
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?.build()?;
+    let mut read = 0;
+    for batch in reader {
+        let batch = batch?;
+        let timestamps = batch
+            .column_by_name("timestamp")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampSecondArray>()
+            .unwrap();
+        let messages = batch
+            .column_by_name("message")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        if read == 0 {
+            assert_eq!(timestamps.timezone(), Some("UTC"));
+            assert_eq!(timestamps.value(0), start.timestamp());
+            assert_eq!(messages.value(0), "request 0");
+        }
+        assert!(batch.column_by_name("attributes").unwrap().is_null(0));
+        read += batch.num_rows();
+    }
+    assert_eq!(read, total as usize);
+
+    Ok(())
+}
+
+/// Test the accepted forms of --since and --until
+#[test]
+fn test_parse_time_bound() -> Result<()> {
+    let now = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    let tz = DisplayTimezone::Local;
+
+    assert_eq!(
+        parse_time_bound("2025-11-05T18:30:00+02:00", now, tz)?,
+        Utc.with_ymd_and_hms(2025, 11, 5, 16, 30, 0).unwrap()
+    );
+    assert_eq!(parse_time_bound("24h", now, tz)?, now - Duration::hours(24));
+    assert_eq!(parse_time_bound("7d", now, tz)?, now - Duration::days(7));
+    assert_eq!(parse_time_bound("30m", now, tz)?, now - Duration::minutes(30));
+
+    let local = Local.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap();
+    assert_eq!(parse_time_bound("2025-11-01", now, tz)?, local.with_timezone(&Utc));
+    let local = Local.with_ymd_and_hms(2025, 11, 1, 9, 15, 0).unwrap();
+    assert_eq!(parse_time_bound("2025-11-01 09:15", now, tz)?, local.with_timezone(&Utc));
+
+    assert_eq!(parse_time_bound("1h30m", now, tz)?, now - Duration::minutes(90));
+    assert_eq!(parse_time_bound("2d ago", now, tz)?, now - Duration::days(2));
+    assert_eq!(parse_time_bound("now", now, tz)?, now);
+    let midnight = Local.from_utc_datetime(&now.naive_utc()).date_naive();
+    let midnight = Local.from_local_datetime(&midnight.and_hms_opt(0, 0, 0).unwrap()).unwrap();
+    assert_eq!(parse_time_bound("today", now, tz)?, midnight.with_timezone(&Utc));
+    assert_eq!(
+        parse_time_bound("Yesterday", now, tz)?,
+        (midnight - Duration::days(1)).with_timezone(&Utc)
+    );
+
+    assert!(parse_time_bound("5x", now, tz).is_err());
+    assert!(parse_time_bound("90", now, tz).is_err());
+    assert!(parse_time_bound("h30m", now, tz).is_err());
+    assert!(parse_time_bound("ago", now, tz).is_err());
+
+    Ok(())
+}
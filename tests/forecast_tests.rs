@@ -0,0 +1,128 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use systers::db::{init_database, insert_disk_usage, DiskUsage, HostFilter};
+use systers::forecast::{forecast_disks, theil_sen, ForecastConfidence, ForecastOptions};
+use systers::reporter::{export_full_report, generate_full_report, ExportFormat};
+
+const GB: u64 = 1 << 30;
+
+/// Test that one outlier barely moves the Theil-Sen trend
+#[test]
+fn test_theil_sen_ignores_outlier() {
+    let mut points: Vec<(f64, f64)> = (0..10).map(|x| (x as f64, 2.0 * x as f64 + 1.0)).collect();
+    points[4].1 = 100.0;
+
+    let fit = theil_sen(&points).unwrap();
+    assert_eq!(fit.slope, 2.0);
+    assert_eq!(fit.intercept, 1.0);
+    assert!(fit.slope_low <= 2.0 && fit.slope_high >= 2.0);
+
+    // No trend can be fitted through a single instant
+    assert!(theil_sen(&[(1.0, 1.0), (1.0, 2.0)]).is_none());
+}
+
+/// Test projecting a growing mount to the warning threshold and to full
+#[test]
+fn test_disk_fill_forecast() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+
+    // A week of hourly samples: /var grows 1 GB a day to 80 of 100 GB, / stays flat
+    for hour in 0..=7 * 24 {
+        let timestamp = now - Duration::hours(7 * 24 - hour);
+        insert_disk_usage(
+            &conn,
+            &DiskUsage {
+                timestamp,
+                mount_point: "/var".to_string(),
+                filesystem: "ext4".to_string(),
+                total_bytes: 100 * GB,
+                used_bytes: 73 * GB + hour as u64 * GB / 24,
+            },
+        )?;
+        insert_disk_usage(
+            &conn,
+            &DiskUsage {
+                timestamp,
+                mount_point: "/".to_string(),
+                filesystem: "ext4".to_string(),
+                total_bytes: 50 * GB,
+                used_bytes: 20 * GB,
+            },
+        )?;
+    }
+
+    let options = ForecastOptions::default();
+    let forecast = forecast_disks(&conn, now, &options, &HostFilter::Local)?.unwrap();
+    assert_eq!(forecast.mounts.len(), 2);
+
+    let root = &forecast.mounts[0];
+    assert_eq!(root.mount_point, "/");
+    assert_eq!(root.growth_per_day, 0.0);
+    assert!(root.full.is_none() && !root.flagged);
+
+    let var = &forecast.mounts[1];
+    assert_eq!(var.samples, 169);
+    assert!((var.growth_per_day - GB as f64).abs() < 1e6);
+    assert_eq!(var.confidence, ForecastConfidence::High);
+    let full = var.full.as_ref().unwrap();
+    assert!((full.days - 20.0).abs() < 0.01);
+    assert!(full.earliest_days <= full.days && full.latest_days.unwrap() >= full.days);
+    assert!((var.warning.as_ref().unwrap().days - 5.0).abs() < 0.01);
+    assert!(var.flagged);
+    assert_eq!(forecast.flagged().len(), 1);
+
+    // A shorter horizon no longer flags it
+    let options = ForecastOptions {
+        horizon: Duration::days(14),
+        ..ForecastOptions::default()
+    };
+    assert!(!forecast_disks(&conn, now, &options, &HostFilter::Local)?.unwrap().mounts[1].flagged);
+
+    let report = generate_full_report(&conn, 24)?;
+    assert!(report
+        .metrics
+        .issues
+        .iter()
+        .any(|i| i.message.contains("DISK FILLING UP: /var projected to be full in 20.0 days")));
+    let text = export_full_report(&report, ExportFormat::Text)?;
+    assert!(text.contains("DISK FORECAST"));
+    assert!(text.contains("🔴 /var (ext4): 80.0% of 100.0 GB used"));
+    assert!(text.contains("Growth: 1.0 GB/day"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&export_full_report(&report, ExportFormat::Json)?)?;
+    assert_eq!(json["disk_forecast"]["mounts"][1]["confidence"], "high");
+    assert_eq!(json["disk_forecast"]["mounts"][1]["flagged"], true);
+
+    Ok(())
+}
+
+/// Test that near-flat growth is not projected centuries ahead
+#[test]
+fn test_near_flat_growth_is_not_projected() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+
+    // 4 KiB more every 12 hours on a half-full 4 TB disk: full in about 3 * 10^8 days
+    for step in 0..3 {
+        insert_disk_usage(
+            &conn,
+            &DiskUsage {
+                timestamp: now - Duration::hours(24 - 12 * step),
+                mount_point: "/srv".to_string(),
+                filesystem: "xfs".to_string(),
+                total_bytes: 4096 * GB,
+                used_bytes: 2048 * GB + step as u64 * 4096,
+            },
+        )?;
+    }
+
+    let options = ForecastOptions::default();
+    let forecast = forecast_disks(&conn, now, &options, &HostFilter::Local)?.unwrap();
+    let srv = &forecast.mounts[0];
+    assert!(srv.growth_per_day > 0.0);
+    assert!(srv.warning.is_none() && srv.full.is_none() && !srv.flagged);
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::fs::File;
+use std::io::Write;
+use systers::db::{init_database, insert_kernel_event};
+use systers::kernel::{
+    boot_event, collect_kernel_events, is_kernel_log, parse_kernel_line, EVENT_BOOT,
+    EVENT_FS_ERROR, EVENT_HARDWARE_ERROR, EVENT_IO_ERROR, EVENT_OOM_KILL, EVENT_SEGFAULT,
+};
+use systers::reporter::{format_full_report, generate_full_report};
+use tempfile::TempDir;
+
+/// Test structured parsing of each kernel event type
+#[test]
+fn test_parse_kernel_lines() {
+    let now = Utc::now();
+
+    let oom = parse_kernel_line(
+        "Nov  5 20:00:01 web kernel: [12345.678901] Out of memory: Killed process 1234 (java) total-vm:8123456kB, anon-rss:4012345kB, file-rss:0kB, shmem-rss:0kB",
+        now,
+    )
+    .unwrap();
+    assert_eq!(oom.event_type, EVENT_OOM_KILL);
+    assert_eq!(oom.process.as_deref(), Some("java"));
+    assert_eq!(oom.pid, Some(1234));
+    assert_eq!(oom.details.as_ref().unwrap()["anon_rss_kb"], 4_012_345);
+    // The syslog header and monotonic prefix are stripped from the message
+    assert!(oom.message.starts_with("Out of memory"));
+
+    let segfault = parse_kernel_line(
+        "kernel: myapp[4321]: segfault at 0 ip 000055d5c0a0b1c2 sp 00007ffd1e2f3a40 error 4 in libfoo.so[7f0000000000+20000]",
+        now,
+    )
+    .unwrap();
+    assert_eq!(segfault.event_type, EVENT_SEGFAULT);
+    assert_eq!(segfault.process.as_deref(), Some("myapp"));
+    let details = segfault.details.unwrap();
+    assert_eq!(details["ip"], "000055d5c0a0b1c2");
+    assert_eq!(details["error_code"], 4);
+    assert_eq!(details["object"], "libfoo.so");
+
+    let mce = parse_kernel_line("kernel: mce: [Hardware Error]: Machine check events logged", now).unwrap();
+    assert_eq!(mce.event_type, EVENT_HARDWARE_ERROR);
+
+    let edac = parse_kernel_line("kernel: EDAC MC0: 1 CE memory read error on CPU_SrcID#0_Ha#0_Chan#1_DIMM#0", now).unwrap();
+    assert_eq!(edac.event_type, EVENT_HARDWARE_ERROR);
+    assert_eq!(edac.details.unwrap()["source"], "edac");
+
+    let io = parse_kernel_line("kernel: blk_update_request: I/O error, dev sdb, sector 123456 op 0x0:(READ)", now).unwrap();
+    assert_eq!(io.event_type, EVENT_IO_ERROR);
+    assert_eq!(io.device.as_deref(), Some("sdb"));
+    assert_eq!(io.details.unwrap()["sector"], 123_456);
+
+    let remount = parse_kernel_line("kernel: EXT4-fs (sda1): Remounting filesystem read-only", now).unwrap();
+    assert_eq!(remount.event_type, EVENT_FS_ERROR);
+    assert_eq!(remount.device.as_deref(), Some("sda1"));
+    assert_eq!(remount.details.unwrap()["remounted_read_only"], true);
+
+    // Informational filesystem messages are not events
+    assert!(parse_kernel_line("kernel: EXT4-fs (sda1): mounted filesystem with ordered data mode", now).is_none());
+    assert!(parse_kernel_line("kernel: usb 1-1: new high-speed USB device", now).is_none());
+}
+
+/// Test collection from a kernel log, de-duplication and the report section
+#[test]
+fn test_kernel_report_from_log() -> Result<()> {
+    assert!(is_kernel_log("/var/log/kern.log"));
+    assert!(!is_kernel_log("/var/log/auth.log"));
+
+    let temp_dir = TempDir::new()?;
+    let log_path = temp_dir.path().join("kern.log");
+    let mut log_file = File::create(&log_path)?;
+    let ts = (Utc::now() - Duration::minutes(5)).to_rfc3339();
+    writeln!(log_file, "{} web kernel: Out of memory: Killed process 99 (postgres) total-vm:100kB, anon-rss:2097152kB, file-rss:0kB", ts)?;
+    writeln!(log_file, "{} web kernel: EXT4-fs error (device sda1): ext4_find_entry:1455: inode #2: comm ls: reading directory lblock 0", ts)?;
+    // Only kernel lines are considered
+    writeln!(log_file, "{} web myapp[12]: I/O error, dev sda reported by app", ts)?;
+    log_file.sync_all()?;
+
+    let events = collect_kernel_events(&log_path, 1000)?;
+    assert_eq!(events.len(), 2);
+
+    let conn = init_database(":memory:")?;
+    for event in &events {
+        assert!(insert_kernel_event(&conn, event)?);
+    }
+    // The same line seen again in syslog is skipped
+    assert!(!insert_kernel_event(&conn, &events[0])?);
+
+    let report = generate_full_report(&conn, 1)?;
+    let kernel = report.kernel.as_ref().expect("kernel section");
+    assert_eq!(kernel.total_events, 2);
+    assert_eq!(kernel.count(EVENT_OOM_KILL), 1);
+    assert_eq!(kernel.count(EVENT_FS_ERROR), 1);
+
+    let text = format_full_report(&report);
+    assert!(text.contains("KERNEL EVENTS"));
+    assert!(text.contains("postgres (pid 99) killed by OOM killer (2048 MB resident)"));
+    assert!(text.contains("The OOM killer ended 1 process(es) [oom-kills]"));
+
+    Ok(())
+}
+
+/// Test that a boot is stored once even when its boot time shifts with the clock
+#[test]
+fn test_boot_is_keyed_on_boot_id() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let mut boot = boot_event();
+    assert_eq!(boot.event_type, EVENT_BOOT);
+    boot.timestamp = Utc::now() - Duration::hours(2);
+    boot.details = Some(serde_json::json!({ "kernel": "6.1.0", "boot_id": "3f1c-a" }));
+    assert!(insert_kernel_event(&conn, &boot)?);
+
+    // An NTP step moves the boot time derived from /proc/stat by a few seconds
+    boot.timestamp += Duration::seconds(3);
+    assert!(!insert_kernel_event(&conn, &boot)?);
+
+    boot.timestamp += Duration::minutes(30);
+    boot.details = Some(serde_json::json!({ "kernel": "6.1.0", "boot_id": "7d2e-b" }));
+    assert!(insert_kernel_event(&conn, &boot)?);
+
+    let report = generate_full_report(&conn, 3)?;
+    assert_eq!(report.kernel.as_ref().expect("kernel section").count(EVENT_BOOT), 2);
+
+    Ok(())
+}
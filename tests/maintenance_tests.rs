@@ -0,0 +1,126 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use common::sample_metrics;
+use rusqlite::Connection;
+use systers::db::{init_database, insert_log_entry, insert_metrics, LogEntry};
+use systers::maintenance::{backup_path, backup_to, integrity_check, table_stats, vacuum};
+use tempfile::TempDir;
+
+/// Test that an online backup holds the same data and gets a unique name
+#[test]
+fn test_backup_copies_database() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+    let timestamp = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 1).unwrap();
+
+    let conn = init_database(&db_path)?;
+    insert_metrics(&conn, &sample_metrics(timestamp, 25.0))?;
+
+    let first = backup_path(&db_path, None, "backup");
+    backup_to(&conn, &first)?;
+    let second = backup_path(&db_path, None, "backup");
+    assert_ne!(first, second);
+    assert!(first
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("systers.db.backup-"));
+
+    let copy = Connection::open(&first)?;
+    let rows: i64 = copy.query_row("SELECT COUNT(*) FROM system_metrics", [], |row| row.get(0))?;
+    assert_eq!(rows, 1);
+    assert!(integrity_check(&copy)?.is_empty());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&first)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    Ok(())
+}
+
+/// Test row counts and time spans per table, and vacuum reclaiming space
+#[test]
+fn test_stats_and_vacuum() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+    let newest = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    let oldest = newest - Duration::hours(2);
+
+    let conn = init_database(&db_path)?;
+    for i in 0..3 {
+        insert_metrics(&conn, &sample_metrics(oldest + Duration::hours(i), 25.0))?;
+    }
+    for i in 0..2000 {
+        insert_log_entry(
+            &conn,
+            &LogEntry {
+                timestamp: newest,
+                level: "ERROR".to_string(),
+                source: "syslog".to_string(),
+                message: format!("failure number {} with some padding to fill pages", i),
+                attributes: None,
+            },
+        )?;
+    }
+
+    let stats = table_stats(&conn)?;
+    let metrics = stats.iter().find(|t| t.name == "system_metrics").unwrap();
+    assert_eq!(metrics.rows, 3);
+    assert_eq!(metrics.oldest, Some(oldest));
+    assert_eq!(metrics.newest, Some(newest));
+    assert!(metrics.size_bytes > 0);
+    let logs = stats.iter().find(|t| t.name == "log_entries").unwrap();
+    assert_eq!(logs.rows, 2000);
+    assert!(logs.size_bytes > metrics.size_bytes);
+    let version = stats.iter().find(|t| t.name == "schema_version").unwrap();
+    assert_eq!(version.oldest, None);
+
+    conn.execute("DELETE FROM log_entries", [])?;
+    let (before, after) = vacuum(&conn)?;
+    assert!(after < before);
+
+    Ok(())
+}
+
+/// Test that a damaged database is reported by the integrity check
+#[test]
+fn test_integrity_check_detects_corruption() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+
+    {
+        let conn = init_database(&db_path)?;
+        assert!(integrity_check(&conn)?.is_empty());
+        conn.execute_batch(
+            "CREATE TABLE filler (value TEXT);
+             CREATE INDEX idx_filler ON filler (value);",
+        )?;
+        for i in 0..500 {
+            conn.execute(
+                "INSERT INTO filler VALUES (?1)",
+                [format!("row {:05} padding padding padding", i)],
+            )?;
+        }
+    }
+
+    // Dropping the index from the schema leaves its pages orphaned
+    {
+        let conn = Connection::open(&db_path)?;
+        conn.execute_batch(
+            "PRAGMA writable_schema = ON;
+             DELETE FROM sqlite_master WHERE name = 'idx_filler';
+             PRAGMA writable_schema = OFF;",
+        )?;
+    }
+
+    let conn = Connection::open(&db_path)?;
+    let problems = integrity_check(&conn)?;
+    assert!(!problems.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,322 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use common::sample_metrics;
+use rusqlite::Connection;
+use std::path::Path;
+use systers::collector::hostname;
+use systers::db::{
+    cleanup_merged_data, cleanup_old_data, finish_collection_run, init_database, insert_auth_event,
+    insert_metrics, query_collection_runs, query_logs, query_metrics, start_collection_run,
+    AuthEvent, Batch, CollectorOutcome, ConnectionOptions, HostFilter, LogEntry, RUN_MODE_COLLECT,
+};
+use systers::export::{export_table, DataFormat, ExportTable};
+use systers::merge::merge_database;
+use systers::migrations::migrate_to;
+use systers::reporter::{
+    format_full_report, generate_full_report_between, generate_report, generate_security_report,
+    ReportOptions,
+};
+use tempfile::TempDir;
+
+/// Write one collection run with a metric and a log entry, recorded as `host`
+fn record_run(conn: &Connection, host: &str, started: chrono::DateTime<Utc>) -> Result<()> {
+    let run_id = start_collection_run(conn, RUN_MODE_COLLECT, started, host)?;
+    let batch = Batch::begin_run(conn, run_id)?;
+    batch.insert_metrics(&sample_metrics(started, 90.0))?;
+    batch.insert_log_entry(&LogEntry {
+        timestamp: started,
+        level: "ERROR".to_string(),
+        source: "syslog".to_string(),
+        message: "disk failure".to_string(),
+        attributes: None,
+    })?;
+    batch.commit()?;
+    finish_collection_run(
+        conn,
+        run_id,
+        started,
+        &[CollectorOutcome::ok("metrics", 2)],
+        None,
+    )?;
+    Ok(())
+}
+
+fn count(conn: &Connection, sql: &str) -> Result<i64> {
+    Ok(conn.query_row(sql, [], |row| row.get(0))?)
+}
+
+fn merged_count(report: &systers::merge::MergeReport, table: &str) -> usize {
+    report
+        .tables
+        .iter()
+        .find(|t| t.name == table)
+        .unwrap()
+        .merged
+}
+
+/// Test that rows from another host are tagged, linked to their run and merged only once
+#[test]
+fn test_merge_tags_rows_and_skips_duplicates() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let started = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+
+    let source_path = temp_dir.path().join("web-02.db");
+    {
+        let source = init_database(&source_path)?;
+        record_run(&source, "web-02", started)?;
+        record_run(&source, "web-02", started + Duration::hours(1))?;
+    }
+
+    let conn = init_database(temp_dir.path().join("systers.db"))?;
+    // Same timestamp as the source, but measured on this host
+    insert_metrics(&conn, &sample_metrics(started, 10.0))?;
+
+    let report = merge_database(&conn, &source_path, None, &ConnectionOptions::default())?;
+    assert_eq!(report.host, "web-02");
+    assert!(!report.migrated);
+    assert_eq!(merged_count(&report, "collection_runs"), 2);
+    assert_eq!(merged_count(&report, "system_metrics"), 2);
+    assert_eq!(merged_count(&report, "log_entries"), 2);
+    assert_eq!(report.duplicates(), 0);
+
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM system_metrics")?, 3);
+    assert_eq!(
+        count(
+            &conn,
+            "SELECT COUNT(*) FROM system_metrics WHERE host = 'web-02'"
+        )?,
+        2
+    );
+    // Merged rows point at the merged copy of their run
+    assert_eq!(
+        count(
+            &conn,
+            "SELECT COUNT(*) FROM log_entries l
+             JOIN collection_runs r ON r.id = l.run_id
+             WHERE r.hostname = 'web-02' AND r.host = 'web-02'"
+        )?,
+        2
+    );
+
+    // Other hosts' runs do not count towards this host's collection health
+    let runs = query_collection_runs(&conn, started, started + Duration::hours(2))?;
+    assert!(runs.is_empty());
+
+    let again = merge_database(&conn, &source_path, None, &ConnectionOptions::default())?;
+    assert_eq!(again.merged(), 0);
+    assert_eq!(again.duplicates(), 6);
+
+    Ok(())
+}
+
+/// Test that an older source is migrated in a copy and left untouched
+#[test]
+fn test_merge_migrates_old_source() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let source_path = temp_dir.path().join("old.db");
+    {
+        let source = Connection::open(&source_path)?;
+        migrate_to(&source, 5, None)?;
+        source.execute_batch(
+            "INSERT INTO system_metrics (timestamp, cpu_usage, memory_total, memory_used,
+                memory_available, disk_total, disk_used, process_count,
+                load_avg_1min, load_avg_5min, load_avg_15min)
+             VALUES (1762372800, 50.0, 100, 40, 60, 100, 20, 50, 0.5, 0.4, 0.3);
+             INSERT INTO auth_events (timestamp, event_type, username, source_ip, message)
+             VALUES (1762372800, 'ssh_failed', 'root', '203.0.113.9', 'Failed password for root');",
+        )?;
+    }
+
+    let conn = init_database(temp_dir.path().join("systers.db"))?;
+
+    // Pre-ledger databases do not know their hostname
+    let error =
+        merge_database(&conn, &source_path, None, &ConnectionOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("--host"));
+
+    let report = merge_database(
+        &conn,
+        &source_path,
+        Some("old-box"),
+        &ConnectionOptions::default(),
+    )?;
+    assert_eq!(report.source_version, 5);
+    assert!(report.migrated);
+    assert_eq!(merged_count(&report, "system_metrics"), 1);
+    assert_eq!(merged_count(&report, "auth_events"), 1);
+    assert_eq!(
+        count(
+            &conn,
+            "SELECT COUNT(*) FROM auth_events WHERE host = 'old-box'"
+        )?,
+        1
+    );
+
+    let source = Connection::open(&source_path)?;
+    assert_eq!(
+        count(&source, "SELECT MAX(version) FROM schema_version")?,
+        5
+    );
+
+    Ok(())
+}
+
+/// Test that a copy of this host's own database merges back as local rows
+#[test]
+fn test_merge_own_backup_is_local() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let started = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    let db_path = temp_dir.path().join("systers.db");
+
+    let conn = init_database(&db_path)?;
+    record_run(&conn, &hostname(), started)?;
+    let backup_path = temp_dir.path().join("backup.db");
+    systers::maintenance::backup_to(&conn, &backup_path)?;
+    record_run(&conn, &hostname(), started + Duration::hours(1))?;
+    conn.execute(
+        "DELETE FROM log_entries WHERE timestamp = ?1",
+        [started.timestamp()],
+    )?;
+
+    let report = merge_database(&conn, &backup_path, None, &ConnectionOptions::default())?;
+    assert_eq!(report.host, "");
+    assert_eq!(merged_count(&report, "log_entries"), 1);
+    assert_eq!(merged_count(&report, "system_metrics"), 0);
+    assert_eq!(
+        count(&conn, "SELECT COUNT(*) FROM log_entries WHERE host = ''")?,
+        2
+    );
+
+    assert!(merge_database(
+        &conn,
+        Path::new(&db_path),
+        None,
+        &ConnectionOptions::default()
+    )
+    .is_err());
+
+    Ok(())
+}
+
+fn failed_login(timestamp: chrono::DateTime<Utc>, source_ip: &str) -> AuthEvent {
+    AuthEvent {
+        timestamp,
+        event_type: "ssh_failed".to_string(),
+        username: Some("root".to_string()),
+        source_ip: Some(source_ip.to_string()),
+        method: Some("password".to_string()),
+        command: None,
+        message: format!("Failed password for root from {}", source_ip),
+    }
+}
+
+/// Test that rows merged from another host leave this host's reports unchanged
+#[test]
+fn test_merged_rows_stay_out_of_local_reports() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let started = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    let (start, end) = (started - Duration::hours(1), started + Duration::hours(3));
+
+    let source_path = temp_dir.path().join("web-02.db");
+    {
+        let source = init_database(&source_path)?;
+        record_run(&source, "web-02", started + Duration::minutes(30))?;
+        insert_metrics(&source, &sample_metrics(started + Duration::hours(2), 99.0))?;
+        insert_auth_event(&source, &failed_login(started, "198.51.100.7"))?;
+    }
+
+    let conn = init_database(temp_dir.path().join("systers.db"))?;
+    record_run(&conn, &hostname(), started)?;
+    insert_metrics(&conn, &sample_metrics(started + Duration::hours(1), 10.0))?;
+    insert_auth_event(
+        &conn,
+        &failed_login(started + Duration::hours(1), "198.51.100.7"),
+    )?;
+
+    let snapshot = |conn: &Connection| -> Result<serde_json::Value> {
+        let (metrics, logs, checks) = generate_report(conn, start, end)?;
+        let security = generate_security_report(conn, start, end, &HostFilter::Local)?;
+        Ok(serde_json::json!([metrics, logs, checks, security]))
+    };
+    let before = snapshot(&conn)?;
+
+    let report = merge_database(&conn, &source_path, None, &ConnectionOptions::default())?;
+    assert_eq!(merged_count(&report, "system_metrics"), 2);
+    assert_eq!(merged_count(&report, "auth_events"), 1);
+
+    assert_eq!(snapshot(&conn)?, before);
+
+    Ok(())
+}
+
+/// Test that merged rows are read back by host and outlive this host's retention
+#[test]
+fn test_merged_rows_read_back_by_host() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let started = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+    let (start, end) = (started - Duration::hours(1), started + Duration::hours(1));
+
+    let source_path = temp_dir.path().join("web-02.db");
+    {
+        let source = init_database(&source_path)?;
+        record_run(&source, "web-02", started)?;
+    }
+
+    let conn = init_database(temp_dir.path().join("systers.db"))?;
+    insert_metrics(&conn, &sample_metrics(started, 10.0))?;
+    merge_database(&conn, &source_path, None, &ConnectionOptions::default())?;
+
+    let web02 = HostFilter::Named("web-02".to_string());
+    assert_eq!("web-02".parse::<HostFilter>()?, web02);
+    assert_eq!("all".parse::<HostFilter>()?, HostFilter::All);
+    assert_eq!(hostname().parse::<HostFilter>()?, HostFilter::Local);
+
+    let metrics = query_metrics(&conn, start, end, &web02)?;
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].cpu_usage, 90.0);
+    assert_eq!(query_metrics(&conn, start, end, &HostFilter::Local)?.len(), 1);
+    assert_eq!(query_metrics(&conn, start, end, &HostFilter::All)?.len(), 2);
+    assert_eq!(query_logs(&conn, start, end, Some("ERROR"), &web02)?.len(), 1);
+    assert!(query_logs(&conn, start, end, None, &HostFilter::Local)?.is_empty());
+
+    let options = ReportOptions {
+        host: web02.clone(),
+        ..ReportOptions::default()
+    };
+    let report = generate_full_report_between(&conn, start, end, &options)?;
+    assert_eq!(report.host, "web-02");
+    assert_eq!(report.metrics.max_cpu_usage, 90.0);
+    assert_eq!(report.logs.total_errors, 1);
+    assert!(format_full_report(&report).contains("Host: web-02"));
+
+    let mut out = Vec::new();
+    let rows = export_table(
+        &conn,
+        ExportTable::Metrics,
+        start,
+        end,
+        &HostFilter::All,
+        DataFormat::Csv,
+        &mut out,
+    )?;
+    assert_eq!(rows, 2);
+    let mut reader = csv::Reader::from_reader(out.as_slice());
+    assert_eq!(reader.headers()?.iter().next_back(), Some("host"));
+    let hosts: Vec<String> = reader
+        .records()
+        .map(|record| Ok(record?.iter().next_back().unwrap_or_default().to_string()))
+        .collect::<Result<_>>()?;
+    assert_eq!(hosts.len(), 2);
+    assert!(hosts.contains(&"web-02".to_string()));
+
+    // This host's retention leaves merged rows alone; they have their own
+    cleanup_old_data(&conn, 30)?;
+    assert!(query_metrics(&conn, start, end, &HostFilter::Local)?.is_empty());
+    assert_eq!(query_metrics(&conn, start, end, &web02)?.len(), 1);
+    assert_eq!(cleanup_merged_data(&conn, 30)?, 2);
+    assert!(query_metrics(&conn, start, end, &HostFilter::All)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use systers::db::{
+    init_database, insert_log_entry, insert_metrics, open_for_reading, query_logs,
+    query_system_checks, CheckStatus, ConnectionOptions, HostFilter, LogEntry, SystemMetrics,
+    SCHEMA_VERSION,
+};
+use systers::migrations::{
+    get_schema_version, migrate_to, migration_status, pending_migrations, rollback_to,
+};
+use tempfile::TempDir;
+
+fn backups_in(dir: &TempDir) -> Result<Vec<String>> {
+    Ok(std::fs::read_dir(dir.path())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".bak"))
+        .collect())
+}
+
+/// Test rolling all the way back and migrating forward again keeps the data
+#[test]
+fn test_rollback_and_migrate_round_trip() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+    let timestamp = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 1).unwrap();
+
+    let conn = init_database(&db_path)?;
+    // A fresh database is created without a backup
+    assert!(backups_in(&temp_dir)?.is_empty());
+    insert_metrics(
+        &conn,
+        &SystemMetrics {
+            timestamp,
+            cpu_usage: 42.0,
+            memory_total: 100,
+            memory_used: 50,
+            memory_available: 50,
+            disk_total: 100,
+            disk_used: 10,
+            process_count: 12,
+            load_avg_1min: 0.5,
+            load_avg_5min: 0.5,
+            load_avg_15min: 0.5,
+        },
+    )?;
+    insert_log_entry(
+        &conn,
+        &LogEntry {
+            timestamp,
+            level: "ERROR".to_string(),
+            source: "syslog".to_string(),
+            message: "disk failure".to_string(),
+            attributes: None,
+        },
+    )?;
+
+    let undone = rollback_to(&conn, 1, Some(&db_path))?;
+    assert_eq!(undone.len(), SCHEMA_VERSION as usize - 1);
+    assert_eq!(undone[0].version, SCHEMA_VERSION);
+    assert_eq!(get_schema_version(&conn)?, 1);
+    assert_eq!(backups_in(&temp_dir)?.len(), 1);
+
+    // v1 stored RFC 3339 text timestamps
+    let stored: String =
+        conn.query_row("SELECT timestamp FROM log_entries", [], |row| row.get(0))?;
+    assert_eq!(stored, "2025-11-05T20:00:01+00:00");
+
+    assert!(migration_status(&conn)?.iter().skip(1).all(|s| !s.applied));
+    assert_eq!(pending_migrations(&conn, SCHEMA_VERSION)?.len(), SCHEMA_VERSION as usize - 1);
+    drop(conn);
+
+    // Opening with the application migrates it forward again
+    let conn = init_database(&db_path)?;
+    assert_eq!(get_schema_version(&conn)?, SCHEMA_VERSION);
+    assert_eq!(backups_in(&temp_dir)?.len(), 2);
+    let logs = query_logs(&conn, timestamp, timestamp, None, &HostFilter::Local)?;
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].message, "disk failure");
+
+    Ok(())
+}
+
+/// Test migrating to an intermediate version and a failed migration rolling back
+#[test]
+fn test_migrate_to_target_version() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+
+    let applied = migrate_to(&conn, 3, None)?;
+    assert_eq!(applied.iter().map(|m| m.version).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(get_schema_version(&conn)?, 3);
+
+    // The v4 step fails if the column already exists; the transaction is undone
+    conn.execute("ALTER TABLE log_entries ADD COLUMN attributes TEXT", [])?;
+    assert!(migrate_to(&conn, SCHEMA_VERSION, None).is_err());
+    assert_eq!(get_schema_version(&conn)?, 3);
+
+    Ok(())
+}
+
+/// Test that report connections refuse a database that is not yet migrated
+#[test]
+fn test_open_for_reading_requires_current_schema() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir.path().join("systers.db");
+    let options = ConnectionOptions::default();
+
+    let conn = rusqlite::Connection::open(&db_path)?;
+    migrate_to(&conn, 3, None)?;
+    let err = open_for_reading(&db_path, &options).unwrap_err().to_string();
+    assert!(err.contains("is at schema v3"), "{}", err);
+    assert!(err.contains("run syswriter or `systers db migrate`"), "{}", err);
+
+    migrate_to(&conn, SCHEMA_VERSION, None)?;
+    assert!(open_for_reading(&db_path, &options).is_ok());
+
+    Ok(())
+}
+
+/// Test v10 converting free-text check rows into typed status, value, unit and details
+#[test]
+fn test_typed_check_migration() -> Result<()> {
+    let conn = rusqlite::Connection::open_in_memory()?;
+    migrate_to(&conn, 9, None)?;
+    conn.execute_batch(
+        "INSERT INTO system_checks (timestamp, check_name, check_type, status, value, message)
+         VALUES (100, 'Package Updates', 'package_manager', 'warning', '3', '3 updates'),
+                (100, 'Directory Sizes', 'disk_usage', 'info', '10', 'Top directories'),
+                (100, 'Custom', 'custom', 'degraded', 'eth0 down', 'Link lost'),
+                (100, 'Systemd Services', 'systemd', 'error', NULL, 'Check failed');",
+    )?;
+
+    migrate_to(&conn, 10, None)?;
+    let checks = query_system_checks(
+        &conn,
+        Utc.timestamp_opt(0, 0).unwrap(),
+        Utc.timestamp_opt(200, 0).unwrap(),
+        &HostFilter::Local,
+    )?;
+    let by_name = |name: &str| checks.iter().find(|c| c.check_name == name).unwrap();
+
+    let updates = by_name("Package Updates");
+    assert_eq!(updates.status, CheckStatus::Warning);
+    assert_eq!(updates.value, Some(3.0));
+    assert_eq!(updates.unit.as_deref(), Some("updates"));
+    assert!(updates.details.is_none());
+
+    assert_eq!(by_name("Directory Sizes").status, CheckStatus::Ok);
+
+    let custom = by_name("Custom");
+    assert_eq!(custom.status, CheckStatus::Unknown);
+    assert_eq!(custom.value, None);
+    assert_eq!(custom.details, Some(serde_json::json!({ "value": "eth0 down" })));
+
+    let failed = by_name("Systemd Services");
+    assert_eq!(failed.status, CheckStatus::Error);
+    assert_eq!((failed.value, failed.unit.as_deref()), (None, None));
+
+    // Rolling back restores the text values
+    rollback_to(&conn, 9, None)?;
+    let values: Vec<Option<String>> = conn
+        .prepare("SELECT value FROM system_checks ORDER BY id")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    assert_eq!(
+        values,
+        vec![
+            Some("3".to_string()),
+            Some("10".to_string()),
+            Some("eth0 down".to_string()),
+            None
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use systers::db::{
+    init_database, insert_disk_usage, insert_log_entry, insert_system_check, CheckStatus,
+    DiskUsage, LogEntry, SystemCheckResult,
+};
+use systers::reporter::{
+    export_full_report, generate_full_report, generate_full_report_with_options, ExportFormat,
+    ReportOptions,
+};
+use systers::system_checks::parse_reallocated_sectors;
+use systers::timeline::Severity;
+
+const GB: u64 = 1 << 30;
+
+fn check(
+    timestamp: DateTime<Utc>,
+    check_type: &str,
+    status: CheckStatus,
+    value: f64,
+    details: serde_json::Value,
+) -> SystemCheckResult {
+    SystemCheckResult {
+        timestamp,
+        check_name: check_type.to_string(),
+        check_type: check_type.to_string(),
+        status,
+        value: Some(value),
+        unit: None,
+        details: Some(details),
+        message: String::new(),
+    }
+}
+
+/// Test that check findings map to recommendations with stable ids, most severe first
+#[test]
+fn test_recommendations_from_checks() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now() - Duration::minutes(5);
+
+    insert_system_check(
+        &conn,
+        &check(
+            now,
+            "swap",
+            CheckStatus::Warning,
+            72.5,
+            json!({"total_bytes": 4 * GB, "used_bytes": 3 * GB}),
+        ),
+    )?;
+    insert_system_check(
+        &conn,
+        &check(
+            now,
+            "systemd",
+            CheckStatus::Warning,
+            2.0,
+            json!({"failed_services": 2, "failed_service_names": ["nginx.service", "cron.service"]}),
+        ),
+    )?;
+    insert_system_check(
+        &conn,
+        &check(
+            now,
+            "package_manager",
+            CheckStatus::Warning,
+            12.0,
+            json!({"updates_available": 12, "security_updates": 3, "package_manager": "apt"}),
+        ),
+    )?;
+    insert_system_check(
+        &conn,
+        &check(
+            now,
+            "disk_health",
+            CheckStatus::Ok,
+            0.0,
+            json!([
+                {"device": "/dev/sda", "health_status": "PASSED", "reallocated_sectors": 8},
+                {"device": "/dev/sdb", "health_status": "PASSED", "reallocated_sectors": 0}
+            ]),
+        ),
+    )?;
+    // /var is its own filesystem and almost full; / has room
+    for hours in 0..3 {
+        for (mount_point, used) in [("/", 20 * GB), ("/var", 96 * GB)] {
+            insert_disk_usage(
+                &conn,
+                &DiskUsage {
+                    timestamp: now - Duration::hours(hours),
+                    mount_point: mount_point.to_string(),
+                    filesystem: "ext4".to_string(),
+                    total_bytes: 100 * GB,
+                    used_bytes: used,
+                },
+            )?;
+        }
+    }
+
+    let report = generate_full_report(&conn, 24)?;
+    let ids: Vec<&str> = report.recommendations.iter().map(|r| r.id).collect();
+    assert_eq!(
+        ids,
+        [
+            "log-space",
+            "failed-units",
+            "swap-high",
+            "security-updates",
+            "smart-reallocated"
+        ]
+    );
+
+    assert_eq!(report.recommendations[0].severity, Severity::Critical);
+    assert_eq!(
+        report.recommendations[0].finding,
+        "/var/log is on /var, which is 96.0% full"
+    );
+    let units = &report.recommendations[1];
+    assert_eq!(units.severity, Severity::Critical);
+    assert_eq!(
+        units.finding,
+        "2 systemd unit(s) failed: nginx.service, cron.service"
+    );
+    assert!(units
+        .commands
+        .contains(&"sudo journalctl -u nginx.service -n 50 --no-pager".to_string()));
+    assert_eq!(
+        report.recommendations[3].commands,
+        ["sudo apt update && sudo apt upgrade"]
+    );
+    assert_eq!(
+        report.recommendations[4].finding,
+        "Disks have reallocated sectors: /dev/sda (8)"
+    );
+
+    let text = export_full_report(&report, ExportFormat::Text)?;
+    assert!(
+        text.contains("🔴 2 systemd unit(s) failed: nginx.service, cron.service [failed-units]")
+    );
+    assert!(text.contains("     $ sudo journalctl --vacuum-size=500M"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&export_full_report(&report, ExportFormat::Json)?)?;
+    assert_eq!(json["recommendations"][1]["id"], "failed-units");
+    assert_eq!(json["recommendations"][1]["severity"], "critical");
+    assert_eq!(json["recommendations"][2]["commands"][0], "free -h");
+
+    let markdown = export_full_report(&report, ExportFormat::Markdown)?;
+    assert!(markdown.contains(
+        "- 🔴 **2 systemd unit(s) failed: nginx.service, cron.service** (`failed-units`)"
+    ));
+
+    Ok(())
+}
+
+/// Test that the error count threshold from the options decides the "multiple errors" recommendation
+#[test]
+fn test_multiple_errors_threshold() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now() - Duration::minutes(5);
+    for i in 0..5 {
+        insert_log_entry(
+            &conn,
+            &LogEntry {
+                timestamp: now - Duration::minutes(i),
+                level: "ERROR".to_string(),
+                source: "syslog".to_string(),
+                message: "connection refused".to_string(),
+                attributes: None,
+            },
+        )?;
+    }
+
+    let report = generate_full_report(&conn, 24)?;
+    assert!(report.recommendations.is_empty());
+
+    let options = ReportOptions {
+        error_count: 3,
+        ..ReportOptions::default()
+    };
+    let report = generate_full_report_with_options(&conn, 24, &options)?;
+    assert_eq!(report.recommendations.len(), 1);
+    assert_eq!(report.recommendations[0].id, "log-errors");
+    assert_eq!(
+        report.recommendations[0].finding,
+        "5 errors logged (more than 3)"
+    );
+
+    Ok(())
+}
+
+/// Test reading the reallocated sector count from smartctl attribute output
+#[test]
+fn test_parse_reallocated_sectors() {
+    let output = "\
+ID# ATTRIBUTE_NAME          FLAG     VALUE WORST THRESH TYPE      UPDATED  WHEN_FAILED RAW_VALUE
+  5 Reallocated_Sector_Ct   0x0033   098   098   010    Pre-fail  Always       -       24
+  9 Power_On_Hours          0x0032   091   091   000    Old_age   Always       -       41234";
+    assert_eq!(parse_reallocated_sectors(output), Some(24));
+    assert_eq!(
+        parse_reallocated_sectors("SMART overall-health: PASSED"),
+        None
+    );
+}
@@ -1,14 +1,18 @@
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use systers::db::{init_database, insert_log_entry, insert_metrics};
-use systers::db::{LogEntry, SystemMetrics};
-use systers::reporter::{format_report, generate_report};
+use systers::db::{init_database, insert_log_entry, insert_metrics, insert_system_check};
+use systers::db::{CheckStatus, LogEntry, SystemCheckResult, SystemMetrics};
+use systers::markdown::format_markdown_summary;
+use systers::reporter::{
+    export_full_report, format_report, generate_full_report, generate_report, ExportFormat,
+};
 
 /// Test report generation with no data
 #[test]
 fn test_generate_report_no_data() -> Result<()> {
     let conn = init_database(":memory:")?;
-    let (metrics, logs, system_checks) = generate_report(&conn, 24)?;
+    let end = Utc::now();
+    let (metrics, logs, system_checks) = generate_report(&conn, end - Duration::hours(24), end)?;
 
     assert_eq!(metrics.avg_cpu_usage, 0.0);
     assert_eq!(metrics.max_cpu_usage, 0.0);
@@ -51,11 +55,13 @@ fn test_generate_report_with_data() -> Result<()> {
             level: "ERROR".to_string(),
             source: "test".to_string(),
             message: format!("Test error {}", i),
+            attributes: None,
         };
         insert_log_entry(&conn, &error)?;
     }
 
-    let (metrics, logs, _system_checks) = generate_report(&conn, 24)?;
+    let end = Utc::now();
+    let (metrics, logs, _system_checks) = generate_report(&conn, end - Duration::hours(24), end)?;
 
     assert!(metrics.avg_cpu_usage > 0.0);
     assert!(metrics.max_cpu_usage > 0.0);
@@ -87,11 +93,12 @@ fn test_issue_detection_high_cpu() -> Result<()> {
     };
     insert_metrics(&conn, &metrics)?;
 
-    let (report, _, _) = generate_report(&conn, 1)?;
+    let end = Utc::now();
+    let (report, _, _) = generate_report(&conn, end - Duration::hours(1), end)?;
 
     // Should detect high CPU issue
     assert!(!report.issues.is_empty());
-    assert!(report.issues.iter().any(|i| i.contains("CPU")));
+    assert!(report.issues.iter().any(|i| i.message.contains("CPU")));
 
     Ok(())
 }
@@ -118,11 +125,12 @@ fn test_issue_detection_high_memory() -> Result<()> {
     };
     insert_metrics(&conn, &metrics)?;
 
-    let (report, _, _) = generate_report(&conn, 1)?;
+    let end = Utc::now();
+    let (report, _, _) = generate_report(&conn, end - Duration::hours(1), end)?;
 
     // Should detect high memory issue
     assert!(!report.issues.is_empty());
-    assert!(report.issues.iter().any(|i| i.contains("MEMORY")));
+    assert!(report.issues.iter().any(|i| i.message.contains("MEMORY")));
 
     Ok(())
 }
@@ -149,11 +157,12 @@ fn test_issue_detection_high_disk() -> Result<()> {
     };
     insert_metrics(&conn, &metrics)?;
 
-    let (report, _, _) = generate_report(&conn, 1)?;
+    let end = Utc::now();
+    let (report, _, _) = generate_report(&conn, end - Duration::hours(1), end)?;
 
     // Should detect high disk issue
     assert!(!report.issues.is_empty());
-    assert!(report.issues.iter().any(|i| i.contains("DISK")));
+    assert!(report.issues.iter().any(|i| i.message.contains("DISK")));
 
     Ok(())
 }
@@ -180,7 +189,9 @@ fn test_format_report() -> Result<()> {
     };
     insert_metrics(&conn, &metrics)?;
 
-    let (metrics_report, logs_report, system_checks_report) = generate_report(&conn, 1)?;
+    let end = Utc::now();
+    let (metrics_report, logs_report, system_checks_report) =
+        generate_report(&conn, end - Duration::hours(1), end)?;
     let report = format_report(&metrics_report, &logs_report, &system_checks_report);
 
     // Verify report contains expected sections
@@ -206,7 +217,7 @@ fn test_statistics_calculations() -> Result<()> {
     let now = Utc::now();
 
     // Insert metrics with known values
-    let cpu_values = vec![10.0, 20.0, 30.0, 40.0, 50.0];
+    let cpu_values = [10.0, 20.0, 30.0, 40.0, 50.0];
     for (i, cpu) in cpu_values.iter().enumerate() {
         let metrics = SystemMetrics {
             timestamp: now - Duration::hours(i as i64),
@@ -224,7 +235,8 @@ fn test_statistics_calculations() -> Result<()> {
         insert_metrics(&conn, &metrics)?;
     }
 
-    let (report, _, _) = generate_report(&conn, 24)?;
+    let end = Utc::now();
+    let (report, _, _) = generate_report(&conn, end - Duration::hours(24), end)?;
 
     // Average CPU should be 30.0 (10+20+30+40+50)/5
     assert_eq!(report.avg_cpu_usage, 30.0);
@@ -237,3 +249,211 @@ fn test_statistics_calculations() -> Result<()> {
 
     Ok(())
 }
+
+/// Test the HTML report is self-contained, charts the period and escapes log text
+#[test]
+fn test_html_report() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+
+    for i in 0..6 {
+        insert_metrics(
+            &conn,
+            &SystemMetrics {
+                timestamp: now - Duration::minutes(i * 30),
+                cpu_usage: 95.0 - i as f32 * 10.0,
+                memory_total: 100,
+                memory_used: 40,
+                memory_available: 60,
+                disk_total: 100,
+                disk_used: 50,
+                process_count: 100,
+                load_avg_1min: 1.0,
+                load_avg_5min: 1.0,
+                load_avg_15min: 1.0,
+            },
+        )?;
+    }
+    insert_log_entry(
+        &conn,
+        &LogEntry {
+            timestamp: now,
+            level: "ERROR".to_string(),
+            source: "app".to_string(),
+            message: "bad input <script>alert(1)</script>".to_string(),
+            attributes: None,
+        },
+    )?;
+    insert_system_check(
+        &conn,
+        &SystemCheckResult {
+            timestamp: now,
+            check_name: "SMART Disk Health".to_string(),
+            check_type: "disk_health".to_string(),
+            status: CheckStatus::Critical,
+            value: Some(1.0),
+            unit: Some("failed disks".to_string()),
+            details: None,
+            message: "1 of 2 disk(s) have issues".to_string(),
+        },
+    )?;
+
+    let report = generate_full_report(&conn, 24)?;
+    assert_eq!(report.series.len(), 6);
+    assert!(report.series.windows(2).all(|p| p[0].timestamp < p[1].timestamp));
+
+    let format: ExportFormat = "html".parse()?;
+    let html = export_full_report(&report, format)?;
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<style>") && html.contains("<script>"));
+    // Nothing is loaded from elsewhere
+    assert!(!html.contains("<link") && !html.contains(" src="));
+    assert_eq!(html.matches("<svg").count(), 4);
+    assert!(html.contains("class=\"line\""));
+    assert!(html.contains("class=\"sortable\""));
+    assert!(html.contains("bad input &lt;script&gt;alert(1)&lt;/script&gt;"));
+    assert!(!html.contains("<script>alert"));
+    // High CPU and the failed disk are listed as issues
+    assert!(html.contains("<li class=\"critical\">SMART Disk Health: 1 of 2 disk(s) have issues</li>"));
+    assert!(html.contains("<li class=\"warning\">HIGH CPU USAGE"));
+    assert!(html.contains("status-critical"));
+
+    // JSON output is unchanged by the chart data
+    let json = export_full_report(&report, ExportFormat::Json)?;
+    assert!(!json.contains("\"series\""));
+    // Metric issues carry their severity instead of an icon
+    let json: serde_json::Value = serde_json::from_str(&json)?;
+    let issue = &json["metrics"]["issues"][0];
+    assert_eq!(issue["severity"], "warning");
+    assert!(issue["message"].as_str().unwrap().starts_with("HIGH CPU USAGE"));
+
+    Ok(())
+}
+
+/// Test the Markdown report uses tables and fences instead of box drawing
+#[test]
+fn test_markdown_report() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+
+    insert_metrics(
+        &conn,
+        &SystemMetrics {
+            timestamp: now,
+            cpu_usage: 97.0,
+            memory_total: 100,
+            memory_used: 40,
+            memory_available: 60,
+            disk_total: 100,
+            disk_used: 50,
+            process_count: 100,
+            load_avg_1min: 1.0,
+            load_avg_5min: 1.0,
+            load_avg_15min: 1.0,
+        },
+    )?;
+    insert_log_entry(
+        &conn,
+        &LogEntry {
+            timestamp: now,
+            level: "ERROR".to_string(),
+            source: "app".to_string(),
+            message: "panicked at ```main.rs```\n    at main\n    at start".to_string(),
+            attributes: None,
+        },
+    )?;
+    insert_system_check(
+        &conn,
+        &SystemCheckResult {
+            timestamp: now,
+            check_name: "Systemd Services".to_string(),
+            check_type: "systemd".to_string(),
+            status: CheckStatus::Warning,
+            value: Some(1.0),
+            unit: Some("failed services".to_string()),
+            details: None,
+            message: "Failed: 1 (nginx | php)".to_string(),
+        },
+    )?;
+
+    let report = generate_full_report(&conn, 24)?;
+    let markdown = export_full_report(&report, "md".parse()?)?;
+    assert!(!markdown.contains('━') && !markdown.contains('║'));
+    assert!(markdown.starts_with("# System Analysis Report"));
+    assert!(markdown.contains("| CPU | 97.0% | 97.0% | 97.0% | 97.0% | 97.0% | 97.0% | 0.0% | 90 min"));
+    // Pipes are escaped so the row keeps its columns
+    assert!(markdown.contains(
+        "| Systemd Services | ⚠️ warning | 1 failed services |"
+    ));
+    assert!(markdown.contains("Failed: 1 (nginx \\| php) |"));
+    // The fence is longer than the backticks inside the message
+    assert!(markdown.contains("````text\npanicked at ```main.rs```\n    at main\n    at start\n````"));
+    assert!(markdown.contains("- HIGH CPU USAGE: Peak CPU usage reached 97.0%"));
+
+    let summary = format_markdown_summary(&report);
+    assert!(summary.lines().count() <= 10);
+    assert!(summary.contains("Checks: ⚠️ Systemd Services"));
+    assert!(summary.contains("- HIGH CPU USAGE"));
+
+    Ok(())
+}
+
+/// Test time-weighted statistics are not skewed by a burst of samples
+#[test]
+fn test_time_weighted_statistics() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+    let sample = |minutes_ago: i64, cpu_usage: f32| SystemMetrics {
+        timestamp: now - Duration::minutes(minutes_ago),
+        cpu_usage,
+        memory_total: 100,
+        memory_used: 50,
+        memory_available: 50,
+        disk_total: 100,
+        disk_used: 50,
+        process_count: 100,
+        load_avg_1min: 1.0,
+        load_avg_5min: 1.0,
+        load_avg_15min: 1.0,
+    };
+
+    // Hourly runs at 10% CPU, then ten one-minute samples of a 100% spike
+    for hour in 1..=5 {
+        insert_metrics(&conn, &sample(hour * 60, 10.0))?;
+    }
+    for minute in 50..60 {
+        insert_metrics(&conn, &sample(minute, 100.0))?;
+    }
+
+    let end = Utc::now();
+    let (metrics, logs, checks) = generate_report(&conn, end - Duration::hours(24), end)?;
+    let cpu = &metrics.statistics["cpu_usage"];
+    assert_eq!(cpu.samples, 15);
+
+    // Four hours plus half a gap at 10%, ten minutes at 100%
+    // (an unweighted mean would be 70%)
+    let expected = (10.0 * 270.5 + 100.0 * 10.0) / 280.5;
+    assert!((cpu.avg - expected).abs() < 1e-9);
+    assert!((metrics.avg_cpu_usage as f64 - expected).abs() < 1e-4);
+    assert_eq!((cpu.min, cpu.max), (10.0, 100.0));
+    assert_eq!((cpu.p50, cpu.p90, cpu.p95, cpu.p99), (10.0, 10.0, 10.0, 100.0));
+    let variance = (270.5 * (10.0 - expected).powi(2) + 10.0 * (100.0 - expected).powi(2)) / 280.5;
+    assert!((cpu.std_dev - variance.sqrt()).abs() < 1e-9);
+
+    let above = cpu.above_threshold.as_ref().unwrap();
+    assert_eq!(above.threshold, 90.0);
+    assert!((above.minutes - 10.0).abs() < 1e-9);
+    assert!((above.percent_of_period - 10.0 / 1440.0 * 100.0).abs() < 1e-9);
+    // Process count has no threshold
+    assert!(metrics.statistics["process_count"].above_threshold.is_none());
+
+    let text = format_report(&metrics, &logs, &checks);
+    assert!(text.contains("  p50/p90/p95/p99: 10.0% / 10.0% / 10.0% / 100.0%\n"));
+    assert!(text.contains("  Above 90.0%: 10 min (0.7% of period)\n"));
+
+    let json = serde_json::to_value(&metrics)?;
+    assert_eq!(json["statistics"]["cpu_usage"]["p99"], 100.0);
+    assert_eq!(json["statistics"]["cpu_usage"]["above_threshold"]["minutes"], 10.0);
+
+    Ok(())
+}
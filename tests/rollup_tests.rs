@@ -0,0 +1,149 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, DurationRound, TimeZone, Utc};
+use common::sample_metrics;
+use systers::db::{
+    cleanup_old_data, init_database, insert_log_entry, insert_metrics, query_log_rollups,
+    query_metric_rollups, LogEntry, Resolution,
+};
+use systers::reporter::generate_report;
+use systers::rollup::{build_rollups, percentile, METRIC_CPU};
+
+fn log(timestamp: DateTime<Utc>, level: &str) -> LogEntry {
+    LogEntry {
+        timestamp,
+        level: level.to_string(),
+        source: "/var/log/syslog".to_string(),
+        message: format!("{} message", level),
+        attributes: None,
+    }
+}
+
+#[test]
+fn test_percentile() {
+    let values: Vec<f64> = (1..=100).map(f64::from).collect();
+    assert_eq!(percentile(&values, 95.0), 95.0);
+    assert_eq!(percentile(&values, 100.0), 100.0);
+    assert_eq!(percentile(&[7.0], 95.0), 7.0);
+    assert_eq!(percentile(&[], 95.0), 0.0);
+}
+
+/// Test that completed buckets are rolled up once and the open bucket is skipped
+#[test]
+fn test_build_rollups_incrementally() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let hour = Utc.with_ymd_and_hms(2025, 3, 10, 14, 0, 0).unwrap();
+
+    for (minute, cpu) in [(0, 10.0), (15, 20.0), (30, 30.0), (45, 80.0)] {
+        insert_metrics(
+            &conn,
+            &sample_metrics(hour + Duration::minutes(minute), cpu),
+        )?;
+    }
+    // Sample in the current, unfinished hour
+    insert_metrics(&conn, &sample_metrics(hour + Duration::minutes(70), 99.0))?;
+    insert_log_entry(&conn, &log(hour + Duration::minutes(5), "ERROR"))?;
+    insert_log_entry(&conn, &log(hour + Duration::minutes(6), "ERROR"))?;
+    insert_log_entry(&conn, &log(hour + Duration::minutes(7), "WARNING"))?;
+
+    let now = hour + Duration::minutes(80);
+    assert_eq!(build_rollups(&conn, now)?, (1, 0));
+    // Nothing new to build on a second run
+    assert_eq!(build_rollups(&conn, now)?, (0, 0));
+
+    let rollups = query_metric_rollups(&conn, Resolution::Hourly, hour, now)?;
+    let cpu = rollups.iter().find(|r| r.metric == METRIC_CPU).unwrap();
+    assert_eq!(cpu.bucket_start, hour);
+    assert_eq!(cpu.samples, 4);
+    assert_eq!(cpu.avg, 35.0);
+    assert_eq!(cpu.min, 10.0);
+    assert_eq!(cpu.max, 80.0);
+    assert_eq!(cpu.p95, 80.0);
+
+    let logs = query_log_rollups(&conn, Resolution::Hourly, hour, now)?;
+    assert_eq!(logs.iter().find(|r| r.level == "ERROR").unwrap().count, 2);
+    assert_eq!(logs.iter().find(|r| r.level == "WARNING").unwrap().count, 1);
+
+    // The next hour is built once it has completed, the day once it has ended
+    let next_day = Utc.with_ymd_and_hms(2025, 3, 11, 0, 30, 0).unwrap();
+    assert_eq!(build_rollups(&conn, next_day)?, (1, 1));
+    let daily = query_metric_rollups(&conn, Resolution::Daily, hour - Duration::days(1), next_day)?;
+    let cpu = daily.iter().find(|r| r.metric == METRIC_CPU).unwrap();
+    assert_eq!(cpu.samples, 5);
+    assert_eq!(cpu.max, 99.0);
+
+    Ok(())
+}
+
+/// Test that reports over long ranges fall back to rollups once raw data is gone
+#[test]
+fn test_report_reads_rollups_after_cleanup() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let now = Utc::now();
+    // Start of the current hour, so both periods land in fixed buckets
+    // whatever the minute the test runs at
+    let hour = now.duration_trunc(Duration::hours(1))?;
+
+    // An old busy period and a quiet recent one that is not rolled up yet
+    insert_metrics(&conn, &sample_metrics(hour - Duration::days(5), 95.0))?;
+    insert_metrics(
+        &conn,
+        &sample_metrics(hour - Duration::days(5) + Duration::minutes(1), 85.0),
+    )?;
+    insert_log_entry(&conn, &log(hour - Duration::days(5), "CRITICAL"))?;
+    insert_metrics(&conn, &sample_metrics(hour, 10.0))?;
+    insert_log_entry(&conn, &log(hour, "ERROR"))?;
+
+    build_rollups(&conn, now)?;
+    cleanup_old_data(&conn, 2)?;
+
+    // Short ranges still read raw samples
+    let end = Utc::now();
+    let (metrics, _, _) = generate_report(&conn, end - Duration::hours(24), end)?;
+    assert_eq!(metrics.resolution, Resolution::Raw);
+    assert_eq!(metrics.max_cpu_usage, 10.0);
+
+    let end = Utc::now();
+    let (metrics, logs, _) = generate_report(&conn, end - Duration::hours(24 * 7), end)?;
+    assert_eq!(metrics.resolution, Resolution::Hourly);
+    assert_eq!(metrics.max_cpu_usage, 95.0);
+    // Time-weighted: the old hourly bucket (avg 90) stands for its hour, the
+    // lone recent sample for 1.5 default collection intervals (90 minutes)
+    assert!((metrics.avg_cpu_usage - 42.0).abs() < 0.01);
+    let cpu = &metrics.statistics["cpu_usage"];
+    assert_eq!(cpu.samples, 3);
+    assert_eq!((cpu.min, cpu.max), (10.0, 95.0));
+    assert_eq!(logs.total_critical, 1);
+    assert_eq!(logs.total_errors, 1);
+
+    Ok(())
+}
+
+/// Test that a report starting mid-bucket does not count rolled-up samples from before its start
+#[test]
+fn test_report_skips_rollup_bucket_before_start() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let hour = Utc.with_ymd_and_hms(2025, 3, 10, 14, 0, 0).unwrap();
+
+    insert_metrics(&conn, &sample_metrics(hour + Duration::minutes(5), 95.0))?;
+    insert_log_entry(&conn, &log(hour + Duration::minutes(5), "CRITICAL"))?;
+    insert_metrics(&conn, &sample_metrics(hour + Duration::minutes(70), 40.0))?;
+    insert_log_entry(&conn, &log(hour + Duration::minutes(70), "ERROR"))?;
+
+    build_rollups(&conn, hour + Duration::hours(3))?;
+    cleanup_old_data(&conn, 2)?;
+
+    let (metrics, logs, _) = generate_report(
+        &conn,
+        hour + Duration::minutes(30),
+        hour + Duration::hours(3),
+    )?;
+    assert_eq!(metrics.resolution, Resolution::Hourly);
+    // The 14:00 bucket starts before the report and is left out
+    assert_eq!(metrics.max_cpu_usage, 40.0);
+    assert_eq!(logs.total_critical, 0);
+    assert_eq!(logs.total_errors, 1);
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use common::sample_metrics;
+use systers::db::{
+    init_database, insert_kernel_event, insert_log_entry, insert_metrics, insert_system_check,
+    CheckStatus, HostFilter, KernelEvent, LogEntry, SystemCheckResult, SystemMetrics,
+};
+use systers::kernel::{EVENT_BOOT, EVENT_OOM_KILL};
+use systers::reporter::ExportFormat;
+use systers::timeline::{build_timeline, export_timeline, EventKind, Severity};
+use systers::timezone::DisplayTimezone;
+
+fn sample(timestamp: DateTime<Utc>, memory_used: u64) -> SystemMetrics {
+    SystemMetrics {
+        cpu_usage: 10.0,
+        memory_used,
+        memory_available: 100 - memory_used,
+        ..sample_metrics(timestamp, 10.0)
+    }
+}
+
+fn check(timestamp: DateTime<Utc>, status: CheckStatus) -> SystemCheckResult {
+    SystemCheckResult {
+        timestamp,
+        check_name: "Systemd Services".to_string(),
+        check_type: "systemd".to_string(),
+        status,
+        value: None,
+        unit: None,
+        details: None,
+        message: format!("services {}", status),
+    }
+}
+
+fn kernel_event(timestamp: DateTime<Utc>, event_type: &str, process: Option<&str>) -> KernelEvent {
+    KernelEvent {
+        timestamp,
+        event_type: event_type.to_string(),
+        process: process.map(String::from),
+        pid: None,
+        device: None,
+        details: None,
+        message: format!("{} event", event_type),
+    }
+}
+
+/// Test that memory pressure, an OOM kill, an error burst and a failed check
+/// form one incident while a later reboot stands alone
+#[test]
+fn test_timeline_groups_incident() -> Result<()> {
+    let conn = init_database(":memory:")?;
+    let spike = (Utc::now() - Duration::hours(6)).duration_trunc(Duration::minutes(1))?;
+
+    // Memory reaches 98% for one sample; the first check result is before the period
+    insert_metrics(&conn, &sample(spike - Duration::hours(1), 40))?;
+    insert_metrics(&conn, &sample(spike, 98))?;
+    insert_metrics(&conn, &sample(spike + Duration::hours(1), 40))?;
+    insert_system_check(&conn, &check(spike - Duration::hours(20), CheckStatus::Ok))?;
+    insert_system_check(&conn, &check(spike - Duration::hours(1), CheckStatus::Ok))?;
+    insert_system_check(
+        &conn,
+        &check(spike + Duration::minutes(10), CheckStatus::Critical),
+    )?;
+    insert_system_check(&conn, &check(spike + Duration::hours(1), CheckStatus::Ok))?;
+
+    insert_kernel_event(
+        &conn,
+        &kernel_event(spike + Duration::minutes(3), EVENT_OOM_KILL, Some("java")),
+    )?;
+    for i in 0..6 {
+        insert_log_entry(
+            &conn,
+            &LogEntry {
+                timestamp: spike + Duration::minutes(4) + Duration::seconds(30 * i),
+                level: "ERROR".to_string(),
+                source: "syslog".to_string(),
+                message: "app.service: Main process exited".to_string(),
+                attributes: None,
+            },
+        )?;
+    }
+    // Too few errors for a burst, and far from anything else
+    insert_log_entry(
+        &conn,
+        &LogEntry {
+            timestamp: spike - Duration::hours(2),
+            level: "ERROR".to_string(),
+            source: "syslog".to_string(),
+            message: "one-off error".to_string(),
+            attributes: None,
+        },
+    )?;
+    insert_kernel_event(
+        &conn,
+        &kernel_event(spike + Duration::hours(3), EVENT_BOOT, None),
+    )?;
+
+    let end = Utc::now();
+    let timeline = build_timeline(
+        &conn,
+        end - Duration::hours(24),
+        end,
+        &HostFilter::Local,
+        Duration::minutes(15),
+        DisplayTimezone::Local,
+    )?;
+
+    assert_eq!(timeline.events.len(), 6);
+    assert_eq!(timeline.incidents.len(), 1);
+    let incident = &timeline.incidents[0];
+    assert_eq!(incident.events, 4);
+    assert_eq!(
+        (incident.start, incident.end),
+        (spike, spike + Duration::minutes(10))
+    );
+    assert_eq!(incident.severity, Severity::Critical);
+    assert_eq!(
+        incident.story,
+        "memory hit 98.0% → OOM killed java → 6 log errors → Systemd Services critical"
+    );
+
+    // The recovery an hour later is outside the incident, and so is the reboot
+    let recovery = &timeline.events[4];
+    assert_eq!(recovery.kind, EventKind::CheckChange);
+    assert_eq!(recovery.severity, Severity::Info);
+    assert!(recovery
+        .description
+        .starts_with("Systemd Services: critical -> ok"));
+    assert!(recovery.incident.is_none());
+    let reboot = &timeline.events[5];
+    assert_eq!(reboot.kind, EventKind::Reboot);
+    assert!(reboot.incident.is_none());
+
+    let text = export_timeline(&timeline, ExportFormat::Text)?;
+    assert!(text.contains("INCIDENT TIMELINE"));
+    assert!(text.contains("┌ INCIDENT 1 🔴"));
+    assert!(text.contains("│ memory hit 98.0% → OOM killed java"));
+    assert!(text.contains("Memory above 90.0% (peak 98.0%, 1 sample)"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&export_timeline(&timeline, ExportFormat::Json)?)?;
+    assert_eq!(json["incidents"][0]["events"], 4);
+    assert_eq!(json["events"][0]["kind"], "threshold");
+    assert_eq!(json["events"][0]["incident"], 1);
+    assert!(export_timeline(&timeline, ExportFormat::Html).is_err());
+
+    // A tighter gap splits the story apart
+    let timeline = build_timeline(
+        &conn,
+        end - Duration::hours(24),
+        end,
+        &HostFilter::Local,
+        Duration::minutes(2),
+        DisplayTimezone::Local,
+    )?;
+    assert_eq!(timeline.incidents.len(), 1);
+    assert_eq!(
+        timeline.incidents[0].story,
+        "OOM killed java → 6 log errors"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use chrono::{Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use systers::db::init_database;
+use systers::export::parse_time_bound;
+use systers::reporter::{export_full_report, generate_full_report_between, ReportOptions};
+use systers::timezone::DisplayTimezone;
+
+/// Test the accepted --tz forms and converting to and from wall-clock time
+#[test]
+fn test_parse_and_convert() -> Result<()> {
+    assert_eq!("local".parse::<DisplayTimezone>()?, DisplayTimezone::Local);
+    assert_eq!(
+        "utc".parse::<DisplayTimezone>()?,
+        DisplayTimezone::Named(Tz::UTC)
+    );
+    let berlin: DisplayTimezone = "Europe/Berlin".parse()?;
+    assert_eq!(berlin, DisplayTimezone::Named(Tz::Europe__Berlin));
+    let india: DisplayTimezone = "+05:30".parse()?;
+    assert_eq!(
+        india,
+        DisplayTimezone::Fixed(FixedOffset::east_opt(5 * 3600 + 1800).unwrap())
+    );
+    assert!("Mars/Olympus_Mons".parse::<DisplayTimezone>().is_err());
+    assert!("+25:00".parse::<DisplayTimezone>().is_err());
+
+    // Berlin follows daylight saving time: UTC+2 in summer, UTC+1 in winter
+    let summer = Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap();
+    let winter = Utc.with_ymd_and_hms(2025, 12, 1, 12, 0, 0).unwrap();
+    assert_eq!(
+        berlin.convert(summer).to_rfc3339(),
+        "2025-07-01T14:00:00+02:00"
+    );
+    assert_eq!(
+        berlin.convert(winter).to_rfc3339(),
+        "2025-12-01T13:00:00+01:00"
+    );
+    assert_eq!(
+        india.convert(winter).to_rfc3339(),
+        "2025-12-01T17:30:00+05:30"
+    );
+
+    let naive = NaiveDate::from_ymd_opt(2025, 7, 1)
+        .unwrap()
+        .and_hms_opt(14, 0, 0)
+        .unwrap();
+    assert_eq!(berlin.resolve(&naive), Some(summer));
+    // 02:30 is skipped when clocks go forward
+    let skipped = NaiveDate::from_ymd_opt(2025, 3, 30)
+        .unwrap()
+        .and_hms_opt(2, 30, 0)
+        .unwrap();
+    assert_eq!(berlin.resolve(&skipped), None);
+
+    Ok(())
+}
+
+/// Test that the display time zone applies to local dates in time bounds and to reports
+#[test]
+fn test_display_timezone() -> Result<()> {
+    let india: DisplayTimezone = "+05:30".parse()?;
+    let now = Utc.with_ymd_and_hms(2025, 11, 5, 20, 0, 0).unwrap();
+
+    // 20:00 UTC is already 01:30 on the 6th in UTC+05:30
+    assert_eq!(
+        parse_time_bound("today", now, india)?,
+        Utc.with_ymd_and_hms(2025, 11, 5, 18, 30, 0).unwrap()
+    );
+    assert_eq!(
+        parse_time_bound("yesterday", now, india)?,
+        Utc.with_ymd_and_hms(2025, 11, 4, 18, 30, 0).unwrap()
+    );
+    assert_eq!(
+        parse_time_bound("2025-11-05 09:00", now, india)?,
+        Utc.with_ymd_and_hms(2025, 11, 5, 3, 30, 0).unwrap()
+    );
+
+    let conn = init_database(":memory:")?;
+    let start = Utc.with_ymd_and_hms(2025, 11, 4, 18, 30, 0).unwrap();
+    let report = generate_full_report_between(
+        &conn,
+        start,
+        start + Duration::days(1),
+        &ReportOptions {
+            timezone: india,
+            ..ReportOptions::default()
+        },
+    )?;
+    let text = export_full_report(&report, "text".parse()?)?;
+    assert!(text.contains("2025-11-05 00:00:00 +05:30 to 2025-11-06 00:00:00 +05:30"));
+    let markdown = export_full_report(&report, "markdown".parse()?)?;
+    assert!(markdown.contains("2025-11-05 00:00:00 +05:30"));
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use common::sample_metrics;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+use systers::db::{CheckStatus, LogEntry, SystemCheckResult};
+use systers::tui::{draw, App, Pane};
+
+fn log(timestamp: DateTime<Utc>, level: &str, source: &str, message: &str) -> LogEntry {
+    LogEntry {
+        timestamp,
+        level: level.to_string(),
+        source: source.to_string(),
+        message: message.to_string(),
+        attributes: None,
+    }
+}
+
+fn press(app: &mut App, code: KeyCode) {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+}
+
+/// A day of 10-minute samples with a CPU spike, logs around it and elsewhere
+fn app() -> (App, DateTime<Utc>) {
+    let end = Utc::now().duration_trunc(Duration::hours(1)).unwrap();
+    let start = end - Duration::hours(24);
+    let spike = end - Duration::hours(6);
+
+    let metrics = (0..=24 * 6)
+        .map(|i| {
+            let timestamp = start + Duration::minutes(10 * i);
+            sample_metrics(timestamp, if timestamp == spike { 97.0 } else { 10.0 })
+        })
+        .collect();
+    let logs = vec![
+        log(
+            start + Duration::hours(1),
+            "WARNING",
+            "syslog",
+            "disk almost full",
+        ),
+        log(
+            spike - Duration::minutes(5),
+            "ERROR",
+            "app.log",
+            "request timed out",
+        ),
+        log(
+            spike + Duration::minutes(2),
+            "CRITICAL",
+            "syslog",
+            "worker pool exhausted",
+        ),
+        log(
+            end - Duration::hours(1),
+            "ERROR",
+            "syslog",
+            "connection refused",
+        ),
+    ];
+    let checks = vec![SystemCheckResult {
+        timestamp: spike,
+        check_name: "Systemd Services".to_string(),
+        check_type: "systemd".to_string(),
+        status: CheckStatus::Warning,
+        value: Some(1.0),
+        unit: None,
+        details: None,
+        message: "Failed: 1 (app.service)".to_string(),
+    }];
+
+    (App::new(metrics, logs, checks, start, end), spike)
+}
+
+/// Test zooming around the cursor and jumping from a CPU spike to its logs
+#[test]
+fn test_zoom_and_jump_from_spike_to_logs() {
+    let (mut app, spike) = app();
+    app.columns = 48;
+
+    // Half-hour bars over the day; the spike is the highest bar
+    let buckets = app.buckets("cpu_usage");
+    assert_eq!(buckets.len(), 48);
+    assert_eq!(buckets[app.column_of(spike)], Some(97.0));
+
+    press(&mut app, KeyCode::Char('p'));
+    assert_eq!(app.cursor, spike);
+
+    press(&mut app, KeyCode::Char('+'));
+    assert_eq!(app.view_end - app.view_start, Duration::hours(12));
+    assert!(app.view_start <= spike && spike <= app.view_end);
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char('+'));
+    press(&mut app, KeyCode::Char('+'));
+    // Never closer than an hour
+    assert_eq!(app.view_end - app.view_start, Duration::hours(1));
+    press(&mut app, KeyCode::Char('-'));
+    assert_eq!(app.view_end - app.view_start, Duration::hours(2));
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.pane, Pane::Logs);
+    let around: Vec<&str> = app
+        .filtered_logs()
+        .iter()
+        .map(|l| l.message.as_str())
+        .collect();
+    assert_eq!(around, ["request timed out", "worker pool exhausted"]);
+    // The first entry at or after the spike is selected
+    assert_eq!(app.log_selected, 1);
+
+    press(&mut app, KeyCode::Char('c'));
+    assert_eq!(app.filtered_logs().len(), 4);
+}
+
+/// Test the log level, source and search filters
+#[test]
+fn test_log_filters() {
+    let (mut app, _) = app();
+    press(&mut app, KeyCode::Char('2'));
+
+    press(&mut app, KeyCode::Char('v'));
+    assert_eq!(app.filtered_logs().len(), 3);
+    press(&mut app, KeyCode::Char('v'));
+    assert_eq!(app.filtered_logs().len(), 1);
+    press(&mut app, KeyCode::Char('v'));
+    assert_eq!(app.filtered_logs().len(), 4);
+
+    // Sources cycle in name order, then back to all
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(app.source.as_deref(), Some("app.log"));
+    assert_eq!(app.filtered_logs().len(), 1);
+    press(&mut app, KeyCode::Char('s'));
+    press(&mut app, KeyCode::Char('s'));
+    assert_eq!(app.source, None);
+
+    press(&mut app, KeyCode::Char('/'));
+    for c in "REFUSED".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    press(&mut app, KeyCode::Enter);
+    assert!(!app.searching);
+    let found: Vec<&str> = app
+        .filtered_logs()
+        .iter()
+        .map(|l| l.message.as_str())
+        .collect();
+    assert_eq!(found, ["connection refused"]);
+
+    // 'q' typed into the search does not quit
+    press(&mut app, KeyCode::Char('/'));
+    press(&mut app, KeyCode::Char('q'));
+    assert!(!app.quit);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.search, "");
+    press(&mut app, KeyCode::Char('q'));
+    assert!(app.quit);
+}
+
+/// Test drawing every pane into an in-memory terminal
+#[test]
+fn test_draw_panes() {
+    let (mut app, _) = app();
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    let screen = |terminal: &Terminal<TestBackend>| {
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+
+    terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+    // The chart width follows the terminal
+    assert_eq!(app.columns, 98);
+    let text = screen(&terminal);
+    assert!(text.contains("CPU  peak 97.0%"));
+    assert!(text.contains("per bar"));
+
+    press(&mut app, KeyCode::Tab);
+    terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+    let text = screen(&terminal);
+    assert!(text.contains("Logs: 4 of 4"));
+    assert!(text.contains("worker pool exhausted"));
+
+    press(&mut app, KeyCode::Tab);
+    terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+    let text = screen(&terminal);
+    assert!(text.contains("Check history: 1 results"));
+    assert!(text.contains("Failed: 1 (app.service)"));
+    assert_eq!(app.checks().len(), 1);
+}
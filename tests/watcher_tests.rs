@@ -0,0 +1,119 @@
+use anyhow::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use systers::config::{AlertConfig, LogSourceConfig, WatchConfig};
+use chrono::{Duration as ChronoDuration, Utc};
+use systers::db::{init_database, query_logs, HostFilter};
+use systers::watcher::{run_watch, LogTailer, LogWatcher};
+use tempfile::TempDir;
+
+fn append(path: &std::path::Path, text: &str) -> Result<()> {
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// Test that the tailer follows appends, partial lines, rotation and truncation
+#[test]
+fn test_log_tailer_follows_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let log_path = temp_dir.path().join("app.log");
+    append(&log_path, "old line that was already scanned\n")?;
+
+    let mut tailer = LogTailer::new(LogSourceConfig::plain(&log_path));
+    assert!(tailer.read_new_lines()?.is_empty());
+
+    // Unterminated lines wait for the rest of the line
+    append(&log_path, "first line\nsecond ")?;
+    assert_eq!(tailer.read_new_lines()?, vec!["first line"]);
+    append(&log_path, "line\n")?;
+    assert_eq!(tailer.read_new_lines()?, vec!["second line"]);
+
+    // Rotation: lines written to the old file are read before the new file
+    append(&log_path, "last line before rotation\n")?;
+    fs::rename(&log_path, temp_dir.path().join("app.log.1"))?;
+    append(&log_path, "first line after rotation\n")?;
+    assert_eq!(
+        tailer.read_new_lines()?,
+        vec!["last line before rotation", "first line after rotation"]
+    );
+
+    // Truncation in place restarts from the beginning
+    File::create(&log_path)?;
+    append(&log_path, "after truncate\n")?;
+    assert_eq!(tailer.read_new_lines()?, vec!["after truncate"]);
+
+    Ok(())
+}
+
+/// Test that new lines are classified, stored and alerted on in watch mode
+#[test]
+fn test_run_watch_stores_and_alerts() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let log_path = temp_dir.path().join("app.log");
+    let alert_path = temp_dir.path().join("alert.txt");
+    append(&log_path, "kernel panic from before the watcher started\n")?;
+
+    let conn = init_database(":memory:")?;
+    let mut watcher = LogWatcher::new(&[LogSourceConfig::plain(&log_path)])?;
+
+    let options = WatchConfig {
+        batch_size: 100,
+        flush_interval_secs: 60,
+    };
+    let alerts = AlertConfig {
+        command: Some(format!(
+            "printf '%s' \"$SYSTERS_ALERT_MESSAGE\" > {}",
+            alert_path.display()
+        )),
+    };
+
+    let running = Arc::new(AtomicBool::new(true));
+    let writer = {
+        let running = Arc::clone(&running);
+        let log_path = log_path.clone();
+        thread::spawn(move || -> Result<()> {
+            thread::sleep(Duration::from_millis(300));
+            append(&log_path, "ERROR: connection refused\n")?;
+            append(&log_path, "Kernel panic - not syncing: fatal exception\n")?;
+            append(&log_path, "service started normally\n")?;
+            thread::sleep(Duration::from_millis(800));
+            running.store(false, Ordering::SeqCst);
+            Ok(())
+        })
+    };
+
+    run_watch(&conn, &mut watcher, &options, &alerts, &running, None)?;
+    writer.join().unwrap()?;
+
+    let now = Utc::now();
+    let entries = query_logs(
+        &conn,
+        now - ChronoDuration::hours(1),
+        now + ChronoDuration::hours(1),
+        None,
+        &HostFilter::Local,
+    )?;
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+        .iter()
+        .any(|e| e.level == "CRITICAL" && e.message.contains("Kernel panic - not syncing")));
+    assert!(entries.iter().all(|e| !e.message.contains("before the watcher")));
+
+    // The alert command runs in the background
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !alert_path.exists() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(50));
+    }
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(
+        fs::read_to_string(&alert_path)?,
+        "Kernel panic - not syncing: fatal exception"
+    );
+
+    Ok(())
+}